@@ -0,0 +1,171 @@
+//! Retired instruction counts of the core operations, for regression
+//! tracking on machines too noisy for wall time.
+//!
+//! The counts come from the hardware counter of [`roaring_bench::perf`]
+//! rather than from a simulation under valgrind, as an iai-callgrind suite
+//! would take them: the code runs at native speed, but cache counts are not
+//! reported. Requires the `perf` feature on Linux:
+//!
+//! ```text
+//! cargo bench --features perf --bench instructions
+//! ```
+//!
+//! The input is fixed by the seed, and each operation runs against the same
+//! few sizes with a small sample, so the counts are repeatable enough to diff
+//! between runs.
+
+// Without both libraries some of the shared setup goes unused.
+#![cfg_attr(not(all(feature = "croaring", feature = "roaring")), allow(unused))]
+
+use std::time::Duration;
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
+use roaring_bench::{
+    gen::{self, Distribution},
+    harness::{Fingerprint, Group, Id},
+    perf::Instructions,
+    pool::{self, Workload},
+    with_croaring, with_roaring,
+};
+
+type Harness = Criterion<Instructions>;
+
+/// The sizes each operation is counted at.
+const SIZES: [u32; 3] = [100, 10_000, 1_000_000];
+
+/// The number of lookups performed per iteration of the contains group.
+const QUERIES: usize = 1_024;
+
+fn uniform(instance: u32) -> Workload {
+    Workload::Generated {
+        distribution: Distribution::UniformSparse,
+        instance,
+    }
+}
+
+pub fn bench_insert(c: &mut Harness) {
+    let mut group = Group::new(c, "instructions_insert");
+    group.tags(&["construct"]);
+    for size in SIZES {
+        let values = pool::get(uniform(0), size).values();
+        group.throughput(Throughput::Elements(size as u64));
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring", size), values, |b, values| {
+                b.iter(|| {
+                    let mut bm = croaring::Bitmap::create();
+                    for &v in values {
+                        bm.add(v);
+                    }
+                    bm
+                });
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring", size), values, |b, values| {
+                b.iter(|| {
+                    let mut bm = RoaringBitmap::new();
+                    for &v in values {
+                        bm.insert(v);
+                    }
+                    bm
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+pub fn bench_contains(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("instructions/bench_contains");
+
+    let mut group = Group::new(c, "instructions_contains");
+    group.tags(&["readonly", "query"]);
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    for size in SIZES {
+        let pooled = pool::get(uniform(0), size);
+        let universe = gen::universe(size, gen::DEFAULT_DENSITY);
+        let queries = (0..QUERIES)
+            .map(|_| rng.gen_range(0..universe))
+            .collect::<Vec<u32>>();
+        group.dataset(Fingerprint::of(&queries));
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring", size), pooled.croaring(), |b, bm| {
+                b.iter(|| queries.iter().filter(|v| bm.contains(**v)).count());
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring", size), pooled.roaring(), |b, bm| {
+                b.iter(|| queries.iter().filter(|v| bm.contains(**v)).count());
+            });
+        }
+    }
+    group.finish();
+}
+
+pub fn bench_set_ops(c: &mut Harness) {
+    for name in ["union", "intersection"] {
+        let union = name == "union";
+        let mut group = Group::new(c, format!("instructions_{name}"));
+        group.tags(&["readonly", "setops"]);
+        for size in SIZES {
+            let (a, b) = (pool::get(uniform(0), size), pool::get(uniform(1), size));
+            group.throughput(Throughput::Elements(size as u64 * 2));
+            with_croaring! {
+                let (a, b) = (a.croaring(), b.croaring());
+                group.bench_function(Id::new("croaring", size), |bench| {
+                    bench.iter(|| if union { a.or(b) } else { a.and(b) });
+                });
+            }
+            with_roaring! {
+                let (a, b) = (a.roaring(), b.roaring());
+                group.bench_function(Id::new("roaring", size), |bench| {
+                    bench.iter(|| if union { a | b } else { a & b });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+pub fn bench_deserialize(c: &mut Harness) {
+    let mut group = Group::new(c, "instructions_deserialize");
+    group.tags(&["serde"]);
+    for size in SIZES {
+        let pooled = pool::get(uniform(0), size);
+        with_croaring! {
+            let bytes = pooled.croaring().serialize();
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+            group.bench_with_input(Id::new("croaring", size), &bytes, |b, bytes| {
+                b.iter(|| croaring::Bitmap::try_deserialize(bytes).unwrap());
+            });
+        }
+        with_roaring! {
+            let mut bytes = Vec::new();
+            pooled.roaring().serialize_into(&mut bytes).unwrap();
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+            group.bench_with_input(Id::new("roaring", size), &bytes, |b, bytes| {
+                b.iter(|| RoaringBitmap::deserialize_from(bytes.as_slice()).unwrap());
+            });
+        }
+    }
+    group.finish();
+}
+
+fn config() -> Harness {
+    let measurement = Instructions::new().expect("failed to open perf instruction counter");
+    Criterion::default()
+        .with_measurement(measurement)
+        .sample_size(10)
+        .warm_up_time(Duration::from_millis(100))
+        .measurement_time(Duration::from_secs(1))
+}
+
+criterion_group!(
+    name = benches;
+    config = config();
+    targets = bench_insert, bench_contains, bench_set_ops, bench_deserialize
+);
+criterion_main!(benches);
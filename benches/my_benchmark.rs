@@ -1,235 +1,5899 @@
-use std::ops::BitAnd;
+// Without both libraries some of the shared setup goes unused.
+#![cfg_attr(not(all(feature = "croaring", feature = "roaring")), allow(unused))]
 
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion::{black_box, Criterion, Throughput};
+#[cfg(feature = "roaring")]
 use roaring::RoaringBitmap;
+use roaring_bench::{
+    bitmap_impl::BitmapImpl,
+    bitset::FlatBitSet,
+    bloom::BloomFilter,
+    cache, for_each_impl,
+    gen::{self, Distribution},
+    harness::{self, Fingerprint, Group, Id},
+    mix,
+    pool::{self, Pooled, Workload},
+    scale, with_croaring, with_roaring, N,
+};
 
-static N: [u32; 5] = [10, 100, 1_000, 100_000, 1_000_000];
+#[global_allocator]
+static ALLOC: roaring_bench::allocator::Tracking<std::alloc::System> =
+    roaring_bench::allocator::Tracking(std::alloc::System);
 
-pub fn bench_add(c: &mut Criterion) {
-    let mut group = c.benchmark_group("add_elements_sequential");
-    for &batch_size in &N {
-        group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = croaring::Bitmap::create();
-                b.iter(|| {
-                    for i in 0..batch_size {
-                        bm.add(i as u32);
-                    }
-                });
-            },
+/// The measurement every group is run with, selected by the `alloc-stats`
+/// and `perf` features (the former taking precedence).
+#[cfg(feature = "alloc-stats")]
+type Measure = roaring_bench::alloc_stats::Allocations;
+#[cfg(all(feature = "perf", target_os = "linux", not(feature = "alloc-stats")))]
+type Measure = roaring_bench::perf::Instructions;
+#[cfg(not(any(all(feature = "perf", target_os = "linux"), feature = "alloc-stats")))]
+type Measure = criterion::measurement::WallTime;
+
+type Harness = Criterion<Measure>;
+
+#[cfg(feature = "alloc-stats")]
+fn measurement() -> Measure {
+    roaring_bench::alloc_stats::Allocations::new()
+}
+#[cfg(all(feature = "perf", target_os = "linux", not(feature = "alloc-stats")))]
+fn measurement() -> Measure {
+    roaring_bench::perf::Instructions::new().expect("failed to open perf instruction counter")
+}
+#[cfg(not(any(all(feature = "perf", target_os = "linux"), feature = "alloc-stats")))]
+fn measurement() -> Measure {
+    criterion::measurement::WallTime
+}
+
+/// The `std::collections` participants enabled by the `baselines` feature,
+/// showing whether a compressed bitmap helps at all at a given size.
+#[cfg(feature = "baselines")]
+mod baselines {
+    use std::collections::{BTreeSet, HashSet};
+
+    use roaring_bench::harness::{Group, Id};
+
+    use super::{Measure, SetOp};
+
+    /// Insert `values` into a long-lived set, as the backends do.
+    pub fn insert(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+        group.bench_with_input(Id::new("hashset", batch_size), values, |b, values| {
+            let mut set = HashSet::new();
+            b.iter(|| {
+                for &i in values {
+                    set.insert(i);
+                }
+            });
+        });
+        group.bench_with_input(Id::new("btreeset", batch_size), values, |b, values| {
+            let mut set = BTreeSet::new();
+            b.iter(|| {
+                for &i in values {
+                    set.insert(i);
+                }
+            });
+        });
+    }
+
+    pub fn contains(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        values: &[u32],
+        queries: &[u32],
+    ) {
+        let set = values.iter().copied().collect::<HashSet<_>>();
+        group.bench_with_input(Id::new("hashset", batch_size), &set, |b, set| {
+            b.iter(|| queries.iter().filter(|v| set.contains(*v)).count());
+        });
+        let set = values.iter().copied().collect::<BTreeSet<_>>();
+        group.bench_with_input(Id::new("btreeset", batch_size), &set, |b, set| {
+            b.iter(|| queries.iter().filter(|v| set.contains(*v)).count());
+        });
+    }
+
+    /// Collect the values into a `Vec`. A `HashSet` yields them unordered.
+    pub fn collect(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+        let set = values.iter().copied().collect::<HashSet<_>>();
+        group.bench_with_input(Id::new("hashset", batch_size), &set, |b, set| {
+            b.iter(|| {
+                let _: Vec<u32> = set.iter().copied().collect();
+            });
+        });
+        let set = values.iter().copied().collect::<BTreeSet<_>>();
+        group.bench_with_input(Id::new("btreeset", batch_size), &set, |b, set| {
+            b.iter(|| {
+                let _: Vec<u32> = set.iter().copied().collect();
+            });
+        });
+    }
+
+    /// Apply `op` by reference, producing a new set.
+    pub fn set_op(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        op: SetOp,
+        a: &[u32],
+        b: &[u32],
+    ) {
+        let (a_hash, b_hash) = (
+            a.iter().copied().collect::<HashSet<_>>(),
+            b.iter().copied().collect::<HashSet<_>>(),
         );
-        group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = RoaringBitmap::new();
-                b.iter(|| {
-                    for i in 0..batch_size {
-                        bm.insert(i as u32);
-                    }
-                });
-            },
+        group.bench_function(Id::new("hashset_ref", batch_size), |bench| {
+            bench.iter(|| apply(op, &a_hash, &b_hash));
+        });
+        let (a_btree, b_btree) = (
+            a.iter().copied().collect::<BTreeSet<_>>(),
+            b.iter().copied().collect::<BTreeSet<_>>(),
         );
+        group.bench_function(Id::new("btreeset_ref", batch_size), |bench| {
+            bench.iter(|| apply(op, &a_btree, &b_btree));
+        });
+    }
+
+    fn apply<'a, T>(op: SetOp, a: &'a T, b: &'a T) -> T
+    where
+        &'a T:
+            std::ops::BitAnd<Output = T> + std::ops::Sub<Output = T> + std::ops::BitXor<Output = T>,
+    {
+        match op {
+            SetOp::Intersection => a & b,
+            SetOp::Difference => a - b,
+            SetOp::SymmetricDifference => a ^ b,
+        }
     }
-    group.finish();
 }
 
-pub fn bench_add_shuffled(c: &mut Criterion) {
-    use rand::prelude::SliceRandom;
-    let mut rng = rand::thread_rng();
+/// The uncompressed bitset participant enabled by the `dense-baselines`
+/// feature, for the dense workloads a flat bitset may serve better.
+#[cfg(feature = "dense-baselines")]
+mod dense {
+    use criterion::BatchSize;
+    use roaring_bench::{
+        bitset::FlatBitSet,
+        harness::{Group, Id},
+    };
 
-    let mut group = c.benchmark_group("add_elements_shuffled");
-    for &batch_size in &N {
-        // Shuffle insert order
-        let mut shuffled = (0..batch_size).collect::<Vec<u32>>();
-        shuffled.shuffle(&mut rng);
+    use super::Measure;
 
-        group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &_batch_size| {
-                let mut bm = croaring::Bitmap::create();
-                b.iter(|| {
-                    for i in &shuffled {
-                        bm.add(*i);
-                    }
-                });
-            },
-        );
+    /// Insert `values` into a long-lived set, as the backends do.
+    pub fn insert(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+        group.bench_with_input(Id::new("flat_bitset", batch_size), values, |b, values| {
+            let mut set = FlatBitSet::new();
+            b.iter(|| {
+                for &i in values {
+                    set.insert(i);
+                }
+            });
+        });
+    }
+
+    pub fn contains(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        values: &[u32],
+        queries: &[u32],
+    ) {
+        let set = values.iter().copied().collect::<FlatBitSet>();
+        group.bench_with_input(Id::new("flat_bitset", batch_size), &set, |b, set| {
+            b.iter(|| queries.iter().filter(|v| set.contains(**v)).count());
+        });
+    }
+
+    pub fn collect(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+        let set = values.iter().copied().collect::<FlatBitSet>();
+        group.bench_with_input(Id::new("flat_bitset", batch_size), &set, |b, set| {
+            b.iter(|| {
+                let _: Vec<u32> = set.iter().collect();
+            });
+        });
+    }
+
+    /// Apply `op` to a fresh copy of `a` per iteration, as the in-place
+    /// groups do.
+    pub fn inplace(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        op: fn(&mut FlatBitSet, &FlatBitSet),
+        a: &[u32],
+        b: &[u32],
+    ) {
+        let a = a.iter().copied().collect::<FlatBitSet>();
+        let b = b.iter().copied().collect::<FlatBitSet>();
+        group.bench_function(Id::new("flat_bitset", batch_size), |bench| {
+            bench.iter_batched_ref(|| a.clone(), |acc| op(acc, &b), BatchSize::SmallInput);
+        });
+    }
+}
+
+/// The second roaring participant enabled by the `roaring-pinned` feature,
+/// measuring another version of roaring alongside the one under test (see
+/// `Cargo.toml`).
+#[cfg(feature = "roaring-pinned")]
+mod pinned {
+    use roaring_bench::harness::{Group, Id};
+    use roaring_pinned::RoaringBitmap;
+
+    use super::{Measure, SetOp};
+
+    /// Insert `values` into a long-lived bitmap, as the backends do.
+    pub fn insert(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
         group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &_batch_size| {
+            Id::new("roaring_pinned", batch_size),
+            values,
+            |b, values| {
                 let mut bm = RoaringBitmap::new();
                 b.iter(|| {
-                    for i in &shuffled {
-                        bm.insert(*i);
+                    for &i in values {
+                        bm.insert(i);
                     }
                 });
             },
         );
     }
-    group.finish();
+
+    pub fn contains(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        values: &[u32],
+        queries: &[u32],
+    ) {
+        let bm = RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap();
+        group.bench_with_input(Id::new("roaring_pinned", batch_size), &bm, |b, bm| {
+            b.iter(|| queries.iter().filter(|v| bm.contains(**v)).count());
+        });
+    }
+
+    pub fn collect(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+        let bm = RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap();
+        group.bench_with_input(Id::new("roaring_pinned", batch_size), &bm, |b, bm| {
+            b.iter(|| {
+                let _: Vec<u32> = bm.iter().collect();
+            });
+        });
+    }
+
+    /// Apply `op` by reference, producing a new bitmap.
+    pub fn set_op(
+        group: &mut Group<'_, Measure>,
+        batch_size: u32,
+        op: SetOp,
+        a: &[u32],
+        b: &[u32],
+    ) {
+        let a = RoaringBitmap::from_sorted_iter(a.iter().copied()).unwrap();
+        let b = RoaringBitmap::from_sorted_iter(b.iter().copied()).unwrap();
+        group.bench_function(Id::new("roaring_pinned_ref", batch_size), |bench| {
+            bench.iter(|| match op {
+                SetOp::Intersection => &a & &b,
+                SetOp::Difference => &a - &b,
+                SetOp::SymmetricDifference => &a ^ &b,
+            });
+        });
+    }
 }
 
-pub fn bench_add_range(c: &mut Criterion) {
-    let mut group = c.benchmark_group("add_range");
+/// The pooled workload holding `instance` of the values of `distribution`.
+fn generated(distribution: Distribution, instance: u32) -> Workload {
+    Workload::Generated {
+        distribution,
+        instance,
+    }
+}
+
+pub fn bench_add(c: &mut Harness) {
+    let mut group = Group::new(c, "add_elements_sequential");
+    group.tags(&["construct"]);
     for &batch_size in &N {
         group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = croaring::Bitmap::create();
-                b.iter(|| {
-                    bm.add_range(0..batch_size);
-                });
-            },
+        for_each_impl!(insert_sequential_case(&mut group, batch_size));
+
+        #[cfg(feature = "baselines")]
+        baselines::insert(
+            &mut group,
+            batch_size,
+            pool::get(Workload::Sequential, batch_size).values(),
         );
-        group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = RoaringBitmap::new();
-                b.iter(|| {
-                    bm.insert_range(0..batch_size);
-                });
-            },
+        #[cfg(feature = "dense-baselines")]
+        dense::insert(
+            &mut group,
+            batch_size,
+            pool::get(Workload::Sequential, batch_size).values(),
+        );
+        #[cfg(feature = "roaring-pinned")]
+        pinned::insert(
+            &mut group,
+            batch_size,
+            pool::get(Workload::Sequential, batch_size).values(),
         );
     }
     group.finish();
+
+    for distribution in Distribution::ALL {
+        let mut group = Group::new(c, format!("add_elements_{distribution}"));
+        group.tags(&["construct", "distribution"]);
+        for &batch_size in &N {
+            let values = pool::get(generated(distribution, 0), batch_size).values();
+            group.throughput(Throughput::Elements(batch_size as u64));
+            for_each_impl!(insert_case(&mut group, batch_size, values));
+
+            #[cfg(feature = "baselines")]
+            baselines::insert(&mut group, batch_size, values);
+            #[cfg(feature = "dense-baselines")]
+            dense::insert(&mut group, batch_size, values);
+            #[cfg(feature = "roaring-pinned")]
+            pinned::insert(&mut group, batch_size, values);
+        }
+        group.finish();
+    }
 }
 
-pub fn bench_collect_uint(c: &mut Criterion) {
-    let mut group = c.benchmark_group("collect_uint");
+pub fn bench_add_shuffled(c: &mut Harness) {
+    let mut group = Group::new(c, "add_elements_shuffled");
+    group.tags(&["construct"]);
     for &batch_size in &N {
-        group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = croaring::Bitmap::create();
-                bm.add_range(0..batch_size);
-                b.iter(|| {
-                    let _: Vec<u32> = bm.iter().collect();
-                });
-            },
-        );
-        group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut bm = RoaringBitmap::new();
-                bm.insert_range(0..batch_size);
-                b.iter(|| {
-                    let _: Vec<u32> = bm.iter().collect();
-                });
-            },
-        );
+        let values = pool::get(Workload::Sequential, batch_size);
+        bench_add_shuffled_case(&mut group, batch_size, values.values());
     }
     group.finish();
+
+    for distribution in Distribution::ALL {
+        let mut group = Group::new(c, format!("add_elements_shuffled_{distribution}"));
+        group.tags(&["construct", "distribution"]);
+        for &batch_size in &N {
+            let values = pool::get(generated(distribution, 0), batch_size);
+            bench_add_shuffled_case(&mut group, batch_size, values.values());
+        }
+        group.finish();
+    }
+}
+
+/// Insert `values` into each backend in a random order.
+fn bench_add_shuffled_case(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+    use rand::prelude::SliceRandom;
+
+    // Shuffle insert order
+    let mut shuffled = values.to_vec();
+    shuffled.shuffle(&mut gen::rng("bench_add_shuffled"));
+    group.dataset(Fingerprint::of(&shuffled));
+
+    group.throughput(Throughput::Elements(batch_size as u64));
+    for_each_impl!(insert_case(group, batch_size, &shuffled));
+}
+
+/// Insert `0..batch_size` into a long-lived bitmap, one value at a time.
+fn insert_sequential_case<B: BitmapImpl>(group: &mut Group<'_, Measure>, batch_size: u32) {
+    group.bench_with_input(
+        Id::new(B::NAME, batch_size),
+        &batch_size,
+        |b, &batch_size| {
+            let mut bm = B::new();
+            b.iter(|| {
+                for i in 0..batch_size {
+                    bm.insert(i);
+                }
+            });
+        },
+    );
+}
+
+/// Insert `values` into a long-lived bitmap, one at a time in order.
+fn insert_case<B: BitmapImpl>(group: &mut Group<'_, Measure>, batch_size: u32, values: &[u32]) {
+    group.bench_with_input(Id::new(B::NAME, batch_size), values, |b, values| {
+        let mut bm = B::new();
+        b.iter(|| {
+            for &i in values {
+                bm.insert(i);
+            }
+        });
+    });
 }
 
-/// Benchmark performing a set union of two sets, both of size "batch_size / 2".
-pub fn bench_union(c: &mut Criterion) {
-    // In this case, there are no "runs" in the sets, so RLE won't help.
-    let mut group = c.benchmark_group("union_no_rle");
+pub fn bench_add_range(c: &mut Harness) {
+    let mut group = Group::new(c, "add_range");
+    group.tags(&["construct"]);
     for &batch_size in &N {
         group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let (set_a, set_b): (croaring::Bitmap, croaring::Bitmap) =
-                    (0..batch_size).partition(|v| (v % 2) == 0);
-                b.iter(|| black_box(set_a.and(&set_b)));
-            },
-        );
-        group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let (set_a, set_b): (RoaringBitmap, RoaringBitmap) =
-                    (0..batch_size).partition(|v| (v % 2) == 0);
+        for_each_impl!(insert_range_case(&mut group, batch_size));
+    }
+    group.finish();
+}
 
-                b.iter(|| black_box((&set_a).bitand(&set_b)));
-            },
-        );
+/// Insert the range `0..batch_size` into a long-lived bitmap.
+fn insert_range_case<B: BitmapImpl>(group: &mut Group<'_, Measure>, batch_size: u32) {
+    group.bench_with_input(
+        Id::new(B::NAME, batch_size),
+        &batch_size,
+        |b, &batch_size| {
+            let mut bm = B::new();
+            b.iter(|| {
+                bm.insert_range(0..batch_size);
+            });
+        },
+    );
+}
+
+/// The number of values in the bitmap the append_sorted groups append to.
+const APPEND_BASE: u32 = 1_000_000;
+
+/// The tails of the append_sorted groups, by the gap between consecutive
+/// appended values.
+const APPEND_TAILS: [(&str, u32); 2] = [("dense", 1), ("sparse", 64)];
+
+/// Appending a sorted tail of values to a bitmap.
+type AppendOp<B> = fn(&mut B, &[u32]);
+
+/// Appending increasing values past the maximum of an already populated
+/// bitmap, as when ingesting monotonic IDs, in bulk and by inserting each
+/// value individually.
+///
+/// `croaring` and `roaring` insert the values one at a time; roaring's
+/// `push` only accepts values above the current maximum, and `append`
+/// requires a sorted iterator.
+pub fn bench_append_sorted(c: &mut Harness) {
+    use criterion::BatchSize;
+
+    let base = pool::get(Workload::Sequential, APPEND_BASE);
+    for (tail_name, gap) in APPEND_TAILS {
+        let mut group = Group::new(c, format!("append_sorted_{tail_name}"));
+        group.tags(&["construct"]);
+        for &batch_size in &N {
+            let tail = (APPEND_BASE..=u32::MAX)
+                .step_by(gap as usize)
+                .take(batch_size as usize)
+                .collect::<Vec<u32>>();
+            if tail.len() < batch_size as usize {
+                continue;
+            }
+            group.dataset(Fingerprint::of(&tail));
+            group.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                let cases: [(&str, AppendOp<croaring::Bitmap>); 3] = [
+                    ("croaring", |bm, tail| tail.iter().for_each(|&v| bm.add(v))),
+                    ("croaring_add_many", |bm, tail| bm.add_many(tail)),
+                    ("croaring_extend", |bm, tail| bm.extend(tail.iter().copied())),
+                ];
+                for (name, append) in cases {
+                    group.bench_function(Id::new(name, batch_size), |b| {
+                        b.iter_batched_ref(
+                            || base.croaring().clone(),
+                            |bm| append(bm, &tail),
+                            BatchSize::LargeInput,
+                        );
+                    });
+                }
+            }
+            with_roaring! {
+                let cases: [(&str, AppendOp<RoaringBitmap>); 4] = [
+                    ("roaring", |bm, tail| tail.iter().for_each(|&v| {
+                        bm.insert(v);
+                    })),
+                    ("roaring_extend", |bm, tail| bm.extend(tail.iter().copied())),
+                    ("roaring_append", |bm, tail| {
+                        bm.append(tail.iter().copied()).expect("tail is sorted");
+                    }),
+                    ("roaring_push", |bm, tail| tail.iter().for_each(|&v| {
+                        assert!(bm.push(v), "tail is above the maximum");
+                    })),
+                ];
+                for (name, append) in cases {
+                    group.bench_function(Id::new(name, batch_size), |b| {
+                        b.iter_batched_ref(
+                            || base.roaring().clone(),
+                            |bm| append(bm, &tail),
+                            BatchSize::LargeInput,
+                        );
+                    });
+                }
+            }
+        }
+        group.finish();
+    }
+}
+
+pub fn bench_collect_uint(c: &mut Harness) {
+    let mut group = Group::new(c, "collect_uint");
+    group.tags(&["readonly", "iter"]);
+    for &batch_size in &N {
+        bench_collect_uint_case(&mut group, batch_size, Workload::Sequential);
     }
     group.finish();
 
-    // In this case, the sets are contiguous and RLE should be very effective.
-    let mut group = c.benchmark_group("union_with_rle");
+    for distribution in Distribution::ALL {
+        let mut group = Group::new(c, format!("collect_uint_{distribution}"));
+        group.tags(&["readonly", "iter", "distribution"]);
+        for &batch_size in &N {
+            bench_collect_uint_case(&mut group, batch_size, generated(distribution, 0));
+        }
+        group.finish();
+    }
+}
+
+fn bench_collect_uint_case(group: &mut Group<'_, Measure>, batch_size: u32, workload: Workload) {
+    let input = pool::get(workload, batch_size);
+    group.throughput(Throughput::Elements(batch_size as u64));
+    for_each_impl!(collect_case(group, batch_size, input));
+
+    #[cfg(feature = "baselines")]
+    baselines::collect(group, batch_size, input.values());
+    #[cfg(feature = "dense-baselines")]
+    dense::collect(group, batch_size, input.values());
+    #[cfg(feature = "roaring-pinned")]
+    pinned::collect(group, batch_size, input.values());
+}
+
+/// Collect the values of the pooled bitmap into a `Vec`.
+fn collect_case<B: BitmapImpl>(group: &mut Group<'_, Measure>, batch_size: u32, input: &Pooled) {
+    group.bench_with_input(Id::new(B::NAME, batch_size), B::pooled(input), |b, bm| {
+        b.iter(|| {
+            let _: Vec<u32> = bm.iter().collect();
+        });
+    });
+}
+
+/// The inputs of the iteration groups: sparse and dense values, and runs
+/// that croaring's `croaring_rle` participant stores as run containers.
+const ITERATE_INPUTS: [(&str, Workload); 3] = [
+    (
+        "sparse",
+        Workload::Generated {
+            distribution: Distribution::UniformSparse,
+            instance: 0,
+        },
+    ),
+    ("dense", Workload::Sequential),
+    (
+        "run_heavy",
+        Workload::Generated {
+            distribution: Distribution::RunHeavy,
+            instance: 0,
+        },
+    ),
+];
+
+/// A fold over the values of a bitmap measured by [`bench_iterate_fold`].
+#[derive(Debug, Clone, Copy)]
+enum Fold {
+    Sum,
+    Count,
+    Rev,
+}
+
+impl Fold {
+    fn name(&self) -> &'static str {
+        match self {
+            Fold::Sum => "sum",
+            Fold::Count => "count",
+            Fold::Rev => "rev",
+        }
+    }
+
+    fn apply<B: BitmapImpl>(&self, bm: &B) -> u64 {
+        match self {
+            Fold::Sum => bm.iter().map(u64::from).sum(),
+            Fold::Count => bm.iter().count() as u64,
+            Fold::Rev => bm.iter_rev().map(u64::from).sum(),
+        }
+    }
+}
+
+/// Iteration without collecting into a `Vec`, so that unlike
+/// [`bench_collect_uint`] the allocation of the output is not measured:
+/// folds over the values in both directions, and seeking to the middle.
+pub fn bench_iterate(c: &mut Harness) {
+    for (input, workload) in ITERATE_INPUTS {
+        for fold in [Fold::Sum, Fold::Count, Fold::Rev] {
+            bench_iterate_fold(
+                c,
+                &format!("iterate_{}_{input}", fold.name()),
+                workload,
+                fold,
+            );
+        }
+
+        // The position of the middle value, reached by stepping through the
+        // values before it or, where supported, by seeking to it directly.
+        let mut group = Group::new(c, format!("iterate_nth_{input}"));
+        group.tags(&["readonly", "iter"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            let mid = batch_size as usize / 2;
+            group.throughput(Throughput::Elements(mid as u64));
+            for_each_impl!(nth_case(&mut group, batch_size, pooled, mid));
+            with_croaring! {
+                let target = pooled.values()[mid];
+                group.bench_with_input(
+                    Id::new("croaring_reset_at_or_after", batch_size),
+                    pooled.croaring(),
+                    |b, bm| {
+                        b.iter(|| {
+                            let mut iter = bm.iter();
+                            iter.reset_at_or_after(target);
+                            iter.next()
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+/// The pooled bitmap and, for a library with run containers, its
+/// run-length optimised copy as the `{library}_rle` participant.
+fn with_rle<B: BitmapImpl>(pooled: &Pooled) -> Vec<(String, &B)> {
+    let mut bitmaps = vec![(B::NAME.to_string(), B::pooled(pooled))];
+    if B::RUN_CONTAINERS {
+        bitmaps.push((format!("{}_rle", B::NAME), B::pooled_rle(pooled)));
+    }
+    bitmaps
+}
+
+/// Step through the values of the pooled bitmaps to the one at `mid`.
+fn nth_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    mid: usize,
+) {
+    for (name, bm) in with_rle::<B>(pooled) {
+        group.bench_with_input(Id::new(name, batch_size), bm, |b, bm| {
+            b.iter(|| bm.iter().nth(mid));
+        });
+    }
+}
+
+/// A group timing each backend's `fold` over the values of `workload`, the
+/// result of which is kept from being optimised away.
+fn bench_iterate_fold(c: &mut Harness, name: &str, workload: Workload, fold: Fold) {
+    let mut group = Group::new(c, name);
+    group.tags(&["readonly", "iter"]);
     for &batch_size in &N {
+        let pooled = pool::get(workload, batch_size);
         group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let (mut set_a, mut set_b): (croaring::Bitmap, croaring::Bitmap) =
-                    (0..batch_size).partition(|&v| v < (batch_size / 2));
-                set_a.run_optimize();
-                set_b.run_optimize();
-                b.iter(|| black_box(set_a.and(&set_b)));
-            },
-        );
-        group.bench_with_input(
-            BenchmarkId::new("roaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let (set_a, set_b): (RoaringBitmap, RoaringBitmap) =
-                    (0..batch_size).partition(|&v| v < (batch_size / 2));
-
-                b.iter(|| black_box((&set_a).bitand(&set_b)));
-            },
-        );
+        for_each_impl!(fold_case(&mut group, batch_size, pooled, fold));
     }
     group.finish();
+}
+
+fn fold_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    fold: Fold,
+) {
+    for (name, bm) in with_rle::<B>(pooled) {
+        group.bench_with_input(Id::new(name, batch_size), bm, |b, bm| {
+            b.iter(|| black_box(fold.apply(bm)));
+        });
+    }
+}
 
-    let mut group = c.benchmark_group("rle_optimise_suitable");
+/// The number of values decoded per call by the batched participants of
+/// [`bench_iterate_batched`].
+const ITERATE_BATCH: usize = 1024;
+
+/// Summing the values decoded a batch at a time into a reusable buffer,
+/// against decoding them one at a time.
+///
+/// croaring's `next_many` decodes a whole batch per call. roaring 0.10 has
+/// no equivalent, so its batched participant fills the buffer from the
+/// ordinary iterator.
+pub fn bench_iterate_batched(c: &mut Harness) {
+    for (input, workload) in ITERATE_INPUTS {
+        let mut group = Group::new(c, format!("iterate_batched_{input}"));
+        group.tags(&["readonly", "iter"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64));
+            for_each_impl!(per_element_case(&mut group, batch_size, pooled));
+            with_croaring! {
+                group.bench_with_input(
+                    Id::new("croaring_next_many", batch_size),
+                    pooled.croaring(),
+                    |b, bm| {
+                        let mut buf = vec![0; ITERATE_BATCH];
+                        b.iter(|| {
+                            let mut iter = bm.iter();
+                            let mut sum = 0;
+                            loop {
+                                let n = iter.next_many(&mut buf);
+                                if n == 0 {
+                                    break;
+                                }
+                                sum += buf[..n].iter().map(|&v| u64::from(v)).sum::<u64>();
+                            }
+                            black_box(sum)
+                        });
+                    },
+                );
+            }
+            with_roaring! {
+                group.bench_with_input(
+                    Id::new("roaring_chunked", batch_size),
+                    pooled.roaring(),
+                    |b, bm| {
+                        let mut buf = Vec::with_capacity(ITERATE_BATCH);
+                        b.iter(|| {
+                            let mut iter = bm.iter();
+                            let mut sum = 0;
+                            loop {
+                                buf.clear();
+                                buf.extend(iter.by_ref().take(ITERATE_BATCH));
+                                if buf.is_empty() {
+                                    break;
+                                }
+                                sum += buf.iter().map(|&v| u64::from(v)).sum::<u64>();
+                            }
+                            black_box(sum)
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Sum the values of the pooled bitmap one at a time.
+fn per_element_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+) {
+    group.bench_with_input(
+        Id::new(format!("{}_per_element", B::NAME), batch_size),
+        B::pooled(pooled),
+        |b, bm| {
+            b.iter(|| black_box(Fold::Sum.apply(bm)));
+        },
+    );
+}
+
+/// A constant-size query of a single bitmap.
+type AccessorOp<B> = fn(&B) -> u64;
+
+/// The minimum, maximum, cardinality and emptiness of a bitmap, whose cost
+/// (the cardinality in particular) depends on whether a backend stores the
+/// answer or derives it from the containers.
+pub fn bench_accessors(c: &mut Harness) {
+    bench_accessor(
+        c,
+        "min",
+        #[cfg(feature = "croaring")]
+        |bm| bm.minimum().map_or(0, u64::from),
+        #[cfg(feature = "roaring")]
+        |bm| bm.min().map_or(0, u64::from),
+    );
+    bench_accessor(
+        c,
+        "max",
+        #[cfg(feature = "croaring")]
+        |bm| bm.maximum().map_or(0, u64::from),
+        #[cfg(feature = "roaring")]
+        |bm| bm.max().map_or(0, u64::from),
+    );
+    bench_accessor(
+        c,
+        "len",
+        #[cfg(feature = "croaring")]
+        |bm| bm.cardinality(),
+        #[cfg(feature = "roaring")]
+        |bm| bm.len(),
+    );
+    bench_accessor(
+        c,
+        "is_empty",
+        #[cfg(feature = "croaring")]
+        |bm| u64::from(bm.is_empty()),
+        #[cfg(feature = "roaring")]
+        |bm| u64::from(bm.is_empty()),
+    );
+}
+
+fn bench_accessor(
+    c: &mut Harness,
+    name: &str,
+    #[cfg(feature = "croaring")] croaring_op: AccessorOp<croaring::Bitmap>,
+    #[cfg(feature = "roaring")] roaring_op: AccessorOp<RoaringBitmap>,
+) {
+    let mut variants = vec![(name.to_string(), Workload::Sequential)];
+    for distribution in Distribution::ALL {
+        variants.push((format!("{name}_{distribution}"), generated(distribution, 0)));
+    }
+    for (group_name, workload) in variants {
+        let mut group = Group::new(c, group_name);
+        group.tags(&["readonly", "query"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            with_croaring! {
+                group.bench_with_input(
+                    Id::new("croaring", batch_size),
+                    pooled.croaring(),
+                    |b, bm| {
+                        b.iter(|| croaring_op(bm));
+                    },
+                );
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring", batch_size), pooled.roaring(), |b, bm| {
+                    b.iter(|| roaring_op(bm));
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Summarising the containers of a bitmap, as a monitoring endpoint would:
+/// croaring's `statistics()`, before and after `run_optimize`, against a
+/// scan of roaring's values (which has no equivalent call).
+///
+/// Each case is labelled with the container breakdown of its input, such
+/// as `1000_4a_0b` for 4 array and no bitset containers. The run-optimized
+/// croaring bitmap adds its run container count (`_2r`).
+pub fn bench_statistics(c: &mut Harness) {
+    for distribution in Distribution::ALL {
+        let mut group = Group::new(c, format!("statistics_{distribution}"));
+        group.tags(&["readonly", "distribution"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            let (array, bitset) = container_breakdown(pooled.values().iter().copied());
+            let label = format!("{batch_size}_{array}a_{bitset}b");
+            with_croaring! {
+                group.bench_with_input(Id::new("croaring", &label), pooled.croaring(), |b, bm| {
+                    b.iter(|| bm.statistics());
+                });
+
+                let bm = pooled.croaring_rle();
+                let stats = bm.statistics();
+                let rle_label = format!(
+                    "{batch_size}_{}a_{}b_{}r",
+                    stats.n_array_containers, stats.n_bitset_containers, stats.n_run_containers,
+                );
+                group.bench_with_input(Id::new("croaring_rle", rle_label), bm, |b, bm| {
+                    b.iter(|| bm.statistics());
+                });
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring", &label), pooled.roaring(), |b, bm| {
+                    b.iter(|| container_breakdown(bm.iter()));
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// The number of (array, bitset) containers holding the sorted `values`,
+/// without run containers.
+fn container_breakdown(values: impl Iterator<Item = u32>) -> (u64, u64) {
+    let (mut array, mut bitset) = (0, 0);
+    let mut classify = |cardinality: u64| match cardinality {
+        0 => {}
+        n if n <= gen::ARRAY_MAX as u64 => array += 1,
+        _ => bitset += 1,
+    };
+    let (mut key, mut cardinality) = (None, 0);
+    for v in values {
+        if key != Some(v >> 16) {
+            classify(cardinality);
+            key = Some(v >> 16);
+            cardinality = 0;
+        }
+        cardinality += 1;
+    }
+    classify(cardinality);
+    (array, bitset)
+}
+
+/// The percentages of the values of one operand of the union and
+/// intersection groups that are also in the other.
+const OVERLAPS: [u32; 3] = [0, 50, 100];
+
+/// Set union and intersection of two sets, both of size "batch_size / 2",
+/// sharing each of the [`OVERLAPS`] of their values, along with the cost of
+/// `run_optimize` itself.
+///
+/// The interleaved operands of the `no_rle` groups leave run containers
+/// nothing to compress, while the contiguous ranges of the `with_rle` groups
+/// (run-optimized by croaring) compress to a run each.
+pub fn bench_union(c: &mut Harness) {
+    bench_overlap_op(c, OverlapOp::Union);
+    bench_overlap_op(c, OverlapOp::Intersection);
+
+    let mut group = Group::new(c, "rle_optimise_suitable");
+    group.tags(&["rle"]);
     for &batch_size in &N {
         group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut x = (0..batch_size).collect::<croaring::Bitmap>();
-                b.iter(|| black_box(x.run_optimize()));
-            },
-        );
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut x = (0..batch_size).collect::<croaring::Bitmap>();
+                    b.iter(|| black_box(x.run_optimize()));
+                },
+            );
+        }
         // roaring does not support RLE
     }
     group.finish();
 
-    let mut group = c.benchmark_group("rle_optimise_unsuitable");
+    let mut group = Group::new(c, "rle_optimise_unsuitable");
+    group.tags(&["rle"]);
     for &batch_size in &N {
         group.throughput(Throughput::Elements(batch_size as u64));
-        group.bench_with_input(
-            BenchmarkId::new("croaring", batch_size),
-            &batch_size,
-            |b, &batch_size| {
-                let mut x = (0..)
-                    .filter(|x| (x % 2) == 0)
-                    .take(batch_size as _)
-                    .collect::<croaring::Bitmap>();
-                b.iter(|| black_box(x.run_optimize()));
-            },
-        );
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut x = (0..)
+                        .filter(|x| (x % 2) == 0)
+                        .take(batch_size as _)
+                        .collect::<croaring::Bitmap>();
+                    b.iter(|| black_box(x.run_optimize()));
+                },
+            );
+        }
         // roaring does not support RLE
     }
     group.finish();
 }
 
-criterion_group!(
-    benches,
-    bench_add,
-    bench_add_range,
-    bench_add_shuffled,
-    bench_collect_uint,
-    bench_union,
-);
-criterion_main!(benches);
+/// The set operation of the overlap groups.
+#[derive(Debug, Clone, Copy)]
+enum OverlapOp {
+    Union,
+    Intersection,
+}
+
+impl OverlapOp {
+    fn name(&self) -> &'static str {
+        match self {
+            OverlapOp::Union => "union",
+            OverlapOp::Intersection => "intersection",
+        }
+    }
+
+    fn apply<B: BitmapImpl>(&self, a: &B, b: &B) -> B {
+        match self {
+            OverlapOp::Union => a.union(b),
+            OverlapOp::Intersection => a.intersect(b),
+        }
+    }
+}
+
+/// One group of `op` per operand layout and [`OVERLAPS`], named
+/// `{op}_{layout}_overlap_{percent}`, and its `_cold` variant (see
+/// [`roaring_bench::cache`]).
+fn bench_overlap_op(c: &mut Harness, op: OverlapOp) {
+    let name = op.name();
+    for (layout, rle) in [("no_rle", false), ("with_rle", true)] {
+        for overlap in OVERLAPS {
+            for &cold in cache::variants() {
+                let mut group = Group::new(
+                    c,
+                    cache::group_name(format!("{name}_{layout}_overlap_{overlap}"), cold),
+                );
+                group.tags(&["readonly", "setops"]);
+                let (workload_a, workload_b) = if rle {
+                    group.tags(&["rle"]);
+                    (Workload::LowerHalf, Workload::ShiftedHalf(overlap))
+                } else {
+                    (Workload::AlternateEven, Workload::AlternateOverlap(overlap))
+                };
+                for &batch_size in &N {
+                    let set_a = pool::get(workload_a, batch_size);
+                    let set_b = pool::get(workload_b, batch_size);
+                    group.throughput(Throughput::Elements(batch_size as u64));
+                    for_each_impl!(overlap_case(
+                        &mut group, batch_size, op, set_a, set_b, rle, cold
+                    ));
+                }
+                group.finish();
+            }
+        }
+    }
+}
+
+/// `op` on the pooled bitmaps `a` and `b`, after run-length optimisation if
+/// `rle`.
+fn overlap_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    op: OverlapOp,
+    a: &Pooled,
+    b: &Pooled,
+    rle: bool,
+    cold: bool,
+) {
+    let (a, b) = if rle {
+        (B::pooled_rle(a), B::pooled_rle(b))
+    } else {
+        (B::pooled(a), B::pooled(b))
+    };
+    group.bench_function(Id::new(B::NAME, batch_size), |bench| {
+        cache::iter(bench, cold, || op.apply(a, b));
+    });
+}
+
+/// The memory layout of an Arrow `UInt32Array`: a contiguous values buffer
+/// and an optional LSB-ordered validity bitmap where a set bit marks a
+/// non-null slot.
+struct ArrowUInt32 {
+    values: Vec<u32>,
+    validity: Option<Vec<u8>>,
+}
+
+impl ArrowUInt32 {
+    fn is_valid(&self, i: usize) -> bool {
+        match &self.validity {
+            Some(v) => v[i / 8] & (1 << (i % 8)) != 0,
+            None => true,
+        }
+    }
+
+    fn iter_valid(&self) -> impl Iterator<Item = u32> + '_ {
+        self.values
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| self.is_valid(*i))
+            .map(|(_, v)| *v)
+    }
+}
+
+/// Benchmark building a bitmap from the IDs held in an Arrow array, as handed
+/// over by analytics engines.
+pub fn bench_from_arrow(c: &mut Harness) {
+    use rand::prelude::SliceRandom;
+    let mut rng = gen::rng("bench_from_arrow");
+
+    let mut group = Group::new(c, "from_arrow_uint32");
+    group.tags(&["construct", "interop"]);
+    for &batch_size in &N {
+        let mut values = (0..batch_size).collect::<Vec<u32>>();
+        values.shuffle(&mut rng);
+        group.dataset(Fingerprint::of(&values));
+
+        // Every 10th slot is null.
+        let mut validity = vec![0xFF_u8; (batch_size as usize).div_ceil(8)];
+        for i in (0..batch_size as usize).step_by(10) {
+            validity[i / 8] &= !(1 << (i % 8));
+        }
+
+        let no_nulls = ArrowUInt32 {
+            values: values.clone(),
+            validity: None,
+        };
+        let with_nulls = ArrowUInt32 {
+            values,
+            validity: Some(validity),
+        };
+
+        group.throughput(Throughput::Elements(batch_size as u64));
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring", batch_size), &no_nulls, |b, array| {
+                b.iter(|| black_box(croaring::Bitmap::of(&array.values)));
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring", batch_size), &no_nulls, |b, array| {
+                b.iter(|| black_box(array.values.iter().collect::<RoaringBitmap>()));
+            });
+        }
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring_skip_nulls", batch_size),
+                &with_nulls,
+                |b, array| {
+                    b.iter(|| black_box(array.iter_valid().collect::<croaring::Bitmap>()));
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new("roaring_skip_nulls", batch_size),
+                &with_nulls,
+                |b, array| {
+                    b.iter(|| black_box(array.iter_valid().collect::<RoaringBitmap>()));
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark applying a deletion vector to a batch of rows, either by probing
+/// the bitmap for every row or by iterating the complement of the deleted
+/// set.
+pub fn bench_deletion_vector(c: &mut Harness) {
+    use rand::prelude::SliceRandom;
+    let mut rng = gen::rng("bench_deletion_vector");
+
+    const ROWS: u32 = 1 << 16;
+    let batch = (0..ROWS as u64).collect::<Vec<u64>>();
+
+    let mut group = Group::new(c, "deletion_vector_filter");
+    group.tags(&["readonly", "query"]);
+    group.throughput(Throughput::Elements(ROWS as u64));
+    for &deleted_pct in &[1_u32, 10, 50, 90] {
+        let mut rows = (0..ROWS).collect::<Vec<u32>>();
+        rows.shuffle(&mut rng);
+        let deleted = &rows[..(ROWS * deleted_pct / 100) as usize];
+        group.dataset(Fingerprint::of(deleted));
+
+        with_croaring! {
+            let dv = croaring::Bitmap::of(deleted);
+            group.bench_with_input(Id::new("croaring_contains", deleted_pct), &dv, |b, dv| {
+                b.iter(|| {
+                    let mut sum = 0;
+                    for row in 0..ROWS {
+                        if !dv.contains(row) {
+                            sum += batch[row as usize];
+                        }
+                    }
+                    black_box(sum)
+                });
+            });
+            group.bench_with_input(Id::new("croaring_complement", deleted_pct), &dv, |b, dv| {
+                b.iter(|| {
+                    let live = dv.flip(0..ROWS);
+                    black_box(live.iter().map(|row| batch[row as usize]).sum::<u64>())
+                });
+            });
+        }
+
+        with_roaring! {
+            let dv = deleted.iter().collect::<RoaringBitmap>();
+            group.bench_with_input(Id::new("roaring_contains", deleted_pct), &dv, |b, dv| {
+                b.iter(|| {
+                    let mut sum = 0;
+                    for row in 0..ROWS {
+                        if !dv.contains(row) {
+                            sum += batch[row as usize];
+                        }
+                    }
+                    black_box(sum)
+                });
+            });
+            group.bench_with_input(Id::new("roaring_complement", deleted_pct), &dv, |b, dv| {
+                b.iter(|| {
+                    let mut live = RoaringBitmap::new();
+                    live.insert_range(0..ROWS);
+                    live -= dv;
+                    black_box(live.iter().map(|row| batch[row as usize]).sum::<u64>())
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark using a bitmap as a selection mask to gather and sum the
+/// matching values of a column, as in predicate-pushdown execution.
+pub fn bench_gather(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_gather");
+
+    let mut group = Group::new(c, "gather_by_mask");
+    group.tags(&["readonly", "iter"]);
+    for &batch_size in &N {
+        let column = (0..batch_size).map(|v| v as f64).collect::<Vec<f64>>();
+
+        // Roughly one in four rows match the predicate.
+        let selected = (0..batch_size)
+            .filter(|_| rng.gen_ratio(1, 4))
+            .collect::<Vec<u32>>();
+        group.dataset(Fingerprint::of(&selected));
+
+        group.throughput(Throughput::Elements(batch_size as u64));
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring", batch_size),
+                &croaring::Bitmap::of(&selected),
+                |b, mask| {
+                    b.iter(|| black_box(mask.iter().map(|i| column[i as usize]).sum::<f64>()));
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new("roaring", batch_size),
+                &selected.iter().collect::<RoaringBitmap>(),
+                |b, mask| {
+                    b.iter(|| black_box(mask.iter().map(|i| column[i as usize]).sum::<f64>()));
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Count the values common to the sorted iterator `a` and sorted slice `b`
+/// with a two-pointer merge.
+fn merge_intersection_len(mut a: impl Iterator<Item = u32>, b: &[u32]) -> usize {
+    let mut count = 0;
+    let mut b = b.iter().peekable();
+    let mut next_a = a.next();
+    while let (Some(x), Some(&&y)) = (next_a, b.peek()) {
+        match x.cmp(&y) {
+            std::cmp::Ordering::Less => next_a = a.next(),
+            std::cmp::Ordering::Greater => {
+                b.next();
+            }
+            std::cmp::Ordering::Equal => {
+                count += 1;
+                next_a = a.next();
+                b.next();
+            }
+        }
+    }
+    count
+}
+
+/// Benchmark intersecting a bitmap with a sorted column of IDs, as when
+/// joining a bitmap index against a sorted column.
+pub fn bench_intersect_sorted_slice(c: &mut Harness) {
+    let mut group = Group::new(c, "intersect_sorted_slice");
+    group.tags(&["readonly", "setops"]);
+    for &batch_size in &N {
+        // The bitmap holds the even values, the slice the multiples of 3.
+        let evens = pool::get(Workload::Even, batch_size);
+        let column = (0..batch_size * 2)
+            .filter(|v| v % 3 == 0)
+            .collect::<Vec<u32>>();
+
+        group.throughput(Throughput::Elements(column.len() as u64));
+
+        with_croaring! {
+            let bm = evens.croaring();
+            group.bench_with_input(Id::new("croaring_contains", batch_size), bm, |b, bm| {
+                b.iter(|| black_box(column.iter().filter(|v| bm.contains(**v)).count()));
+            });
+            group.bench_with_input(Id::new("croaring_merge", batch_size), bm, |b, bm| {
+                b.iter(|| black_box(merge_intersection_len(bm.iter(), &column)));
+            });
+            group.bench_with_input(Id::new("croaring_temp_bitmap", batch_size), bm, |b, bm| {
+                b.iter(|| black_box(bm.and(&croaring::Bitmap::of(&column)).cardinality()));
+            });
+        }
+
+        with_roaring! {
+            let bm = evens.roaring();
+            group.bench_with_input(Id::new("roaring_contains", batch_size), bm, |b, bm| {
+                b.iter(|| black_box(column.iter().filter(|v| bm.contains(**v)).count()));
+            });
+            group.bench_with_input(Id::new("roaring_merge", batch_size), bm, |b, bm| {
+                b.iter(|| black_box(merge_intersection_len(bm.iter(), &column)));
+            });
+            group.bench_with_input(Id::new("roaring_temp_bitmap", batch_size), bm, |b, bm| {
+                b.iter(|| {
+                    let tmp = RoaringBitmap::from_sorted_iter(column.iter().copied()).unwrap();
+                    black_box((bm & &tmp).len())
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark exact-dedup counting over a duplicate-heavy stream, using the
+/// "was it new" result of insertion.
+pub fn bench_dedup_count(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_dedup_count");
+
+    let mut group = Group::new(c, "dedup_count");
+    group.tags(&["construct"]);
+    for &batch_size in &N {
+        // Every value is seen four times on average.
+        let stream = (0..batch_size)
+            .map(|_| rng.gen_range(0..(batch_size / 4).max(1)))
+            .collect::<Vec<u32>>();
+        group.dataset(Fingerprint::of(&stream));
+
+        group.throughput(Throughput::Elements(batch_size as u64));
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring_add_checked", batch_size),
+                &stream,
+                |b, stream| {
+                    b.iter(|| {
+                        let mut bm = croaring::Bitmap::create();
+                        black_box(stream.iter().filter(|v| bm.add_checked(**v)).count())
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("croaring_contains_add", batch_size),
+                &stream,
+                |b, stream| {
+                    b.iter(|| {
+                        let mut bm = croaring::Bitmap::create();
+                        let mut count = 0;
+                        for &v in stream {
+                            if !bm.contains(v) {
+                                bm.add(v);
+                                count += 1;
+                            }
+                        }
+                        black_box(count)
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("croaring_add_cardinality", batch_size),
+                &stream,
+                |b, stream| {
+                    b.iter(|| {
+                        let mut bm = croaring::Bitmap::create();
+                        for &v in stream {
+                            bm.add(v);
+                        }
+                        black_box(bm.cardinality())
+                    });
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new("roaring_insert", batch_size),
+                &stream,
+                |b, stream| {
+                    b.iter(|| {
+                        let mut bm = RoaringBitmap::new();
+                        black_box(stream.iter().filter(|v| bm.insert(**v)).count())
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("roaring_insert_len", batch_size),
+                &stream,
+                |b, stream| {
+                    b.iter(|| {
+                        let mut bm = RoaringBitmap::new();
+                        for &v in stream {
+                            bm.insert(v);
+                        }
+                        black_box(bm.len())
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark refilling a cleared bitmap against dropping it and filling a
+/// newly created one, to inform object pooling in hot loops.
+pub fn bench_clear_vs_new(c: &mut Harness) {
+    let mut group = Group::new(c, "clear_vs_new");
+    group.tags(&["construct"]);
+    for &batch_size in &N {
+        group.throughput(Throughput::Elements(batch_size as u64));
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring_clear", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut bm = croaring::Bitmap::create();
+                    b.iter(|| {
+                        bm.clear();
+                        for i in 0..batch_size {
+                            bm.add(i);
+                        }
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("croaring_new", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut bm = croaring::Bitmap::create();
+                    b.iter(|| {
+                        bm = croaring::Bitmap::create();
+                        for i in 0..batch_size {
+                            bm.add(i);
+                        }
+                    });
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new("roaring_clear", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut bm = RoaringBitmap::new();
+                    b.iter(|| {
+                        bm.clear();
+                        for i in 0..batch_size {
+                            bm.insert(i);
+                        }
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("roaring_new", batch_size),
+                &batch_size,
+                |b, &batch_size| {
+                    let mut bm = RoaringBitmap::new();
+                    b.iter(|| {
+                        bm = RoaringBitmap::new();
+                        for i in 0..batch_size {
+                            bm.insert(i);
+                        }
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// The number of lookups performed per iteration of the contains groups.
+const QUERIES: usize = 1_024;
+
+/// Benchmark point lookups of values that are present ("contains_hit"),
+/// absent ("contains_miss") and an even mix of both ("contains_mixed"),
+/// against a Bloom filter baseline, and their `_cold` variants (see
+/// [`roaring_bench::cache`]) querying the same values.
+pub fn bench_contains(c: &mut Harness) {
+    use rand::Rng;
+
+    for &cold in cache::variants() {
+        let mut rng = gen::rng("bench_contains");
+
+        // The offset from a present value of each query, or None for a random
+        // choice per query.
+        for (name, offset) in [
+            ("contains_hit", Some(0)),
+            ("contains_miss", Some(1)),
+            ("contains_mixed", None),
+        ] {
+            let mut group = Group::new(c, cache::group_name(name.to_string(), cold));
+            group.tags(&["readonly", "query"]);
+            group.throughput(Throughput::Elements(QUERIES as u64));
+            for &batch_size in &N {
+                // The sets hold the even values, so hits are even and misses odd.
+                let values = pool::get(Workload::Even, batch_size);
+                let queries = (0..QUERIES)
+                    .map(|_| {
+                        let offset = offset.unwrap_or_else(|| rng.gen_range(0..=1));
+                        rng.gen_range(0..batch_size) * 2 + offset
+                    })
+                    .collect::<Vec<u32>>();
+                group.dataset(Fingerprint::of(&queries));
+
+                for_each_impl!(contains_case(
+                    &mut group, batch_size, values, &queries, cold
+                ));
+
+                let bloom = values.values().iter().copied().collect::<BloomFilter>();
+                if offset == Some(1) && !cold && !harness::is_listing() {
+                    let fp = queries.iter().filter(|v| bloom.contains(**v)).count();
+                    println!(
+                        "bloom false positive rate at {batch_size}: {:.2}%",
+                        fp as f64 * 100.0 / QUERIES as f64
+                    );
+                }
+                group.bench_with_input(Id::new("bloom", batch_size), &bloom, |b, bloom| {
+                    cache::iter(b, cold, || {
+                        queries.iter().filter(|v| bloom.contains(**v)).count()
+                    });
+                });
+
+                // The optional participants are only measured hot.
+                if !cold {
+                    #[cfg(feature = "baselines")]
+                    baselines::contains(&mut group, batch_size, values.values(), &queries);
+                    #[cfg(feature = "dense-baselines")]
+                    dense::contains(&mut group, batch_size, values.values(), &queries);
+                    #[cfg(feature = "roaring-pinned")]
+                    pinned::contains(&mut group, batch_size, values.values(), &queries);
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// Look up each of `queries` in the pooled bitmap of `values`.
+fn contains_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    values: &Pooled,
+    queries: &[u32],
+    cold: bool,
+) {
+    group.bench_with_input(Id::new(B::NAME, batch_size), B::pooled(values), |b, bm| {
+        cache::iter(b, cold, || {
+            queries.iter().filter(|v| bm.contains(**v)).count()
+        });
+    });
+}
+
+/// The widths of the ranges queried by the range query groups.
+const RANGE_WIDTHS: [(&str, u32); 3] = [("narrow", 256), ("container", 65_536), ("wide", 1 << 24)];
+
+/// Counting the values within, and checking the presence of, [`QUERIES`]
+/// seeded random ranges `lo..hi` of each of [`RANGE_WIDTHS`].
+///
+/// The cardinality groups count within uniformly distributed values, and
+/// `roaring_rank` answers the same from the difference of two ranks. The
+/// `contains_range` groups query the contiguous range `0..size`, so only
+/// the ranges running past its end are absent.
+pub fn bench_range_queries(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_range_queries");
+
+    for (width_name, width) in RANGE_WIDTHS {
+        let mut group = Group::new(c, format!("range_cardinality_{width_name}"));
+        group.tags(&["readonly", "query"]);
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for &batch_size in &N {
+            let pooled = pool::get(generated(Distribution::UniformSparse, 0), batch_size);
+            let universe = gen::universe(batch_size, gen::DEFAULT_DENSITY);
+            let ranges = (0..QUERIES)
+                .map(|_| {
+                    let lo = rng.gen_range(0..universe);
+                    (lo, lo.saturating_add(width))
+                })
+                .collect::<Vec<_>>();
+            group.dataset(Fingerprint::of(
+                &ranges
+                    .iter()
+                    .flat_map(|&(lo, hi)| [lo, hi])
+                    .collect::<Vec<_>>(),
+            ));
+            with_croaring! {
+                group.bench_with_input(Id::new("croaring", batch_size), pooled.croaring(), |b, bm| {
+                    b.iter(|| {
+                        ranges
+                            .iter()
+                            .map(|&(lo, hi)| bm.range_cardinality(lo..hi))
+                            .sum::<u64>()
+                    });
+                });
+            }
+            with_roaring! {
+                let bm = pooled.roaring();
+                group.bench_with_input(Id::new("roaring", batch_size), bm, |b, bm| {
+                    b.iter(|| {
+                        ranges
+                            .iter()
+                            .map(|&(lo, hi)| bm.range_cardinality(lo..hi))
+                            .sum::<u64>()
+                    });
+                });
+                group.bench_with_input(Id::new("roaring_rank", batch_size), bm, |b, bm| {
+                    b.iter(|| {
+                        ranges
+                            .iter()
+                            .map(|&(lo, hi)| {
+                                let below = if lo == 0 { 0 } else { bm.rank(lo - 1) };
+                                bm.rank(hi - 1) - below
+                            })
+                            .sum::<u64>()
+                    });
+                });
+            }
+        }
+        group.finish();
+
+        let mut group = Group::new(c, format!("contains_range_{width_name}"));
+        group.tags(&["readonly", "query"]);
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for &batch_size in &N {
+            let pooled = pool::get(Workload::Sequential, batch_size);
+            let ranges = (0..QUERIES)
+                .map(|_| {
+                    let lo = rng.gen_range(0..batch_size);
+                    (lo, lo.saturating_add(width))
+                })
+                .collect::<Vec<_>>();
+            group.dataset(Fingerprint::of(
+                &ranges
+                    .iter()
+                    .flat_map(|&(lo, hi)| [lo, hi])
+                    .collect::<Vec<_>>(),
+            ));
+            with_croaring! {
+                group.bench_with_input(Id::new("croaring", batch_size), pooled.croaring(), |b, bm| {
+                    b.iter(|| ranges.iter().filter(|&&(lo, hi)| bm.contains_range(lo..hi)).count());
+                });
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring", batch_size), pooled.roaring(), |b, bm| {
+                    b.iter(|| ranges.iter().filter(|&&(lo, hi)| bm.contains_range(lo..hi)).count());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// A boolean query against an inverted index, combining the posting lists
+/// of the given terms.
+enum Query {
+    And(Vec<usize>),
+    Or(Vec<usize>),
+    AndNot(usize, usize),
+}
+
+/// Macro-benchmark of an inverted index: zipfian-sized posting lists for 50k
+/// terms, queried by a mixed AND/OR/NOT query log with zipfian term
+/// popularity. The reported throughput is queries per second.
+pub fn bench_inverted_index(c: &mut Harness) {
+    use rand::Rng;
+    use roaring_bench::zipf::Zipf;
+    let mut rng = gen::rng("bench_inverted_index");
+
+    const DOCS: u32 = 1_000_000;
+    const TERMS: usize = 50_000;
+    const QUERY_LOG: usize = 1_000;
+
+    // Posting list sizes follow Zipf's law: the k-th most common term appears
+    // in roughly DOCS / 2k documents.
+    let postings = (1..=TERMS)
+        .map(|k| {
+            let df = (DOCS as usize / (2 * k)).max(1);
+            let mut docs = (0..df)
+                .map(|_| rng.gen_range(0..DOCS))
+                .collect::<Vec<u32>>();
+            docs.sort_unstable();
+            docs.dedup();
+            docs
+        })
+        .collect::<Vec<_>>();
+
+    let popularity = Zipf::new(TERMS, 1.0);
+    let queries = (0..QUERY_LOG)
+        .map(|_| {
+            let kind = rng.gen_range(0..10);
+            let mut term = || popularity.sample(&mut rng);
+            match kind {
+                0..=4 => Query::And(vec![term(), term()]),
+                5..=7 => Query::Or(vec![term(), term(), term()]),
+                _ => Query::AndNot(term(), term()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "inverted_index");
+    group.tags(&["workload", "setops"]);
+    group.dataset(Fingerprint::of_all(&postings));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(QUERY_LOG as u64));
+
+    with_croaring! {
+        let index = postings
+            .iter()
+            .map(|docs| croaring::Bitmap::of(docs))
+            .collect::<Vec<_>>();
+        group.bench_function("croaring", |b| {
+            b.iter(|| {
+                let mut hits = 0;
+                for q in &queries {
+                    hits += match q {
+                        Query::And(terms) => terms[1..]
+                            .iter()
+                            .fold(index[terms[0]].clone(), |acc, &t| acc.and(&index[t]))
+                            .cardinality(),
+                        Query::Or(terms) => {
+                            let lists = terms.iter().map(|&t| &index[t]).collect::<Vec<_>>();
+                            croaring::Bitmap::fast_or(&lists).cardinality()
+                        }
+                        Query::AndNot(a, b) => index[*a].andnot_cardinality(&index[*b]),
+                    };
+                }
+                black_box(hits)
+            });
+        });
+        drop(index);
+    }
+
+    with_roaring! {
+        let index = postings
+            .iter()
+            .map(|docs| RoaringBitmap::from_sorted_iter(docs.iter().copied()).unwrap())
+            .collect::<Vec<_>>();
+        group.bench_function("roaring", |b| {
+            b.iter(|| {
+                let mut hits = 0;
+                for q in &queries {
+                    hits += match q {
+                        Query::And(terms) => terms[1..]
+                            .iter()
+                            .fold(index[terms[0]].clone(), |acc, &t| acc & &index[t])
+                            .len(),
+                        Query::Or(terms) => terms
+                            .iter()
+                            .fold(RoaringBitmap::new(), |acc, &t| acc | &index[t])
+                            .len(),
+                        Query::AndNot(a, b) => index[*a].difference_len(&index[*b]),
+                    };
+                }
+                black_box(hits)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// The number of shards of the sharded_index group.
+const SHARDS: u32 = 4;
+
+/// The number of documents indexed by each shard of the sharded_index
+/// group.
+const SHARD_DOCS: u32 = 1 << 19;
+
+/// The numbers of posting lists the sharded_index group is run with.
+const SHARD_LISTS: [usize; 2] = [1_000, 10_000];
+
+/// The number of documents returned by each query of the sharded_index
+/// group.
+const TOP_K: usize = 100;
+
+/// Macro-benchmark of a document-sharded inverted index: posting lists of
+/// log-uniform density between 0.001% and 1%, each split across [`SHARDS`]
+/// by document, queried by a log of intersections and unions of 2-5 random
+/// lists. A query visits the shards in document order, stopping once it has
+/// the first [`TOP_K`] matching documents. The reported throughput is
+/// queries per second.
+pub fn bench_sharded_index(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_sharded_index");
+
+    const QUERY_LOG: usize = 1_000;
+
+    // postings[list][shard] holds the shard-local ids of the documents of a
+    // list, cycling through the distributions.
+    let postings = (0..SHARD_LISTS[SHARD_LISTS.len() - 1])
+        .map(|i| {
+            let density = 10f64.powf(rng.gen_range(-5.0..-2.0));
+            let size = ((SHARD_DOCS as f64 * density) as u32).max(1);
+            let distribution = Distribution::ALL[i % Distribution::ALL.len()];
+            (0..SHARDS)
+                .map(|_| gen::generate(distribution, size, density, &mut rng))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    // The posting lists of each shard, index[shard][list].
+    #[cfg(feature = "croaring")]
+    let croaring_index = (0..SHARDS as usize)
+        .map(|s| {
+            postings
+                .iter()
+                .map(|p| croaring::Bitmap::of(&p[s]))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    #[cfg(feature = "roaring")]
+    let roaring_index = (0..SHARDS as usize)
+        .map(|s| {
+            postings
+                .iter()
+                .map(|p| RoaringBitmap::from_sorted_iter(p[s].iter().copied()).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "sharded_index");
+    group.tags(&["workload", "setops"]);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(QUERY_LOG as u64));
+    for lists in SHARD_LISTS {
+        group.dataset(Fingerprint::of_all(postings[..lists].iter().flatten()));
+
+        // Each query intersects (true) or unions (false) its lists.
+        let queries = (0..QUERY_LOG)
+            .map(|_| {
+                let terms = rng.gen_range(2..=5);
+                let terms = (0..terms)
+                    .map(|_| rng.gen_range(0..lists))
+                    .collect::<Vec<_>>();
+                (rng.gen_bool(0.5), terms)
+            })
+            .collect::<Vec<_>>();
+
+        with_croaring! {
+            group.bench_function(Id::new("croaring", lists), |b| {
+                let mut top = Vec::with_capacity(TOP_K);
+                b.iter(|| {
+                    let mut hits = 0;
+                    for (intersect, terms) in &queries {
+                        top.clear();
+                        for (shard, index) in croaring_index.iter().enumerate() {
+                            let matches = if *intersect {
+                                terms[1..].iter().fold(index[terms[0]].clone(), |mut acc, &t| {
+                                    acc.and_inplace(&index[t]);
+                                    acc
+                                })
+                            } else {
+                                let lists = terms.iter().map(|&t| &index[t]).collect::<Vec<_>>();
+                                croaring::Bitmap::fast_or(&lists)
+                            };
+                            let base = shard as u32 * SHARD_DOCS;
+                            top.extend(matches.iter().take(TOP_K - top.len()).map(|v| base + v));
+                            if top.len() == TOP_K {
+                                break;
+                            }
+                        }
+                        hits += top.len();
+                    }
+                    black_box(hits)
+                });
+            });
+        }
+
+        with_roaring! {
+            group.bench_function(Id::new("roaring", lists), |b| {
+                let mut top = Vec::with_capacity(TOP_K);
+                b.iter(|| {
+                    let mut hits = 0;
+                    for (intersect, terms) in &queries {
+                        top.clear();
+                        for (shard, index) in roaring_index.iter().enumerate() {
+                            let matches = if *intersect {
+                                terms[1..].iter().fold(index[terms[0]].clone(), |mut acc, &t| {
+                                    acc &= &index[t];
+                                    acc
+                                })
+                            } else {
+                                terms.iter().fold(RoaringBitmap::new(), |mut acc, &t| {
+                                    acc |= &index[t];
+                                    acc
+                                })
+                            };
+                            let base = shard as u32 * SHARD_DOCS;
+                            top.extend(matches.iter().take(TOP_K - top.len()).map(|v| base + v));
+                            if top.len() == TOP_K {
+                                break;
+                            }
+                        }
+                        hits += top.len();
+                    }
+                    black_box(hits)
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Macro-benchmark of a bitmap-indexed column scan: one bitmap per distinct
+/// value of three columns over 10M rows, evaluating a set of multi-predicate
+/// filters and counting the qualifying rows.
+pub fn bench_olap_filter(c: &mut Harness) {
+    use roaring_bench::zipf::Zipf;
+    let mut rng = gen::rng("bench_olap_filter");
+
+    const ROWS: u32 = 10_000_000;
+
+    // (name, distinct values, zipf exponent) - an exponent of 0 is uniform.
+    let columns = [
+        ("region", 8, 0.0),
+        ("status", 4, 1.0),
+        ("category", 100, 1.2),
+    ];
+
+    // The row IDs holding each value of each column, in row order.
+    let index = columns
+        .iter()
+        .map(|&(_, cardinality, s)| {
+            let dist = Zipf::new(cardinality, s);
+            let mut rows = vec![Vec::new(); cardinality];
+            for row in 0..ROWS {
+                rows[dist.sample(&mut rng)].push(row);
+            }
+            rows
+        })
+        .collect::<Vec<_>>();
+    let (region, status, category) = (0, 1, 2);
+
+    let mut group = Group::new(c, "olap_filter");
+    group.tags(&["workload", "setops"]);
+    group.dataset(Fingerprint::of_all(index.iter().flatten()));
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ROWS as u64 * 3));
+
+    with_croaring! {
+        let bitmaps = index
+            .iter()
+            .map(|col| col.iter().map(|rows| croaring::Bitmap::of(rows)).collect())
+            .collect::<Vec<Vec<_>>>();
+        group.bench_function("croaring", |b| {
+            let any = |col: usize, values: &[usize]| {
+                let lists = values.iter().map(|&v| &bitmaps[col][v]).collect::<Vec<_>>();
+                croaring::Bitmap::fast_or(&lists)
+            };
+            b.iter(|| {
+                // region IN (1, 3) AND status = 0
+                let a = any(region, &[1, 3]).and_cardinality(&bitmaps[status][0]);
+                // category IN (0..5) AND NOT region = 0
+                let b = any(category, &[0, 1, 2, 3, 4]).andnot_cardinality(&bitmaps[region][0]);
+                // status IN (1, 2) AND category = 7 AND region = 2
+                let c = any(status, &[1, 2])
+                    .and(&bitmaps[category][7])
+                    .and_cardinality(&bitmaps[region][2]);
+                black_box(a + b + c)
+            });
+        });
+        drop(bitmaps);
+    }
+
+    with_roaring! {
+        let bitmaps = index
+            .iter()
+            .map(|col| {
+                col.iter()
+                    .map(|rows| RoaringBitmap::from_sorted_iter(rows.iter().copied()).unwrap())
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        group.bench_function("roaring", |b| {
+            let any = |col: usize, values: &[usize]| {
+                values
+                    .iter()
+                    .fold(RoaringBitmap::new(), |acc, &v| acc | &bitmaps[col][v])
+            };
+            b.iter(|| {
+                // region IN (1, 3) AND status = 0
+                let a = any(region, &[1, 3]).intersection_len(&bitmaps[status][0]);
+                // category IN (0..5) AND NOT region = 0
+                let b = any(category, &[0, 1, 2, 3, 4]).difference_len(&bitmaps[region][0]);
+                // status IN (1, 2) AND category = 7 AND region = 2
+                let c = (any(status, &[1, 2]) & &bitmaps[category][7])
+                    .intersection_len(&bitmaps[region][2]);
+                black_box(a + b + c)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// An operation against per-principal authorization bitmaps.
+enum AuthzOp {
+    Check { principal: usize, resource: u32 },
+    Grant { principal: usize, first: u32 },
+    Revoke { principal: usize, first: u32 },
+}
+
+/// Benchmark a read-heavy authorization workload: a hot loop of "is resource
+/// R allowed for principal P" checks against per-principal bitmaps, with 1% of
+/// operations bulk granting or revoking a block of resources.
+pub fn bench_authz(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_authz");
+
+    const PRINCIPALS: usize = 500;
+    const RESOURCES: u32 = 1_000_000;
+    const GRANT_BLOCK: u32 = 64;
+    const OPS: usize = 10_000;
+
+    // Each principal starts with a few blocks of contiguous resources.
+    let initial = (0..PRINCIPALS)
+        .map(|_| {
+            (0..32)
+                .map(|_| rng.gen_range(0..RESOURCES - GRANT_BLOCK))
+                .collect::<Vec<u32>>()
+        })
+        .collect::<Vec<_>>();
+
+    let ops = (0..OPS)
+        .map(|_| {
+            let principal = rng.gen_range(0..PRINCIPALS);
+            let first = rng.gen_range(0..RESOURCES - GRANT_BLOCK);
+            match rng.gen_range(0..200) {
+                0 => AuthzOp::Grant { principal, first },
+                1 => AuthzOp::Revoke { principal, first },
+                _ => AuthzOp::Check {
+                    principal,
+                    resource: rng.gen_range(0..RESOURCES),
+                },
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "authz_mixed");
+    group.tags(&["workload", "query", "mutate"]);
+    group.dataset(Fingerprint::of_all(&initial));
+    group.throughput(Throughput::Elements(OPS as u64));
+
+    with_croaring! {
+        group.bench_function("croaring", |b| {
+            let mut acls = initial
+                .iter()
+                .map(|blocks| {
+                    let mut bm = croaring::Bitmap::create();
+                    for &first in blocks {
+                        bm.add_range(first..first + GRANT_BLOCK);
+                    }
+                    bm
+                })
+                .collect::<Vec<_>>();
+            b.iter(|| {
+                let mut allowed = 0;
+                for op in &ops {
+                    match *op {
+                        AuthzOp::Check {
+                            principal,
+                            resource,
+                        } => allowed += acls[principal].contains(resource) as usize,
+                        AuthzOp::Grant { principal, first } => {
+                            acls[principal].add_range(first..first + GRANT_BLOCK)
+                        }
+                        AuthzOp::Revoke { principal, first } => {
+                            acls[principal].remove_range(first..first + GRANT_BLOCK)
+                        }
+                    }
+                }
+                black_box(allowed)
+            });
+        });
+    }
+
+    with_roaring! {
+        group.bench_function("roaring", |b| {
+            let mut acls = initial
+                .iter()
+                .map(|blocks| {
+                    let mut bm = RoaringBitmap::new();
+                    for &first in blocks {
+                        bm.insert_range(first..first + GRANT_BLOCK);
+                    }
+                    bm
+                })
+                .collect::<Vec<_>>();
+            b.iter(|| {
+                let mut allowed = 0;
+                for op in &ops {
+                    match *op {
+                        AuthzOp::Check {
+                            principal,
+                            resource,
+                        } => allowed += acls[principal].contains(resource) as usize,
+                        AuthzOp::Grant { principal, first } => {
+                            acls[principal].insert_range(first..first + GRANT_BLOCK);
+                        }
+                        AuthzOp::Revoke { principal, first } => {
+                            acls[principal].remove_range(first..first + GRANT_BLOCK);
+                        }
+                    }
+                }
+                black_box(allowed)
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark an observability-pipeline dedup stage end to end: consume a
+/// stream of event IDs with 5-20% duplicates, counting the uniques and
+/// serializing the state every 10k events.
+pub fn bench_stream_dedup(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_stream_dedup");
+
+    const EVENTS: usize = 100_000;
+    const SNAPSHOT_EVERY: usize = 10_000;
+
+    let mut group = Group::new(c, "stream_dedup");
+    group.tags(&["workload", "serde"]);
+    group.throughput(Throughput::Elements(EVENTS as u64));
+    for &dup_pct in &[5_u32, 10, 20] {
+        // IDs are mostly increasing with small gaps, and duplicates are
+        // redeliveries of a recent event.
+        let mut next_id = 0_u32;
+        let stream = (0..EVENTS)
+            .map(|_| {
+                if next_id > 0 && rng.gen_ratio(dup_pct, 100) {
+                    next_id - rng.gen_range(1..=next_id.min(1_000))
+                } else {
+                    next_id += rng.gen_range(1..4);
+                    next_id
+                }
+            })
+            .collect::<Vec<u32>>();
+        group.dataset(Fingerprint::of(&stream));
+
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring", dup_pct), &stream, |b, stream| {
+                let mut buf = Vec::new();
+                b.iter(|| {
+                    let mut seen = croaring::Bitmap::create();
+                    let mut uniques = 0;
+                    for (i, &id) in stream.iter().enumerate() {
+                        uniques += seen.add_checked(id) as usize;
+                        if i % SNAPSHOT_EVERY == SNAPSHOT_EVERY - 1 {
+                            buf.clear();
+                            black_box(seen.serialize_into(&mut buf));
+                        }
+                    }
+                    black_box(uniques)
+                });
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring", dup_pct), &stream, |b, stream| {
+                let mut buf = Vec::new();
+                b.iter(|| {
+                    let mut seen = RoaringBitmap::new();
+                    let mut uniques = 0;
+                    for (i, &id) in stream.iter().enumerate() {
+                        uniques += seen.insert(id) as usize;
+                        if i % SNAPSHOT_EVERY == SNAPSHOT_EVERY - 1 {
+                            buf.clear();
+                            seen.serialize_into(&mut buf).unwrap();
+                            black_box(&buf);
+                        }
+                    }
+                    black_box(uniques)
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark graph analytics over adjacency sets stored as bitmaps: counting
+/// common neighbours of node pairs, and the neighbourhood union of a 2-hop
+/// expansion.
+pub fn bench_graph(c: &mut Harness) {
+    use rand::Rng;
+    use roaring_bench::zipf::Zipf;
+    let mut rng = gen::rng("bench_graph");
+
+    const NODES: usize = 100_000;
+    const PAIRS: usize = 1_000;
+    const EXPANSIONS: usize = 100;
+
+    // Neighbours are drawn with zipfian popularity, producing hub nodes with
+    // very large adjacency sets.
+    let popularity = Zipf::new(NODES, 0.8);
+    let adjacency = (0..NODES)
+        .map(|_| {
+            let degree = rng.gen_range(4..64);
+            let mut neighbours = (0..degree)
+                .map(|_| popularity.sample(&mut rng) as u32)
+                .collect::<Vec<u32>>();
+            neighbours.sort_unstable();
+            neighbours.dedup();
+            neighbours
+        })
+        .collect::<Vec<_>>();
+
+    // Pairs are biased towards hubs, as they are in real link-prediction
+    // queries.
+    let pairs = (0..PAIRS)
+        .map(|_| (popularity.sample(&mut rng), popularity.sample(&mut rng)))
+        .collect::<Vec<_>>();
+    let roots = (0..EXPANSIONS)
+        .map(|_| rng.gen_range(0..NODES))
+        .collect::<Vec<_>>();
+
+    #[cfg(feature = "croaring")]
+    let croaring_adj = adjacency
+        .iter()
+        .map(|n| croaring::Bitmap::of(n))
+        .collect::<Vec<_>>();
+    #[cfg(feature = "roaring")]
+    let roaring_adj = adjacency
+        .iter()
+        .map(|n| RoaringBitmap::from_sorted_iter(n.iter().copied()).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "graph_common_neighbours");
+    group.tags(&["workload", "setops"]);
+    group.dataset(Fingerprint::of_all(&adjacency));
+    group.throughput(Throughput::Elements(PAIRS as u64));
+    with_croaring! {
+        group.bench_function("croaring", |b| {
+            b.iter(|| {
+                pairs
+                    .iter()
+                    .map(|&(a, b)| croaring_adj[a].and_cardinality(&croaring_adj[b]))
+                    .sum::<u64>()
+            });
+        });
+    }
+    with_roaring! {
+        group.bench_function("roaring", |b| {
+            b.iter(|| {
+                pairs
+                    .iter()
+                    .map(|&(a, b)| roaring_adj[a].intersection_len(&roaring_adj[b]))
+                    .sum::<u64>()
+            });
+        });
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "graph_two_hop");
+    group.tags(&["workload", "setops"]);
+    group.dataset(Fingerprint::of_all(&adjacency));
+    group.throughput(Throughput::Elements(EXPANSIONS as u64));
+    with_croaring! {
+        group.bench_function("croaring", |b| {
+            b.iter(|| {
+                roots
+                    .iter()
+                    .map(|&root| {
+                        let hop = croaring_adj[root]
+                            .iter()
+                            .map(|n| &croaring_adj[n as usize])
+                            .collect::<Vec<_>>();
+                        croaring::Bitmap::fast_or(&hop).cardinality()
+                    })
+                    .sum::<u64>()
+            });
+        });
+    }
+    with_roaring! {
+        group.bench_function("roaring", |b| {
+            b.iter(|| {
+                roots
+                    .iter()
+                    .map(|&root| {
+                        let mut hop = RoaringBitmap::new();
+                        for n in &roaring_adj[root] {
+                            hop |= &roaring_adj[n as usize];
+                        }
+                        hop.len()
+                    })
+                    .sum::<u64>()
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Benchmark TTL-based retention in an index: IDs are allocated within
+/// time-bucketed ranges, and each step ingests a new bucket, expires the
+/// oldest live bucket with `remove_range` and runs a batch of queries.
+pub fn bench_retention(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_retention");
+
+    // Buckets are allocated round-robin from a ring, reusing the ID range of
+    // long-expired buckets so the state is stationary across iterations.
+    const RING: u32 = 64;
+    const LIVE: u32 = 16;
+    const BUCKET_WIDTH: u32 = 1 << 16;
+    const IDS_PER_BUCKET: usize = 6_000;
+    const QUERIES_PER_STEP: usize = 100;
+
+    let buckets = (0..RING)
+        .map(|b| {
+            let base = b * BUCKET_WIDTH;
+            let mut ids = (0..IDS_PER_BUCKET)
+                .map(|_| base + rng.gen_range(0..BUCKET_WIDTH))
+                .collect::<Vec<u32>>();
+            ids.sort_unstable();
+            ids
+        })
+        .collect::<Vec<_>>();
+    let queries = (0..QUERIES_PER_STEP * RING as usize)
+        .map(|_| rng.gen_range(0..RING * BUCKET_WIDTH))
+        .collect::<Vec<u32>>();
+    let bucket_range = |b: u32| b * BUCKET_WIDTH..(b + 1) * BUCKET_WIDTH;
+
+    let mut group = Group::new(c, "retention_expiry");
+    group.tags(&["workload", "mutate"]);
+    group.dataset(*Fingerprint::of_all(&buckets).update(&queries));
+
+    with_croaring! {
+        group.bench_function("croaring", |b| {
+            let mut index = croaring::Bitmap::create();
+            for ids in &buckets[..LIVE as usize] {
+                index.add_many(ids);
+            }
+            let mut step = LIVE;
+            b.iter(|| {
+                let next = step % RING;
+                index.remove_range(bucket_range((step - LIVE) % RING));
+                index.add_many(&buckets[next as usize]);
+
+                let q = &queries[next as usize * QUERIES_PER_STEP..][..QUERIES_PER_STEP];
+                step += 1;
+                q.iter().filter(|&&v| index.contains(v)).count()
+            });
+        });
+    }
+
+    with_roaring! {
+        group.bench_function("roaring", |b| {
+            let mut index = RoaringBitmap::new();
+            for ids in &buckets[..LIVE as usize] {
+                index.extend(ids.iter().copied());
+            }
+            let mut step = LIVE;
+            b.iter(|| {
+                let next = step % RING;
+                index.remove_range(bucket_range((step - LIVE) % RING));
+                index.extend(buckets[next as usize].iter().copied());
+
+                let q = &queries[next as usize * QUERIES_PER_STEP..][..QUERIES_PER_STEP];
+                step += 1;
+                q.iter().filter(|&&v| index.contains(v)).count()
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmark sliding-window distinct counting: the window holds one bitmap
+/// per bucket of the last W, and each step adds a new bucket, computes the
+/// union cardinality of the window and drops the oldest bucket.
+pub fn bench_sliding_window(c: &mut Harness) {
+    #[cfg(feature = "roaring")]
+    use roaring::MultiOps;
+    use roaring_bench::zipf::Zipf;
+    use std::collections::VecDeque;
+    let mut rng = gen::rng("bench_sliding_window");
+
+    const BUCKETS: usize = 128;
+    const EVENTS_PER_BUCKET: usize = 10_000;
+    const USERS: usize = 1_000_000;
+
+    // A zipfian user population, so consecutive buckets overlap heavily.
+    let users = Zipf::new(USERS, 0.9);
+    let buckets = (0..BUCKETS)
+        .map(|_| {
+            (0..EVENTS_PER_BUCKET)
+                .map(|_| users.sample(&mut rng) as u32)
+                .collect::<Vec<u32>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "sliding_window_distinct");
+    group.tags(&["workload", "setops"]);
+    group.dataset(Fingerprint::of_all(&buckets));
+    group.throughput(Throughput::Elements(EVENTS_PER_BUCKET as u64));
+    for &window in &[4_usize, 16, 64] {
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring", window), &window, |b, &window| {
+                let mut ring = buckets[..window]
+                    .iter()
+                    .map(|ids| croaring::Bitmap::of(ids))
+                    .collect::<VecDeque<_>>();
+                let mut next = window;
+                b.iter(|| {
+                    ring.push_back(croaring::Bitmap::of(&buckets[next % BUCKETS]));
+                    next += 1;
+                    let distinct =
+                        croaring::Bitmap::fast_or(&ring.iter().collect::<Vec<_>>()).cardinality();
+                    ring.pop_front();
+                    distinct
+                });
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring", window), &window, |b, &window| {
+                let mut ring = buckets[..window]
+                    .iter()
+                    .map(|ids| ids.iter().collect::<RoaringBitmap>())
+                    .collect::<VecDeque<_>>();
+                let mut next = window;
+                b.iter(|| {
+                    ring.push_back(buckets[next % BUCKETS].iter().collect());
+                    next += 1;
+                    let distinct = ring.iter().union().len();
+                    ring.pop_front();
+                    distinct
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Macro-benchmark of LSM/segment compaction: merge 256 overlapping
+/// segments of ~100k elements each into one bitmap, then run-optimise and
+/// serialize the result.
+pub fn bench_compaction(c: &mut Harness) {
+    use rand::Rng;
+    #[cfg(feature = "roaring")]
+    use roaring::MultiOps;
+    let mut rng = gen::rng("bench_compaction");
+
+    const SEGMENTS: u32 = 256;
+    const PER_SEGMENT: usize = 100_000;
+
+    // Segment i draws from [i * 50k, i * 50k + 400k), so each overlaps the
+    // segments written shortly before and after it.
+    #[cfg(feature = "croaring")]
+    let mut croaring_segments = Vec::new();
+    #[cfg(feature = "roaring")]
+    let mut roaring_segments = Vec::new();
+    let mut dataset = Fingerprint::default();
+    for i in 0..SEGMENTS {
+        let base = i * 50_000;
+        let ids = (0..PER_SEGMENT)
+            .map(|_| base + rng.gen_range(0..400_000))
+            .collect::<Vec<u32>>();
+        dataset.update(&ids);
+        #[cfg(feature = "croaring")]
+        croaring_segments.push(croaring::Bitmap::of(&ids));
+        #[cfg(feature = "roaring")]
+        roaring_segments.push(ids.into_iter().collect::<RoaringBitmap>());
+    }
+
+    let mut group = Group::new(c, "segment_compaction");
+    group.tags(&["workload", "setops", "serde", "rle"]);
+    group.dataset(dataset);
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(SEGMENTS as u64 * PER_SEGMENT as u64));
+
+    with_croaring! {
+        group.bench_function("croaring", |b| {
+            let segments = croaring_segments.iter().collect::<Vec<_>>();
+            b.iter(|| {
+                let mut merged = croaring::Bitmap::fast_or(&segments);
+                merged.run_optimize();
+                merged.serialize()
+            });
+        });
+    }
+    with_roaring! {
+        group.bench_function("roaring", |b| {
+            b.iter(|| {
+                // roaring does not support RLE
+                let merged = roaring_segments.iter().union();
+                let mut buf = Vec::with_capacity(merged.serialized_size());
+                merged.serialize_into(&mut buf).unwrap();
+                buf
+            });
+        });
+    }
+
+    group.finish();
+}
+
+/// A bitmap the snapshot benchmarks can update and query generically.
+trait SnapshotBitmap: Clone {
+    fn insert_batch(&mut self, ids: &[u32]);
+    fn count_hits(&self, queries: &[u32]) -> usize;
+}
+
+#[cfg(feature = "croaring")]
+impl SnapshotBitmap for croaring::Bitmap {
+    fn insert_batch(&mut self, ids: &[u32]) {
+        self.add_many(ids);
+    }
+
+    fn count_hits(&self, queries: &[u32]) -> usize {
+        queries.iter().filter(|&&q| self.contains(q)).count()
+    }
+}
+
+#[cfg(feature = "roaring")]
+impl SnapshotBitmap for RoaringBitmap {
+    fn insert_batch(&mut self, ids: &[u32]) {
+        self.extend(ids.iter().copied());
+    }
+
+    fn count_hits(&self, queries: &[u32]) -> usize {
+        queries.iter().filter(|&&q| self.contains(q)).count()
+    }
+}
+
+/// How readers of [`SnapshotState`] obtain a consistent view.
+#[derive(Debug, Clone, Copy)]
+enum SnapshotStrategy {
+    /// Each reader clones the writer's bitmap (which for a copy-on-write
+    /// croaring bitmap shares its containers rather than copying them).
+    Clone,
+
+    /// The writer publishes an immutable copy after every batch, and readers
+    /// take a reference to the latest one.
+    Publish,
+}
+
+/// A single writer and a fixed set of readers, each of which holds on to its
+/// snapshot until it next reads.
+struct SnapshotState<B> {
+    strategy: SnapshotStrategy,
+    master: B,
+    published: roaring_bench::snapshot::SnapshotCell<B>,
+    readers: Vec<std::sync::Arc<B>>,
+}
+
+impl<B: SnapshotBitmap> SnapshotState<B> {
+    fn new(strategy: SnapshotStrategy, master: B, readers: usize) -> Self {
+        let published = roaring_bench::snapshot::SnapshotCell::new(master.clone());
+        let first = published.load();
+        Self {
+            strategy,
+            master,
+            published,
+            readers: vec![first; readers],
+        }
+    }
+
+    fn write(&mut self, batch: &[u32]) {
+        self.master.insert_batch(batch);
+        if let SnapshotStrategy::Publish = self.strategy {
+            self.published
+                .store(std::sync::Arc::new(self.master.clone()));
+        }
+    }
+
+    fn read(&mut self, queries: &[u32]) -> usize {
+        let mut hits = 0;
+        for reader in &mut self.readers {
+            *reader = match self.strategy {
+                SnapshotStrategy::Clone => std::sync::Arc::new(self.master.clone()),
+                SnapshotStrategy::Publish => self.published.load(),
+            };
+            hits += reader.count_hits(queries);
+        }
+        hits
+    }
+}
+
+/// Consistent point-in-time reads of a bitmap under a single writer.
+///
+/// Each epoch the writer inserts a batch of IDs and every reader then takes a
+/// fresh snapshot and runs a batch of membership queries against it. Readers
+/// keep their snapshot alive until the next epoch, so a copy-on-write writer
+/// pays to unshare any container it touches. The writer and reader phases
+/// are timed separately, as `snapshot_writer` and `snapshot_reader`.
+///
+/// Publication uses a lock-protected `Arc` swap in place of `arc-swap`.
+/// Snapshots are exchanged on a single thread, as croaring's copy-on-write
+/// reference counts are not thread safe.
+pub fn bench_snapshot(c: &mut Harness) {
+    use criterion::measurement::Measurement;
+    use rand::Rng;
+    let mut rng = gen::rng("bench_snapshot");
+
+    const UNIVERSE: u32 = 10_000_000;
+    const INITIAL: usize = 1_000_000;
+    const BATCH: usize = 100;
+    const READERS: usize = 8;
+    const QUERIES: usize = 100;
+    const EPOCHS: usize = 64;
+
+    let initial = (0..INITIAL)
+        .map(|_| rng.gen_range(0..UNIVERSE))
+        .collect::<Vec<u32>>();
+    let batches = (0..EPOCHS)
+        .map(|_| {
+            (0..BATCH)
+                .map(|_| rng.gen_range(0..UNIVERSE))
+                .collect::<Vec<u32>>()
+        })
+        .collect::<Vec<_>>();
+    let queries = (0..QUERIES)
+        .map(|_| rng.gen_range(0..UNIVERSE))
+        .collect::<Vec<u32>>();
+
+    #[cfg(feature = "croaring")]
+    let croaring_master = croaring::Bitmap::of(&initial);
+    #[cfg(feature = "croaring")]
+    let mut croaring_cow = croaring_master.clone();
+    #[cfg(feature = "croaring")]
+    roaring_bench::cow::set_copy_on_write(&mut croaring_cow, true);
+    #[cfg(feature = "roaring")]
+    let roaring_master = initial.iter().collect::<RoaringBitmap>();
+
+    // Run `iters` epochs against a fresh state, returning the measured cost
+    // of the writer or reader phase only.
+    fn time_phase<B: SnapshotBitmap>(
+        meter: &Measure,
+        iters: u64,
+        mut state: SnapshotState<B>,
+        batches: &[Vec<u32>],
+        queries: &[u32],
+        time_writer: bool,
+    ) -> <Measure as Measurement>::Value {
+        let mut total = meter.zero();
+        for (_, batch) in (0..iters).zip(batches.iter().cycle()) {
+            let start = meter.start();
+            state.write(batch);
+            let write = meter.end(start);
+
+            let start = meter.start();
+            black_box(state.read(queries));
+            let read = meter.end(start);
+
+            total = meter.add(&total, if time_writer { &write } else { &read });
+        }
+        total
+    }
+    let meter = measurement();
+
+    for (group_name, time_writer) in [("snapshot_writer", true), ("snapshot_reader", false)] {
+        let mut group = Group::new(c, group_name);
+        group.tags(&["workload", "mutate"]);
+        group.dataset(*Fingerprint::of(&initial).update(&queries));
+        group.throughput(Throughput::Elements(if time_writer {
+            BATCH as u64
+        } else {
+            (READERS * QUERIES) as u64
+        }));
+
+        with_croaring! {
+            let cases = [
+                ("croaring_clone", &croaring_master, SnapshotStrategy::Clone),
+                ("croaring_cow", &croaring_cow, SnapshotStrategy::Clone),
+                ("croaring_arc", &croaring_master, SnapshotStrategy::Publish),
+            ];
+            for (name, master, strategy) in cases {
+                group.bench_function(name, |b| {
+                    b.iter_custom(|iters| {
+                        let state = SnapshotState::new(strategy, master.clone(), READERS);
+                        time_phase(&meter, iters, state, &batches, &queries, time_writer)
+                    });
+                });
+            }
+        }
+        with_roaring! {
+            for (name, strategy) in [
+                ("roaring_clone", SnapshotStrategy::Clone),
+                ("roaring_arc", SnapshotStrategy::Publish),
+            ] {
+                group.bench_function(name, |b| {
+                    b.iter_custom(|iters| {
+                        let state = SnapshotState::new(strategy, roaring_master.clone(), READERS);
+                        time_phase(&meter, iters, state, &batches, &queries, time_writer)
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Cloning a bitmap of each distribution, and the first write to a clone,
+/// for systems snapshotting bitmaps by copy.
+///
+/// `croaring_cow` clones a copy-on-write bitmap, which shares its
+/// containers with the clone, so the clone is cheap and the first write
+/// pays to unshare the container it touches. The first write removes the
+/// middle value of the set, so it always lands in an existing container.
+/// Each clone written to is made outside of the measurement.
+pub fn bench_clone(c: &mut Harness) {
+    use criterion::BatchSize;
+
+    for distribution in Distribution::ALL {
+        let mut clone = Group::new(c, format!("clone_{distribution}"));
+        clone.tags(&["construct"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            clone.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                let mut cow = pooled.croaring().clone();
+                roaring_bench::cow::set_copy_on_write(&mut cow, true);
+                let cases = [("croaring", pooled.croaring()), ("croaring_cow", &cow)];
+                for (name, bm) in cases {
+                    clone.bench_with_input(Id::new(name, batch_size), bm, |b, bm| {
+                        b.iter(|| bm.clone());
+                    });
+                }
+            }
+            with_roaring! {
+                clone.bench_with_input(Id::new("roaring", batch_size), pooled.roaring(), |b, bm| {
+                    b.iter(|| bm.clone());
+                });
+            }
+        }
+        clone.finish();
+
+        let mut write = Group::new(c, format!("clone_first_write_{distribution}"));
+        write.tags(&["mutate"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            let values = pooled.values();
+            let target = values[values.len() / 2];
+            with_croaring! {
+                let mut cow = pooled.croaring().clone();
+                roaring_bench::cow::set_copy_on_write(&mut cow, true);
+                let cases = [("croaring", pooled.croaring()), ("croaring_cow", &cow)];
+                for (name, master) in cases {
+                    write.bench_function(Id::new(name, batch_size), |b| {
+                        b.iter_batched_ref(
+                            || master.clone(),
+                            |bm| bm.remove(target),
+                            BatchSize::SmallInput,
+                        );
+                    });
+                }
+            }
+            with_roaring! {
+                write.bench_function(Id::new("roaring", batch_size), |b| {
+                    b.iter_batched_ref(
+                        || pooled.roaring().clone(),
+                        |bm| bm.remove(target),
+                        BatchSize::SmallInput,
+                    );
+                });
+            }
+        }
+        write.finish();
+    }
+}
+
+/// Serialization through a transport enforcing small frames, and
+/// deserialization from the resulting chunked stream.
+///
+/// croaring can only deserialize from a contiguous buffer, so its frames are
+/// first reassembled, while roaring reads directly from the frame stream.
+pub fn bench_framed_serialization(c: &mut Harness) {
+    use rand::Rng;
+    use roaring_bench::frame::{FrameReader, FrameWriter, DEFAULT_FRAME_SIZE};
+    use std::io::{Read, Write};
+    let mut rng = gen::rng("bench_framed_serialization");
+
+    let mut serialize = Group::new(c, "framed_serialize");
+    serialize.tags(&["serde"]);
+    let mut inputs = Vec::new();
+    for &size in &N {
+        let values = (0..size)
+            .map(|_| rng.gen_range(0..size.saturating_mul(10)))
+            .collect::<Vec<u32>>();
+        // Both backends produce the portable format, so share the input.
+        let bytes = portable_bytes(&values);
+
+        serialize.dataset(Fingerprint::of(&values));
+        serialize.throughput(Throughput::Bytes(bytes.len() as u64));
+        with_croaring! {
+            let croaring = croaring::Bitmap::of(&values);
+            serialize.bench_with_input(Id::new("croaring", size), &croaring, |b, bm| {
+                let mut buf = Vec::new();
+                b.iter(|| {
+                    buf.clear();
+                    let mut w = FrameWriter::new(Vec::new(), DEFAULT_FRAME_SIZE);
+                    w.write_all(bm.serialize_into(&mut buf)).unwrap();
+                    w.finish().unwrap()
+                });
+            });
+        }
+        with_roaring! {
+            let roaring = values.iter().collect::<RoaringBitmap>();
+            serialize.bench_with_input(Id::new("roaring", size), &roaring, |b, bm| {
+                b.iter(|| {
+                    let mut w = FrameWriter::new(Vec::new(), DEFAULT_FRAME_SIZE);
+                    bm.serialize_into(&mut w).unwrap();
+                    w.finish().unwrap()
+                });
+            });
+        }
+
+        let mut w = FrameWriter::new(Vec::new(), DEFAULT_FRAME_SIZE);
+        w.write_all(&bytes).unwrap();
+        inputs.push((
+            size,
+            Fingerprint::of(&values),
+            bytes.len(),
+            w.finish().unwrap(),
+        ));
+    }
+    serialize.finish();
+
+    let mut deserialize = Group::new(c, "framed_deserialize");
+    deserialize.tags(&["serde"]);
+    for (size, dataset, len, framed) in &inputs {
+        deserialize.dataset(*dataset);
+        deserialize.throughput(Throughput::Bytes(*len as u64));
+        with_croaring! {
+            deserialize.bench_with_input(Id::new("croaring", size), framed, |b, framed| {
+                let mut buf = Vec::new();
+                b.iter(|| {
+                    buf.clear();
+                    FrameReader::new(framed.as_slice())
+                        .read_to_end(&mut buf)
+                        .unwrap();
+                    croaring::Bitmap::try_deserialize(&buf).unwrap()
+                });
+            });
+        }
+        with_roaring! {
+            deserialize.bench_with_input(Id::new("roaring", size), framed, |b, framed| {
+                b.iter(|| {
+                    RoaringBitmap::deserialize_from(FrameReader::new(framed.as_slice())).unwrap()
+                });
+            });
+        }
+    }
+    deserialize.finish();
+}
+
+/// The reference groups used by [`roaring_bench::normalize`] to compare
+/// results across machines.
+pub fn bench_reference(c: &mut Harness) {
+    use roaring_bench::normalize::{calibration_workload, CALIBRATION_GROUP, NULL_BASELINE_GROUP};
+
+    Group::new(c, NULL_BASELINE_GROUP)
+        .tags(&["reference"])
+        .bench_function("noop", |b| b.iter(|| black_box(())));
+    Group::new(c, CALIBRATION_GROUP)
+        .tags(&["reference"])
+        .bench_function("mix_chain", |b| {
+            b.iter(|| calibration_workload(black_box(42)))
+        });
+}
+
+fn config() -> Harness {
+    // Bind and pin before criterion starts measuring, as the benchmarks (and
+    // their input generation) run on the calling thread.
+    let node = roaring_bench::numa::bind_from_env();
+    let core = roaring_bench::affinity::pin_from_env();
+    if !harness::is_listing() {
+        if let Some(node) = node {
+            println!("bound benchmark memory to NUMA node {node}");
+        }
+        if let Some(core) = core {
+            println!("pinned benchmark thread to core {core}");
+        }
+        println!("global allocator: {}", roaring_bench::allocator::NAME);
+        println!("input seed: {}", gen::seed());
+    }
+    if !harness::memory_budgets_from_env().is_empty() {
+        roaring_bench::allocator::enable_tracking();
+    }
+    if cache::cold_enabled() {
+        cache::prepare();
+        if !harness::is_listing() {
+            println!(
+                "evicting {} MiB between iterations of the _cold groups",
+                cache::EVICT_BYTES >> 20
+            );
+        }
+    }
+    let mut c = Criterion::default().with_measurement(measurement());
+    if scale::large_enabled() {
+        c = c.sample_size(scale::LARGE_SAMPLE_SIZE);
+    }
+    #[cfg(all(feature = "profiling", target_os = "linux"))]
+    let c = c.with_profiler(roaring_bench::profiler::PerfRecord::default());
+    c
+}
+
+/// Bitmaps covering the entire u32 range, and the same with a few holes
+/// punched in it: complement, membership and intersection with a sparse set.
+///
+/// croaring represents each full container as a single run, while roaring
+/// has no run containers and holds 512 MiB of bitsets.
+pub fn bench_full_universe(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_full_universe");
+
+    const HOLES: usize = 1_000;
+    const SPARSE: usize = 1_000;
+
+    let holes = (0..HOLES).map(|_| rng.gen()).collect::<Vec<u32>>();
+    let mut sparse = (0..SPARSE).map(|_| rng.gen()).collect::<Vec<u32>>();
+    sparse.sort_unstable();
+    sparse.dedup();
+    let queries = (0..QUERIES).map(|_| rng.gen()).collect::<Vec<u32>>();
+    let dataset = *Fingerprint::of(&holes).update(&sparse).update(&queries);
+
+    // The "full" and "near_full" bitmaps of each backend.
+    #[cfg(feature = "croaring")]
+    let croaring_cases = {
+        let full = croaring::Bitmap::from_range(..);
+        let mut near_full = full.clone();
+        for &hole in &holes {
+            near_full.remove(hole);
+        }
+        [("full", full), ("near_full", near_full)]
+    };
+    #[cfg(feature = "roaring")]
+    let roaring_cases = {
+        let full = RoaringBitmap::full();
+        let mut near_full = full.clone();
+        for &hole in &holes {
+            near_full.remove(hole);
+        }
+        [("full", full), ("near_full", near_full)]
+    };
+
+    let mut group = Group::new(c, "full_universe_complement");
+    group.tags(&["setops"]);
+    group.dataset(dataset);
+    group.sample_size(10);
+    with_croaring! {
+        for (name, croaring) in &croaring_cases {
+            group.bench_with_input(Id::new("croaring", name), croaring, |b, bm| {
+                b.iter(|| bm.flip(..));
+            });
+        }
+    }
+    with_roaring! {
+        // roaring has no flip: complement by subtracting from a full bitmap,
+        // built once so its 512 MiB is not part of the measurement.
+        let universe = RoaringBitmap::full();
+        for (name, roaring) in &roaring_cases {
+            group.bench_with_input(Id::new("roaring", name), roaring, |b, bm| {
+                b.iter(|| &universe - bm);
+            });
+        }
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "full_universe_contains");
+    group.tags(&["readonly", "query"]);
+    group.dataset(dataset);
+    group.throughput(Throughput::Elements(QUERIES as u64));
+    with_croaring! {
+        for (name, croaring) in &croaring_cases {
+            group.bench_with_input(Id::new("croaring", name), croaring, |b, bm| {
+                b.iter(|| queries.iter().filter(|v| bm.contains(**v)).count());
+            });
+        }
+    }
+    with_roaring! {
+        for (name, roaring) in &roaring_cases {
+            group.bench_with_input(Id::new("roaring", name), roaring, |b, bm| {
+                b.iter(|| queries.iter().filter(|v| bm.contains(**v)).count());
+            });
+        }
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "full_universe_intersect_sparse");
+    group.tags(&["readonly", "setops"]);
+    group.dataset(dataset);
+    with_croaring! {
+        let croaring_sparse = croaring::Bitmap::of(&sparse);
+        for (name, croaring) in &croaring_cases {
+            group.bench_with_input(Id::new("croaring", name), croaring, |b, bm| {
+                b.iter(|| bm.and(&croaring_sparse));
+            });
+        }
+    }
+    with_roaring! {
+        let roaring_sparse = RoaringBitmap::from_sorted_iter(sparse.iter().copied()).unwrap();
+        for (name, roaring) in &roaring_cases {
+            group.bench_with_input(Id::new("roaring", name), roaring, |b, bm| {
+                b.iter(|| bm & &roaring_sparse);
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Building a bitmap holding `0..size` in one step, against creating an
+/// empty bitmap and inserting the range into it.
+pub fn bench_range_construct(c: &mut Harness) {
+    let mut group = Group::new(c, "range_construct");
+    group.tags(&["construct"]);
+    for &size in &N {
+        group.throughput(Throughput::Elements(size as u64));
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring_from_range", size), &size, |b, &size| {
+                b.iter(|| croaring::Bitmap::from_range(0..size));
+            });
+            group.bench_with_input(Id::new("croaring_insert_range", size), &size, |b, &size| {
+                b.iter(|| {
+                    let mut bm = croaring::Bitmap::create();
+                    bm.add_range(0..size);
+                    bm
+                });
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring_from_iter", size), &size, |b, &size| {
+                b.iter(|| (0..size).collect::<RoaringBitmap>());
+            });
+            group.bench_with_input(Id::new("roaring_insert_range", size), &size, |b, &size| {
+                b.iter(|| {
+                    let mut bm = RoaringBitmap::new();
+                    bm.insert_range(0..size);
+                    bm
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The number of operations replayed per iteration of the mixed workload
+/// groups.
+const MIX_OPS: usize = 4_096;
+
+/// A seeded stream of inserts, removes, contains and rank queries replayed
+/// against a bitmap of uniformly distributed values, one group per
+/// [`mix::Mix`] (see [`mix::MIX_ENV`]).
+///
+/// Interleaving writes with reads exercises the container conversions that
+/// pure insert or query groups never trigger. Each iteration replays the
+/// stream against a fresh copy of the bitmap, made outside of the
+/// measurement, so the reported throughput is in operations per second.
+pub fn bench_mixed_workload(c: &mut Harness) {
+    use criterion::BatchSize;
+    use roaring_bench::mix::Op;
+
+    for m in mix::mixes_from_env() {
+        let mut rng = gen::rng(&format!("bench_mixed_workload/{m}"));
+        let mut group = Group::new(c, format!("mixed_workload_{}", m.name()));
+        group.tags(&["mutate", "query"]);
+        group.throughput(Throughput::Elements(MIX_OPS as u64));
+        for &batch_size in &N {
+            let pooled = pool::get(generated(Distribution::UniformSparse, 0), batch_size);
+            let universe = gen::universe(batch_size, gen::DEFAULT_DENSITY);
+            let ops = m.stream(MIX_OPS, universe, &mut rng);
+            let encoded = ops
+                .iter()
+                .flat_map(|op| match *op {
+                    Op::Insert(v) => [0, v],
+                    Op::Remove(v) => [1, v],
+                    Op::Contains(v) => [2, v],
+                    Op::Rank(v) => [3, v],
+                })
+                .collect::<Vec<_>>();
+            group.dataset(Fingerprint::of(&encoded));
+
+            with_croaring! {
+                group.bench_function(Id::new("croaring", batch_size), |b| {
+                    b.iter_batched_ref(
+                        || pooled.croaring().clone(),
+                        |bm| {
+                            let mut acc = 0;
+                            for op in &ops {
+                                match *op {
+                                    Op::Insert(v) => bm.add(v),
+                                    Op::Remove(v) => bm.remove(v),
+                                    Op::Contains(v) => acc += u64::from(bm.contains(v)),
+                                    Op::Rank(v) => acc += bm.rank(v),
+                                }
+                            }
+                            acc
+                        },
+                        BatchSize::SmallInput,
+                    );
+                });
+            }
+            with_roaring! {
+                group.bench_function(Id::new("roaring", batch_size), |b| {
+                    b.iter_batched_ref(
+                        || pooled.roaring().clone(),
+                        |bm| {
+                            let mut acc = 0;
+                            for op in &ops {
+                                match *op {
+                                    Op::Insert(v) => acc += u64::from(bm.insert(v)),
+                                    Op::Remove(v) => acc += u64::from(bm.remove(v)),
+                                    Op::Contains(v) => acc += u64::from(bm.contains(v)),
+                                    Op::Rank(v) => acc += bm.rank(v),
+                                }
+                            }
+                            acc
+                        },
+                        BatchSize::SmallInput,
+                    );
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Complementing a bitmap over a range, either its whole universe or the
+/// middle half of it, on sparse (uniform) and dense (even values) input.
+///
+/// roaring 0.10 has no flip, so its participants XOR with a bitmap of the
+/// range, built within the measurement as a flip of an arbitrary range
+/// would need. The in-place participants flip a fresh copy, made outside of
+/// the measurement.
+pub fn bench_flip(c: &mut Harness) {
+    use criterion::BatchSize;
+
+    let inputs = [
+        ("sparse", generated(Distribution::UniformSparse, 0)),
+        ("dense", Workload::Even),
+    ];
+    for (input, workload) in inputs {
+        for extent in ["full", "partial"] {
+            let mut group = Group::new(c, format!("flip_{input}_{extent}"));
+            group.tags(&["mutate", "setops"]);
+            for &batch_size in &N {
+                let pooled = pool::get(workload, batch_size);
+                let universe = pooled.values().last().map_or(0, |&v| v + 1);
+                let range = match extent {
+                    "full" => 0..universe,
+                    _ => universe / 4..universe / 4 * 3,
+                };
+                group.throughput(Throughput::Elements(u64::from(range.end - range.start)));
+
+                let range = &range;
+                with_croaring! {
+                    let bm = pooled.croaring();
+                    group.bench_function(Id::new("croaring_owned", batch_size), |b| {
+                        b.iter(|| bm.flip(range.clone()));
+                    });
+                    group.bench_function(Id::new("croaring_inplace", batch_size), |b| {
+                        b.iter_batched_ref(
+                            || bm.clone(),
+                            |bm| bm.flip_inplace(range.clone()),
+                            BatchSize::SmallInput,
+                        );
+                    });
+                }
+                with_roaring! {
+                    let bm = pooled.roaring();
+                    let mask = || {
+                        let mut mask = RoaringBitmap::new();
+                        mask.insert_range(range.clone());
+                        mask
+                    };
+                    group.bench_function(Id::new("roaring_owned", batch_size), |b| {
+                        b.iter(|| bm ^ mask());
+                    });
+                    group.bench_function(Id::new("roaring_inplace", batch_size), |b| {
+                        b.iter_batched_ref(
+                            || bm.clone(),
+                            |bm| *bm ^= mask(),
+                            BatchSize::SmallInput,
+                        );
+                    });
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// The number of distinct flip sets cycled through by the xor_toggle group.
+const FLIPS: usize = 64;
+
+/// Sustained toggling of a churn set by XOR with small "flip" sets, as when
+/// rolling feature flags out to (and back from) random subsets of users.
+///
+/// Each iteration toggles the next of `FLIPS` random sets, each 1% of the
+/// set size, into a single long-lived bitmap. The container layout it ends
+/// up with is printed once the case has run.
+pub fn bench_xor_toggle(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_xor_toggle");
+
+    let mut group = Group::new(c, "xor_toggle");
+    group.tags(&["mutate", "setops"]);
+    for &batch_size in &N {
+        let universe = batch_size * 2;
+        let flip_size = (batch_size as usize / 100).max(1);
+        let flips = (0..FLIPS)
+            .map(|_| {
+                let mut flip = (0..flip_size)
+                    .map(|_| rng.gen_range(0..universe))
+                    .collect::<Vec<u32>>();
+                flip.sort_unstable();
+                flip.dedup();
+                flip
+            })
+            .collect::<Vec<_>>();
+        group.dataset(Fingerprint::of_all(&flips));
+        group.throughput(Throughput::Elements(flip_size as u64));
+
+        let values = pool::get(Workload::Even, batch_size);
+
+        with_croaring! {
+            let croaring_flips = flips
+                .iter()
+                .map(|f| croaring::Bitmap::of(f))
+                .collect::<Vec<_>>();
+            let mut bm = values.croaring().clone();
+            let mut ran = false;
+            group.bench_function(Id::new("croaring", batch_size), |b| {
+                ran = true;
+                let mut i = 0;
+                b.iter(|| {
+                    bm.xor_inplace(&croaring_flips[i % FLIPS]);
+                    i += 1;
+                });
+            });
+            if ran && !harness::is_listing() {
+                let stats = bm.statistics();
+                println!(
+                    "xor_toggle croaring at {batch_size}: {} containers \
+                     ({} array, {} run, {} bitset), {} bytes serialized",
+                    stats.n_containers,
+                    stats.n_array_containers,
+                    stats.n_run_containers,
+                    stats.n_bitset_containers,
+                    bm.get_serialized_size_in_bytes(),
+                );
+            }
+        }
+
+        with_roaring! {
+            let roaring_flips = flips
+                .iter()
+                .map(|f| RoaringBitmap::from_sorted_iter(f.iter().copied()).unwrap())
+                .collect::<Vec<_>>();
+            let mut bm = values.roaring().clone();
+            let mut ran = false;
+            group.bench_function(Id::new("roaring", batch_size), |b| {
+                ran = true;
+                let mut i = 0;
+                b.iter(|| {
+                    bm ^= &roaring_flips[i % FLIPS];
+                    i += 1;
+                });
+            });
+            if ran && !harness::is_listing() {
+                println!(
+                    "xor_toggle roaring at {batch_size}: {} bytes serialized",
+                    bm.serialized_size(),
+                );
+            }
+        }
+    }
+    group.finish();
+}
+
+/// `is_empty` and cardinality queries on bitmaps that were filled and then
+/// emptied ("emptied") or 99% emptied ("mostly_emptied") by removing values
+/// in random order.
+///
+/// Removals can leave empty containers behind, or a cardinality that has
+/// to be recomputed rather than read from a maintained count.
+pub fn bench_churned_queries(c: &mut Harness) {
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("bench_churned_queries");
+
+    // The bitmaps of each backend after the removals of one case.
+    struct Churned {
+        name: &'static str,
+        batch_size: u32,
+        dataset: Fingerprint,
+        #[cfg(feature = "croaring")]
+        croaring: croaring::Bitmap,
+        #[cfg(feature = "roaring")]
+        roaring: RoaringBitmap,
+    }
+
+    let mut cases = Vec::new();
+    for &batch_size in &N {
+        let values = pool::get(Workload::Even, batch_size);
+        let mut removals = values.values().to_vec();
+        removals.shuffle(&mut rng);
+        for (name, keep) in [("emptied", 0), ("mostly_emptied", removals.len() / 100)] {
+            let removed = &removals[keep..];
+            cases.push(Churned {
+                name,
+                batch_size,
+                dataset: Fingerprint::of(&removals),
+                #[cfg(feature = "croaring")]
+                croaring: {
+                    let mut bm = values.croaring().clone();
+                    for &v in removed {
+                        bm.remove(v);
+                    }
+                    bm
+                },
+                #[cfg(feature = "roaring")]
+                roaring: {
+                    let mut bm = values.roaring().clone();
+                    for &v in removed {
+                        bm.remove(v);
+                    }
+                    bm
+                },
+            });
+        }
+    }
+
+    let mut group = Group::new(c, "churned_is_empty");
+    group.tags(&["readonly", "query"]);
+    for case in &cases {
+        group.dataset(case.dataset);
+        with_croaring! {
+            group.bench_with_input(
+                Id::new(format!("croaring_{}", case.name), case.batch_size),
+                &case.croaring,
+                |b, bm| {
+                    b.iter(|| bm.is_empty());
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new(format!("roaring_{}", case.name), case.batch_size),
+                &case.roaring,
+                |b, bm| {
+                    b.iter(|| bm.is_empty());
+                },
+            );
+        }
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "churned_len");
+    group.tags(&["readonly", "query"]);
+    for case in &cases {
+        group.dataset(case.dataset);
+        with_croaring! {
+            group.bench_with_input(
+                Id::new(format!("croaring_{}", case.name), case.batch_size),
+                &case.croaring,
+                |b, bm| {
+                    b.iter(|| bm.cardinality());
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new(format!("roaring_{}", case.name), case.batch_size),
+                &case.roaring,
+                |b, bm| {
+                    b.iter(|| bm.len());
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Intersect two ascending iterators by merging them, the way any sorted
+/// set could be intersected without knowledge of its representation.
+fn merge_intersect(a: impl Iterator<Item = u32>, b: impl Iterator<Item = u32>) -> Vec<u32> {
+    let mut out = Vec::new();
+    let (mut a, mut b) = (a.peekable(), b.peekable());
+    while let (Some(&x), Some(&y)) = (a.peek(), b.peek()) {
+        match x.cmp(&y) {
+            std::cmp::Ordering::Less => {
+                a.next();
+            }
+            std::cmp::Ordering::Greater => {
+                b.next();
+            }
+            std::cmp::Ordering::Equal => {
+                out.push(x);
+                a.next();
+                b.next();
+            }
+        }
+    }
+    out
+}
+
+/// Intersection of a contiguous set with the even values of the same size
+/// by the native AND, against a generic merge of the two bitmaps' sorted
+/// iterators into a `Vec`.
+pub fn bench_intersect_merge(c: &mut Harness) {
+    let mut group = Group::new(c, "intersect_merge");
+    group.tags(&["readonly", "setops", "iter"]);
+    for &batch_size in &N {
+        let a = pool::get(Workload::Sequential, batch_size);
+        let b = pool::get(Workload::Even, batch_size);
+        group.throughput(Throughput::Elements(batch_size as u64 * 2));
+
+        with_croaring! {
+            group.bench_function(Id::new("croaring_and", batch_size), |bench| {
+                bench.iter(|| a.croaring().and(b.croaring()));
+            });
+            group.bench_function(Id::new("croaring_merge", batch_size), |bench| {
+                bench.iter(|| merge_intersect(a.croaring().iter(), b.croaring().iter()));
+            });
+        }
+        with_roaring! {
+            group.bench_function(Id::new("roaring_and", batch_size), |bench| {
+                bench.iter(|| a.roaring() & b.roaring());
+            });
+            group.bench_function(Id::new("roaring_merge", batch_size), |bench| {
+                bench.iter(|| merge_intersect(a.roaring().iter(), b.roaring().iter()));
+            });
+        }
+    }
+    group.finish();
+}
+
+/// In-place union of a set 1% the size of a larger one, folding the small
+/// operand into the large accumulator and vice versa. Copying the
+/// accumulator is excluded from the measurement.
+pub fn bench_union_operand_order(c: &mut Harness) {
+    use criterion::BatchSize;
+    use rand::Rng;
+    let mut rng = gen::rng("bench_union_operand_order");
+
+    let mut group = Group::new(c, "union_operand_order");
+    group.tags(&["mutate", "setops"]);
+    for &batch_size in &N {
+        let large = pool::get(Workload::Even, batch_size);
+        let mut small = (0..(batch_size / 100).max(1))
+            .map(|_| rng.gen_range(0..batch_size * 2))
+            .collect::<Vec<u32>>();
+        small.sort_unstable();
+        small.dedup();
+        group.dataset(Fingerprint::of(&small));
+        group.throughput(Throughput::Elements(batch_size as u64));
+
+        with_croaring! {
+            let croaring_small = croaring::Bitmap::of(&small);
+            let croaring_large = large.croaring();
+            group.bench_function(Id::new("croaring_small_into_large", batch_size), |b| {
+                b.iter_batched_ref(
+                    || croaring_large.clone(),
+                    |acc| acc.or_inplace(&croaring_small),
+                    BatchSize::SmallInput,
+                );
+            });
+            group.bench_function(Id::new("croaring_large_into_small", batch_size), |b| {
+                b.iter_batched_ref(
+                    || croaring_small.clone(),
+                    |acc| acc.or_inplace(croaring_large),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+
+        with_roaring! {
+            let roaring_small = RoaringBitmap::from_sorted_iter(small.iter().copied()).unwrap();
+            let roaring_large = large.roaring();
+            group.bench_function(Id::new("roaring_small_into_large", batch_size), |b| {
+                b.iter_batched_ref(
+                    || roaring_large.clone(),
+                    |acc| *acc |= &roaring_small,
+                    BatchSize::SmallInput,
+                );
+            });
+            group.bench_function(Id::new("roaring_large_into_small", batch_size), |b| {
+                b.iter_batched_ref(
+                    || roaring_small.clone(),
+                    |acc| *acc |= roaring_large,
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The number of values held by the bitmap batch_removal removes from.
+const REMOVAL_BASE: u32 = 1_000_000;
+
+/// Removing batches of up to 10k scattered values from a large bitmap, by
+/// calling `remove` per value or by building a bitmap of the batch and
+/// applying an in-place difference. Building the batch bitmap is measured,
+/// copying the base bitmap is not.
+pub fn bench_batch_removal(c: &mut Harness) {
+    use criterion::BatchSize;
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("bench_batch_removal");
+
+    let base = pool::get(Workload::Even, REMOVAL_BASE);
+
+    let mut group = Group::new(c, "batch_removal");
+    group.tags(&["mutate", "setops"]);
+    for batch_size in [10, 100, 1_000, 10_000] {
+        let batch = base
+            .values()
+            .choose_multiple(&mut rng, batch_size)
+            .copied()
+            .collect::<Vec<u32>>();
+        group.dataset(Fingerprint::of(&batch));
+        group.throughput(Throughput::Elements(batch_size as u64));
+
+        with_croaring! {
+            group.bench_function(Id::new("croaring_remove", batch_size), |b| {
+                b.iter_batched_ref(
+                    || base.croaring().clone(),
+                    |bm| {
+                        for &v in &batch {
+                            bm.remove(v);
+                        }
+                    },
+                    BatchSize::SmallInput,
+                );
+            });
+            group.bench_function(Id::new("croaring_andnot", batch_size), |b| {
+                b.iter_batched_ref(
+                    || base.croaring().clone(),
+                    |bm| bm.andnot_inplace(&croaring::Bitmap::of(&batch)),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+        with_roaring! {
+            group.bench_function(Id::new("roaring_remove", batch_size), |b| {
+                b.iter_batched_ref(
+                    || base.roaring().clone(),
+                    |bm| {
+                        for &v in &batch {
+                            bm.remove(v);
+                        }
+                    },
+                    BatchSize::SmallInput,
+                );
+            });
+            group.bench_function(Id::new("roaring_andnot", batch_size), |b| {
+                b.iter_batched_ref(
+                    || base.roaring().clone(),
+                    |bm| *bm -= batch.iter().collect::<RoaringBitmap>(),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The number of values inserted by each iteration of the ingest_optimize
+/// group.
+const INGEST: usize = 1_000_000;
+
+/// Continuous ingest of clustered values with `run_optimize` called every
+/// K inserts, or never. The serialized size of the result is printed once
+/// each case has run, from one more ingest outside the measurement.
+///
+/// roaring has no run containers and so no `run_optimize`; it is measured
+/// only as "never" for reference.
+pub fn bench_ingest_optimize(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_ingest_optimize");
+
+    // Runs of 1-64 consecutive values separated by gaps of up to 64.
+    let mut ingest = Vec::with_capacity(INGEST);
+    let mut next = 0u32;
+    while ingest.len() < INGEST {
+        let run = rng.gen_range(1..=64).min(INGEST - ingest.len()) as u32;
+        ingest.extend(next..next + run);
+        next += run + rng.gen_range(1..=64);
+    }
+
+    let mut group = Group::new(c, "ingest_optimize");
+    group.tags(&["construct", "rle"]);
+    group.dataset(Fingerprint::of(&ingest));
+    group.throughput(Throughput::Elements(INGEST as u64));
+    group.sample_size(10);
+    for (name, every) in [
+        ("10000", Some(10_000)),
+        ("100000", Some(100_000)),
+        ("never", None),
+    ] {
+        with_croaring! {
+            let build = || {
+                let mut bm = croaring::Bitmap::create();
+                for (i, &v) in ingest.iter().enumerate() {
+                    bm.add(v);
+                    if every.is_some_and(|k| (i + 1) % k == 0) {
+                        bm.run_optimize();
+                    }
+                }
+                bm
+            };
+            let mut ran = false;
+            group.bench_function(Id::new("croaring", name), |b| {
+                ran = true;
+                b.iter(build);
+            });
+            if ran && !harness::is_listing() {
+                let size = build().get_serialized_size_in_bytes();
+                println!("ingest_optimize croaring/{name}: {size} bytes serialized");
+            }
+        }
+    }
+
+    with_roaring! {
+        let build = || {
+            let mut bm = RoaringBitmap::new();
+            for &v in &ingest {
+                bm.insert(v);
+            }
+            bm
+        };
+        let mut ran = false;
+        group.bench_function(Id::new("roaring", "never"), |b| {
+            ran = true;
+            b.iter(build);
+        });
+        if ran && !harness::is_listing() {
+            let size = build().serialized_size();
+            println!("ingest_optimize roaring/never: {size} bytes serialized");
+        }
+    }
+    group.finish();
+}
+
+/// Copying a populated 32-bit bitmap into a 64-bit treemap, value by value
+/// and by moving a copy of the whole bitmap in as the treemap's lowest
+/// 2^32 partition.
+///
+/// croaring 0.8 has no `Bitmap64`, so its `Treemap` (a `BTreeMap` of
+/// `Bitmap`) is the 64-bit target.
+pub fn bench_treemap_migration(c: &mut Harness) {
+    #[cfg(feature = "croaring")]
+    use croaring::Treemap;
+    #[cfg(feature = "roaring")]
+    use roaring::RoaringTreemap;
+
+    let mut group = Group::new(c, "treemap_migration");
+    group.tags(&["construct", "interop"]);
+    for &batch_size in &N {
+        let values = pool::get(Workload::Even, batch_size);
+        group.throughput(Throughput::Elements(batch_size as u64));
+
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring_per_value", batch_size),
+                values.croaring(),
+                |b, bm| {
+                    b.iter(|| {
+                        let mut tm = Treemap::create();
+                        for v in bm.iter() {
+                            tm.add(u64::from(v));
+                        }
+                        tm
+                    });
+                },
+            );
+            group.bench_with_input(
+                Id::new("croaring_whole", batch_size),
+                values.croaring(),
+                |b, bm| {
+                    b.iter(|| Treemap {
+                        map: std::iter::once((0, bm.clone())).collect(),
+                    });
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(
+                Id::new("roaring_per_value", batch_size),
+                values.roaring(),
+                |b, bm| {
+                    b.iter(|| bm.iter().map(u64::from).collect::<RoaringTreemap>());
+                },
+            );
+            group.bench_with_input(
+                Id::new("roaring_whole", batch_size),
+                values.roaring(),
+                |b, bm| {
+                    b.iter(|| RoaringTreemap::from_bitmaps(std::iter::once((0, bm.clone()))));
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Deserialization through the validating paths and the fast trusting ones,
+/// of well-formed input and of input with a corrupt header (see
+/// [`corrupt_header`]), which both libraries reject.
+///
+/// croaring exposes no trusting path, so it is called through
+/// `croaring-sys`. It is undefined behaviour on corrupt input, so is only
+/// measured against well-formed bytes.
+pub fn bench_untrusted_deserialize(c: &mut Harness) {
+    use rand::Rng;
+    use std::os::raw::c_char;
+    let mut rng = gen::rng("bench_untrusted_deserialize");
+
+    let mut inputs = Vec::new();
+    for &size in &N {
+        let values = (0..size)
+            .map(|_| rng.gen_range(0..size.saturating_mul(10)))
+            .collect::<Vec<u32>>();
+        let bytes = portable_bytes(&values);
+        let corrupted = corrupt_header(&bytes);
+        with_croaring! {
+            assert!(
+                croaring::Bitmap::try_deserialize(&corrupted).is_none(),
+                "croaring accepted the corrupted input of size {}",
+                size
+            );
+        }
+        with_roaring! {
+            for result in [
+                RoaringBitmap::deserialize_from(corrupted.as_slice()),
+                RoaringBitmap::deserialize_unchecked_from(corrupted.as_slice()),
+            ] {
+                assert!(result.is_err(), "roaring accepted the corrupted input of size {}", size);
+            }
+        }
+        inputs.push((size, Fingerprint::of(&values), bytes, corrupted));
+    }
+
+    let mut group = Group::new(c, "deserialize_well_formed");
+    group.tags(&["serde"]);
+    for (size, dataset, bytes, _) in &inputs {
+        group.dataset(*dataset);
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        with_croaring! {
+            group.bench_with_input(Id::new("croaring_validated", size), bytes, |b, bytes| {
+                b.iter(|| croaring::Bitmap::try_deserialize(bytes).unwrap());
+            });
+            group.bench_with_input(Id::new("croaring_trusting", size), bytes, |b, bytes| {
+                b.iter(|| unsafe {
+                    let bm = croaring_sys::roaring_bitmap_portable_deserialize(
+                        bytes.as_ptr() as *const c_char
+                    );
+                    croaring_sys::roaring_bitmap_free(bm);
+                });
+            });
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring_validated", size), bytes, |b, bytes| {
+                b.iter(|| RoaringBitmap::deserialize_from(bytes.as_slice()).unwrap());
+            });
+            group.bench_with_input(Id::new("roaring_trusting", size), bytes, |b, bytes| {
+                b.iter(|| RoaringBitmap::deserialize_unchecked_from(bytes.as_slice()).unwrap());
+            });
+        }
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "deserialize_corrupted");
+    group.tags(&["serde"]);
+    for (size, dataset, _, corrupted) in &inputs {
+        group.dataset(*dataset);
+        group.throughput(Throughput::Bytes(corrupted.len() as u64));
+        with_croaring! {
+            group.bench_with_input(
+                Id::new("croaring_validated", size),
+                corrupted,
+                |b, bytes| {
+                    b.iter(|| croaring::Bitmap::try_deserialize(bytes));
+                },
+            );
+        }
+        with_roaring! {
+            group.bench_with_input(Id::new("roaring_validated", size), corrupted, |b, bytes| {
+                b.iter(|| RoaringBitmap::deserialize_from(bytes.as_slice()).is_err());
+            });
+            group.bench_with_input(Id::new("roaring_trusting", size), corrupted, |b, bytes| {
+                b.iter(|| RoaringBitmap::deserialize_unchecked_from(bytes.as_slice()).is_err());
+            });
+        }
+    }
+    group.finish();
+}
+
+/// A binary set operation benchmarked by [`bench_set_op`].
+#[derive(Debug, Clone, Copy)]
+enum SetOp {
+    Intersection,
+    Difference,
+    SymmetricDifference,
+}
+
+impl SetOp {
+    fn name(&self) -> &'static str {
+        match self {
+            SetOp::Intersection => "intersection",
+            SetOp::Difference => "difference",
+            SetOp::SymmetricDifference => "symmetric_difference",
+        }
+    }
+
+    fn apply<B: BitmapImpl>(&self, a: &B, b: &B) -> B {
+        match self {
+            SetOp::Intersection => a.intersect(b),
+            SetOp::Difference => a.difference(b),
+            SetOp::SymmetricDifference => a.symmetric_difference(b),
+        }
+    }
+
+    fn apply_owned<B: BitmapImpl>(&self, a: B, b: B) -> B {
+        match self {
+            SetOp::Intersection => a.intersect_owned(b),
+            SetOp::Difference => a.difference_owned(b),
+            SetOp::SymmetricDifference => a.symmetric_difference_owned(b),
+        }
+    }
+}
+
+/// The groups a set operation called `name` is benchmarked as: the group
+/// itself over fixed operands, then one `{name}_{distribution}` group over
+/// two independently generated operands per [`Distribution`].
+fn set_op_variants(name: &str) -> Vec<(String, Option<Distribution>, Workload, Workload)> {
+    let mut variants = vec![(name.to_string(), None, Workload::Sequential, Workload::Even)];
+    for distribution in Distribution::ALL {
+        variants.push((
+            format!("{name}_{distribution}"),
+            Some(distribution),
+            generated(distribution, 0),
+            generated(distribution, 1),
+        ));
+    }
+    variants
+}
+
+/// A binary set operation borrowing its operands.
+type RefOp<B> = fn(&B, &B) -> B;
+
+/// Benchmark a binary set operation between the contiguous range `0..n`
+/// and the `n` even values, which overlap in half of the range, and then
+/// between two independent sets of each [`Distribution`].
+///
+/// The "ref" variants borrow both operands, while the "owned" variants
+/// consume copies of them, letting the implementation reuse an operand's
+/// allocation. Copying the operands is excluded from the measurement.
+fn bench_set_op(c: &mut Harness, op: SetOp) {
+    for (name, distribution, workload_a, workload_b) in set_op_variants(op.name()) {
+        let mut group = Group::new(c, name);
+        group.tags(&["readonly", "setops"]);
+        if distribution.is_some() {
+            group.tags(&["distribution"]);
+        }
+        for &batch_size in &N {
+            let set_a = pool::get(workload_a, batch_size);
+            let set_b = pool::get(workload_b, batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64 * 2));
+            for_each_impl!(set_op_case(&mut group, batch_size, op, set_a, set_b));
+
+            #[cfg(feature = "baselines")]
+            baselines::set_op(&mut group, batch_size, op, set_a.values(), set_b.values());
+            #[cfg(feature = "roaring-pinned")]
+            pinned::set_op(&mut group, batch_size, op, set_a.values(), set_b.values());
+        }
+        group.finish();
+    }
+}
+
+/// `op` on the pooled bitmaps `a` and `b`, borrowing and then consuming
+/// them.
+fn set_op_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    op: SetOp,
+    a: &Pooled,
+    b: &Pooled,
+) {
+    use criterion::BatchSize;
+
+    let (a, b) = (B::pooled(a), B::pooled(b));
+    group.bench_function(Id::new(format!("{}_ref", B::NAME), batch_size), |bench| {
+        bench.iter(|| op.apply(a, b));
+    });
+    group.bench_function(Id::new(format!("{}_owned", B::NAME), batch_size), |bench| {
+        bench.iter_batched(
+            || (a.clone(), b.clone()),
+            |(a, b)| op.apply_owned(a, b),
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+/// A cardinality-only operation on two bitmaps, widened to `f64` so that
+/// counts and ratios share a signature.
+type CardinalityOp<B> = fn(&B, &B) -> f64;
+
+/// The size of a pairwise result measured by [`bench_cardinality_op`].
+#[derive(Debug, Clone, Copy)]
+enum PairwiseSize {
+    IntersectionLen,
+    UnionLen,
+    JaccardIndex,
+}
+
+impl PairwiseSize {
+    fn name(&self) -> &'static str {
+        match self {
+            PairwiseSize::IntersectionLen => "intersection_len",
+            PairwiseSize::UnionLen => "union_len",
+            PairwiseSize::JaccardIndex => "jaccard_index",
+        }
+    }
+
+    /// The size, from the cardinality of the built result.
+    fn materialize<B: BitmapImpl>(&self, a: &B, b: &B) -> f64 {
+        match self {
+            PairwiseSize::IntersectionLen => a.intersect(b).cardinality() as f64,
+            PairwiseSize::UnionLen => a.union(b).cardinality() as f64,
+            PairwiseSize::JaccardIndex => {
+                a.intersect(b).cardinality() as f64 / a.union(b).cardinality() as f64
+            }
+        }
+    }
+}
+
+/// Like [`bench_set_op`] for operations computing only the size of a pairwise
+/// result: each library's own ways of computing it without building the
+/// result, named by their function, then a `{library}_materialize`
+/// participant building it.
+fn bench_cardinality_op(
+    c: &mut Harness,
+    size: PairwiseSize,
+    #[cfg(feature = "croaring")] croaring_ops: &[(&str, CardinalityOp<croaring::Bitmap>)],
+    #[cfg(feature = "roaring")] roaring_ops: &[(&str, CardinalityOp<RoaringBitmap>)],
+) {
+    for (name, distribution, workload_a, workload_b) in set_op_variants(size.name()) {
+        let mut group = Group::new(c, name);
+        group.tags(&["readonly", "setops"]);
+        if distribution.is_some() {
+            group.tags(&["distribution"]);
+        }
+        for &batch_size in &N {
+            let set_a = pool::get(workload_a, batch_size);
+            let set_b = pool::get(workload_b, batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64 * 2));
+
+            with_croaring! {
+                let (a, b) = (set_a.croaring(), set_b.croaring());
+                for (function, op) in croaring_ops {
+                    group.bench_function(Id::new(*function, batch_size), |bench| {
+                        bench.iter(|| op(a, b));
+                    });
+                }
+            }
+            with_roaring! {
+                let (a, b) = (set_a.roaring(), set_b.roaring());
+                for (function, op) in roaring_ops {
+                    group.bench_function(Id::new(*function, batch_size), |bench| {
+                        bench.iter(|| op(a, b));
+                    });
+                }
+            }
+            for_each_impl!(materialize_case(&mut group, batch_size, size, set_a, set_b));
+        }
+        group.finish();
+    }
+}
+
+/// `size` of the pooled bitmaps `a` and `b`, from the built result.
+fn materialize_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    size: PairwiseSize,
+    a: &Pooled,
+    b: &Pooled,
+) {
+    let (a, b) = (B::pooled(a), B::pooled(b));
+    group.bench_function(
+        Id::new(format!("{}_materialize", B::NAME), batch_size),
+        |bench| {
+            bench.iter(|| size.materialize(a, b));
+        },
+    );
+}
+
+/// The size of an intersection or union, and the Jaccard index, without
+/// building the result, against building it and taking its length.
+///
+/// roaring 0.10 has no Jaccard index, so its participant derives one from
+/// the intersection and union lengths.
+pub fn bench_cardinality_ops(c: &mut Harness) {
+    bench_cardinality_op(
+        c,
+        PairwiseSize::IntersectionLen,
+        #[cfg(feature = "croaring")]
+        &[("croaring_and_cardinality", |a, b| {
+            a.and_cardinality(b) as f64
+        })],
+        #[cfg(feature = "roaring")]
+        &[("roaring_intersection_len", |a, b| {
+            a.intersection_len(b) as f64
+        })],
+    );
+    bench_cardinality_op(
+        c,
+        PairwiseSize::UnionLen,
+        #[cfg(feature = "croaring")]
+        &[("croaring_or_cardinality", |a, b| a.or_cardinality(b) as f64)],
+        #[cfg(feature = "roaring")]
+        &[("roaring_union_len", |a, b| a.union_len(b) as f64)],
+    );
+    bench_cardinality_op(
+        c,
+        PairwiseSize::JaccardIndex,
+        #[cfg(feature = "croaring")]
+        &[("croaring_jaccard_index", |a, b| a.jaccard_index(b))],
+        #[cfg(feature = "roaring")]
+        &[("roaring_from_lens", |a, b| {
+            a.intersection_len(b) as f64 / a.union_len(b) as f64
+        })],
+    );
+}
+
+/// A comparison of two bitmaps measured by [`bench_predicate`].
+#[derive(Debug, Clone, Copy)]
+enum Predicate {
+    Subset,
+    Superset,
+    Disjoint,
+    Equality,
+}
+
+impl Predicate {
+    fn name(&self) -> &'static str {
+        match self {
+            Predicate::Subset => "is_subset",
+            Predicate::Superset => "is_superset",
+            Predicate::Disjoint => "is_disjoint",
+            Predicate::Equality => "equality",
+        }
+    }
+
+    /// The check of `a` against `b`, written so that it returns false in
+    /// the early exit case.
+    fn test<B: BitmapImpl>(&self, a: &B, b: &B) -> bool {
+        match self {
+            Predicate::Subset => a.is_subset(b),
+            Predicate::Superset => b.is_superset(a),
+            Predicate::Disjoint => a.is_disjoint(b),
+            Predicate::Equality => a == b,
+        }
+    }
+}
+
+/// Subset, superset, disjointness and equality checks of a uniform bitmap
+/// against a second bitmap that is either an identical copy, which every
+/// check but disjointness must scan to the end, or the same values less the
+/// smallest, which every check can decide within the first container.
+///
+/// croaring has no superset or disjointness checks, so its adapter uses
+/// `is_subset` with the operands swapped and `intersect`.
+pub fn bench_predicates(c: &mut Harness) {
+    for predicate in [
+        Predicate::Subset,
+        Predicate::Superset,
+        Predicate::Disjoint,
+        Predicate::Equality,
+    ] {
+        bench_predicate(c, predicate);
+    }
+}
+
+fn bench_predicate(c: &mut Harness, predicate: Predicate) {
+    for case in ["identical", "early_exit"] {
+        let mut group = Group::new(c, format!("{}_{case}", predicate.name()));
+        group.tags(&["readonly", "query"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(Distribution::UniformSparse, 0), batch_size);
+            let other = match case {
+                "identical" => pooled.values(),
+                _ => &pooled.values()[1..],
+            };
+            for_each_impl!(predicate_case(
+                &mut group, batch_size, predicate, pooled, other
+            ));
+        }
+        group.finish();
+    }
+}
+
+/// `predicate` of the pooled bitmap against a bitmap of `other`.
+fn predicate_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    predicate: Predicate,
+    pooled: &Pooled,
+    other: &[u32],
+) {
+    let (a, b) = (B::pooled(pooled), B::from_sorted(other));
+    group.bench_function(Id::new(B::NAME, batch_size), |bench| {
+        bench.iter(|| predicate.test(a, &b));
+    });
+}
+
+pub fn bench_intersection(c: &mut Harness) {
+    bench_set_op(c, SetOp::Intersection);
+}
+
+pub fn bench_difference(c: &mut Harness) {
+    bench_set_op(c, SetOp::Difference);
+}
+
+pub fn bench_symmetric_difference(c: &mut Harness) {
+    bench_set_op(c, SetOp::SymmetricDifference);
+}
+
+/// The percentages of the candidates excluded in the exclusion groups.
+const EXCLUSION_PERCENTS: [u32; 3] = [1, 10, 50];
+
+/// Removing the values of one bitmap from another in place.
+type ExcludeOp<B> = fn(&mut B, &B);
+
+/// Filtering a set of candidates by an exclusion list, as a moderation or
+/// ACL check would, with the list holding a random [`EXCLUSION_PERCENTS`]
+/// of the candidates.
+///
+/// The set operations (`_andnot` and `_difference`, owned and `_inplace`)
+/// are compared with filtering the candidates by membership of the list
+/// into a new bitmap (`_rebuild`), and with removing the listed values one
+/// at a time (`_remove_each`), as roaring 0.10 has no `retain`. Each
+/// bitmap modified in place is cloned outside of the measurement.
+pub fn bench_exclusion(c: &mut Harness) {
+    use criterion::BatchSize;
+    use rand::Rng;
+    let mut rng = gen::rng("bench_exclusion");
+
+    for pct in EXCLUSION_PERCENTS {
+        let mut group = Group::new(c, format!("exclusion_{pct}pct"));
+        group.tags(&["setops", "mutate"]);
+        for &batch_size in &N {
+            let candidates = pool::get(generated(Distribution::UniformSparse, 0), batch_size);
+            let excluded = candidates
+                .values()
+                .iter()
+                .copied()
+                .filter(|_| rng.gen_ratio(pct, 100))
+                .collect::<Vec<u32>>();
+            group.dataset(*Fingerprint::of(candidates.values()).update(&excluded));
+            group.throughput(Throughput::Elements(batch_size as u64));
+
+            with_croaring! {
+                let (a, b) = (candidates.croaring(), &croaring::Bitmap::of(&excluded));
+                let owned: [(&str, RefOp<croaring::Bitmap>); 2] = [
+                    ("croaring_andnot", |a, b| a.andnot(b)),
+                    ("croaring_rebuild", |a, b| a.iter().filter(|&v| !b.contains(v)).collect()),
+                ];
+                for (name, op) in owned {
+                    group.bench_function(Id::new(name, batch_size), |bench| {
+                        bench.iter(|| op(a, b));
+                    });
+                }
+                let inplace: [(&str, ExcludeOp<croaring::Bitmap>); 2] = [
+                    ("croaring_andnot_inplace", |a, b| a.andnot_inplace(b)),
+                    ("croaring_remove_each", |a, b| b.iter().for_each(|v| a.remove(v))),
+                ];
+                for (name, op) in inplace {
+                    group.bench_function(Id::new(name, batch_size), |bench| {
+                        bench.iter_batched_ref(|| a.clone(), |a| op(a, b), BatchSize::LargeInput);
+                    });
+                }
+            }
+            with_roaring! {
+                let excluded = RoaringBitmap::from_sorted_iter(excluded.iter().copied()).unwrap();
+                let (a, b) = (candidates.roaring(), &excluded);
+                let owned: [(&str, RefOp<RoaringBitmap>); 2] = [
+                    ("roaring_difference", |a, b| a - b),
+                    ("roaring_rebuild", |a, b| {
+                        RoaringBitmap::from_sorted_iter(a.iter().filter(|&v| !b.contains(v)))
+                            .unwrap()
+                    }),
+                ];
+                for (name, op) in owned {
+                    group.bench_function(Id::new(name, batch_size), |bench| {
+                        bench.iter(|| op(a, b));
+                    });
+                }
+                let inplace: [(&str, ExcludeOp<RoaringBitmap>); 2] = [
+                    ("roaring_difference_inplace", |a, b| *a -= b),
+                    ("roaring_remove_each", |a, b| {
+                        b.iter().for_each(|v| {
+                            a.remove(v);
+                        })
+                    }),
+                ];
+                for (name, op) in inplace {
+                    group.bench_function(Id::new(name, batch_size), |bench| {
+                        bench.iter_batched_ref(|| a.clone(), |a| op(a, b), BatchSize::LargeInput);
+                    });
+                }
+            }
+        }
+        group.finish();
+    }
+}
+
+/// The size of the larger operand of the asymmetric set operation groups.
+const ASYMMETRIC_LARGE: u32 = 1_000_000;
+
+/// The ratios of the larger to the smaller operand of the asymmetric set
+/// operation groups.
+const ASYMMETRIC_RATIOS: [u32; 3] = [10, 1_000, 100_000];
+
+/// Set operations between a large bitmap and one [`ASYMMETRIC_RATIOS`]
+/// times smaller, as when a small filter meets a large posting list, with
+/// the small operand first (`_small_lhs`) and second (`_large_lhs`).
+///
+/// Both operands are drawn uniformly from the same universe, so the small
+/// operand touches only a few of the large one's containers, which is where
+/// galloping and container skipping pay off.
+pub fn bench_asymmetric_set_ops(c: &mut Harness) {
+    bench_asymmetric_set_op(
+        c,
+        "intersection",
+        #[cfg(feature = "croaring")]
+        |a, b| a & b,
+        #[cfg(feature = "roaring")]
+        |a, b| a & b,
+    );
+    bench_asymmetric_set_op(
+        c,
+        "difference",
+        #[cfg(feature = "croaring")]
+        |a, b| a - b,
+        #[cfg(feature = "roaring")]
+        |a, b| a - b,
+    );
+    bench_asymmetric_set_op(
+        c,
+        "union",
+        #[cfg(feature = "croaring")]
+        |a, b| a | b,
+        #[cfg(feature = "roaring")]
+        |a, b| a | b,
+    );
+}
+
+fn bench_asymmetric_set_op(
+    c: &mut Harness,
+    name: &str,
+    #[cfg(feature = "croaring")] croaring_op: RefOp<croaring::Bitmap>,
+    #[cfg(feature = "roaring")] roaring_op: RefOp<RoaringBitmap>,
+) {
+    use rand::seq::index;
+    let mut rng = gen::rng(&format!("bench_asymmetric_set_op/{name}"));
+
+    let large = pool::get(generated(Distribution::UniformSparse, 0), ASYMMETRIC_LARGE);
+    let universe = gen::universe(ASYMMETRIC_LARGE, gen::DEFAULT_DENSITY);
+
+    let mut group = Group::new(c, format!("{name}_asymmetric"));
+    group.tags(&["readonly", "setops"]);
+    for ratio in ASYMMETRIC_RATIOS {
+        let small_size = ASYMMETRIC_LARGE / ratio;
+        let mut small = index::sample(&mut rng, universe as usize, small_size as usize)
+            .into_iter()
+            .map(|v| v as u32)
+            .collect::<Vec<_>>();
+        small.sort_unstable();
+        group.dataset(Fingerprint::of(&small));
+        group.throughput(Throughput::Elements((ASYMMETRIC_LARGE + small_size) as u64));
+        with_croaring! {
+            let (l, s) = (large.croaring(), croaring::Bitmap::of(&small));
+            group.bench_function(Id::new("croaring_small_lhs", ratio), |b| {
+                b.iter(|| croaring_op(&s, l));
+            });
+            group.bench_function(Id::new("croaring_large_lhs", ratio), |b| {
+                b.iter(|| croaring_op(l, &s));
+            });
+        }
+        with_roaring! {
+            let l = large.roaring();
+            let s = RoaringBitmap::from_sorted_iter(small.iter().copied()).unwrap();
+            group.bench_function(Id::new("roaring_small_lhs", ratio), |b| {
+                b.iter(|| roaring_op(&s, l));
+            });
+            group.bench_function(Id::new("roaring_large_lhs", ratio), |b| {
+                b.iter(|| roaring_op(l, &s));
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark an in-place set operation accumulating the `n` even values
+/// into a copy of the range `0..n`, and then one set of each
+/// [`Distribution`] into another. The accumulator is reset to a fresh copy
+/// before every iteration, outside of the measurement.
+#[cfg_attr(not(feature = "dense-baselines"), allow(unused_variables))]
+fn bench_inplace_op(
+    c: &mut Harness,
+    name: &str,
+    #[cfg(feature = "croaring")] croaring_op: fn(&mut croaring::Bitmap, &croaring::Bitmap),
+    #[cfg(feature = "roaring")] roaring_op: fn(&mut RoaringBitmap, &RoaringBitmap),
+    flat_op: fn(&mut FlatBitSet, &FlatBitSet),
+) {
+    use criterion::BatchSize;
+
+    for (name, distribution, workload_a, workload_b) in set_op_variants(name) {
+        let mut group = Group::new(c, name);
+        group.tags(&["mutate", "setops"]);
+        if distribution.is_some() {
+            group.tags(&["distribution"]);
+        }
+        for &batch_size in &N {
+            let set_a = pool::get(workload_a, batch_size);
+            let set_b = pool::get(workload_b, batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64 * 2));
+
+            with_croaring! {
+                let (a, b) = (set_a.croaring(), set_b.croaring());
+                group.bench_function(Id::new("croaring", batch_size), |bench| {
+                    bench.iter_batched_ref(
+                        || a.clone(),
+                        |acc| croaring_op(acc, b),
+                        BatchSize::SmallInput,
+                    );
+                });
+            }
+            with_roaring! {
+                let (a, b) = (set_a.roaring(), set_b.roaring());
+                group.bench_function(Id::new("roaring", batch_size), |bench| {
+                    bench.iter_batched_ref(
+                        || a.clone(),
+                        |acc| roaring_op(acc, b),
+                        BatchSize::SmallInput,
+                    );
+                });
+            }
+
+            #[cfg(feature = "dense-baselines")]
+            dense::inplace(
+                &mut group,
+                batch_size,
+                flat_op,
+                set_a.values(),
+                set_b.values(),
+            );
+        }
+        group.finish();
+    }
+}
+
+/// In-place union, intersection, difference and symmetric difference
+/// (`|=`, `&=`, `-=` and `^=`).
+pub fn bench_inplace_set_ops(c: &mut Harness) {
+    bench_inplace_op(
+        c,
+        "union_inplace",
+        #[cfg(feature = "croaring")]
+        |a, b| *a |= b,
+        #[cfg(feature = "roaring")]
+        |a, b| *a |= b,
+        |a, b| *a |= b,
+    );
+    bench_inplace_op(
+        c,
+        "intersection_inplace",
+        #[cfg(feature = "croaring")]
+        |a, b| *a &= b,
+        #[cfg(feature = "roaring")]
+        |a, b| *a &= b,
+        |a, b| *a &= b,
+    );
+    bench_inplace_op(
+        c,
+        "difference_inplace",
+        #[cfg(feature = "croaring")]
+        |a, b| *a -= b,
+        #[cfg(feature = "roaring")]
+        |a, b| *a -= b,
+        |a, b| *a -= b,
+    );
+    bench_inplace_op(
+        c,
+        "symmetric_difference_inplace",
+        #[cfg(feature = "croaring")]
+        |a, b| *a ^= b,
+        #[cfg(feature = "roaring")]
+        |a, b| *a ^= b,
+        |a, b| *a ^= b,
+    );
+}
+
+/// `bytes`, a portable serialization without run containers, with the
+/// container count of its header one too high.
+///
+/// Unlike corrupting a value, which passes unnoticed in a bitset container,
+/// this is rejected whatever the containers are: the descriptions, offsets
+/// and data that follow the count are all misread after the first, and the
+/// containers run past the end of the input.
+fn corrupt_header(bytes: &[u8]) -> Vec<u8> {
+    /// The cookie of a portable serialization without run containers.
+    const NO_RUN_COOKIE: u32 = 12_346;
+
+    let mut corrupted = bytes.to_vec();
+    if let Some(header) = corrupted.get_mut(..8) {
+        assert_eq!(
+            header[..4],
+            NO_RUN_COOKIE.to_le_bytes(),
+            "not a portable serialization"
+        );
+        let count = u32::from_le_bytes([header[4], header[5], header[6], header[7]]);
+        header[4..].copy_from_slice(&(count + 1).to_le_bytes());
+    }
+    corrupted
+}
+
+/// The portable serialization of `values` without run containers, which
+/// both backends can read, written by whichever backend is enabled.
+fn portable_bytes(values: &[u32]) -> Vec<u8> {
+    #[cfg(feature = "roaring")]
+    {
+        let mut bytes = Vec::new();
+        let bm = values.iter().collect::<RoaringBitmap>();
+        bm.serialize_into(&mut bytes).unwrap();
+        bytes
+    }
+    #[cfg(all(feature = "croaring", not(feature = "roaring")))]
+    {
+        croaring::Bitmap::of(values).serialize()
+    }
+    #[cfg(not(any(feature = "croaring", feature = "roaring")))]
+    {
+        Vec::new()
+    }
+}
+
+/// The inputs the serialization groups are run against: sparse values, the
+/// dense even values, and run-heavy values (run-optimised for croaring).
+fn serialization_inputs() -> [(&'static str, Workload, bool); 3] {
+    [
+        ("sparse", generated(Distribution::UniformSparse, 0), false),
+        ("dense", Workload::AlternateEven, false),
+        ("run_heavy", generated(Distribution::RunHeavy, 0), true),
+    ]
+}
+
+/// Serialization of each backend's bitmap into a new `Vec` in the portable
+/// format. Throughput is the serialized size of the backend's own output,
+/// which the `sersize` binary tabulates for every format and distribution.
+pub fn bench_serialize(c: &mut Harness) {
+    for (input, workload, rle) in serialization_inputs() {
+        let mut group = Group::new(c, format!("serialize_{input}"));
+        group.tags(&["serde"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            for_each_impl!(serialize_case(&mut group, batch_size, pooled, rle));
+        }
+        group.finish();
+    }
+}
+
+/// Deserialization of the portable format written by each backend itself.
+pub fn bench_deserialize(c: &mut Harness) {
+    for (input, workload, rle) in serialization_inputs() {
+        let mut group = Group::new(c, format!("deserialize_{input}"));
+        group.tags(&["serde"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            for_each_impl!(deserialize_case(&mut group, batch_size, pooled, rle));
+        }
+        group.finish();
+    }
+}
+
+/// The pooled bitmap, or its run-length optimised form if `rle`.
+fn serialization_input<B: BitmapImpl>(pooled: &Pooled, rle: bool) -> &B {
+    if rle {
+        B::pooled_rle(pooled)
+    } else {
+        B::pooled(pooled)
+    }
+}
+
+/// Serialize the input of `pooled`, see [`serialization_input`].
+fn serialize_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    rle: bool,
+) {
+    let bm = serialization_input::<B>(pooled, rle);
+    group.throughput(Throughput::Bytes(bm.serialized_size() as u64));
+    group.bench_with_input(Id::new(B::NAME, batch_size), bm, |b, bm| {
+        b.iter(|| bm.serialize());
+    });
+}
+
+/// Deserialize the serialization of the input of `pooled`, see
+/// [`serialization_input`].
+fn deserialize_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    rle: bool,
+) {
+    let bytes = serialization_input::<B>(pooled, rle).serialize();
+    group.throughput(Throughput::Bytes(bytes.len() as u64));
+    group.bench_with_input(Id::new(B::NAME, batch_size), &bytes, |b, bytes| {
+        b.iter(|| B::deserialize(bytes));
+    });
+}
+
+/// The cold query path of a storage engine: deserializing a bitmap read
+/// from disk, querying it once and dropping it, timed as a single unit.
+///
+/// The `_intersect` groups intersect the stored bitmap with a resident one
+/// of independent uniform values, and the `_contains` groups look up its
+/// middle value. `croaring_view` reads the bytes in place through a
+/// `BitmapView` rather than copying every container out of them, and
+/// `roaring_unchecked` skips the validation of roaring's eager parse.
+pub fn bench_cold_query(c: &mut Harness) {
+    for (input, workload, rle) in serialization_inputs() {
+        for query in ["intersect", "contains"] {
+            let intersect = query == "intersect";
+            let mut group = Group::new(c, format!("cold_{query}_{input}"));
+            group.tags(&["serde", "query"]);
+            for &batch_size in &N {
+                let pooled = pool::get(workload, batch_size);
+                let resident = pool::get(generated(Distribution::UniformSparse, 1), batch_size);
+                let target = pooled.values()[pooled.values().len() / 2];
+                with_croaring! {
+                    let stored = if rle {
+                        pooled.croaring_rle()
+                    } else {
+                        pooled.croaring()
+                    };
+                    let bytes = stored.serialize();
+                    let resident = resident.croaring();
+                    group.throughput(Throughput::Bytes(bytes.len() as u64));
+                    group.bench_with_input(Id::new("croaring", batch_size), &bytes, |b, bytes| {
+                        b.iter(|| {
+                            let bm = croaring::Bitmap::try_deserialize(bytes).unwrap();
+                            if intersect {
+                                bm.and_cardinality(resident)
+                            } else {
+                                u64::from(bm.contains(target))
+                            }
+                        });
+                    });
+                    group.bench_with_input(
+                        Id::new("croaring_view", batch_size),
+                        &bytes,
+                        |b, bytes| {
+                            b.iter(|| {
+                                // SAFETY: the bytes are croaring's own portable
+                                // serialization.
+                                let view = unsafe { croaring::BitmapView::deserialize(bytes) };
+                                if intersect {
+                                    view.and_cardinality(resident)
+                                } else {
+                                    u64::from(view.contains(target))
+                                }
+                            });
+                        },
+                    );
+                }
+                with_roaring! {
+                    let mut bytes = Vec::new();
+                    pooled.roaring().serialize_into(&mut bytes).unwrap();
+                    let resident = resident.roaring();
+                    group.throughput(Throughput::Bytes(bytes.len() as u64));
+                    group.bench_with_input(Id::new("roaring", batch_size), &bytes, |b, bytes| {
+                        b.iter(|| {
+                            let bm = RoaringBitmap::deserialize_from(bytes.as_slice()).unwrap();
+                            if intersect {
+                                bm.intersection_len(resident)
+                            } else {
+                                u64::from(bm.contains(target))
+                            }
+                        });
+                    });
+                    group.bench_with_input(
+                        Id::new("roaring_unchecked", batch_size),
+                        &bytes,
+                        |b, bytes| {
+                            b.iter(|| {
+                                let bm = RoaringBitmap::deserialize_unchecked_from(bytes.as_slice())
+                                    .unwrap();
+                                if intersect {
+                                    bm.intersection_len(resident)
+                                } else {
+                                    u64::from(bm.contains(target))
+                                }
+                            });
+                        },
+                    );
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// Opening a stored bitmap and querying it, through croaring's frozen
+/// format (which is read in place, as from a memory-mapped file) against
+/// the portable format each backend deserializes eagerly.
+///
+/// The `_contains` groups look up the middle value and the `_iterate`
+/// groups sum every value. `croaring_frozen_view` and
+/// `croaring_portable_view` query a `BitmapView` of the bytes, while
+/// `croaring_frozen_copy` copies the frozen view out into an owned bitmap
+/// first, as a caller needing to mutate it would.
+pub fn bench_frozen_view(c: &mut Harness) {
+    for (input, workload, rle) in serialization_inputs() {
+        for access in ["contains", "iterate"] {
+            let iterate = access == "iterate";
+            let mut group = Group::new(c, format!("frozen_{access}_{input}"));
+            group.tags(&["serde", "query"]);
+            if iterate {
+                group.tags(&["iter"]);
+            }
+            for &batch_size in &N {
+                let pooled = pool::get(workload, batch_size);
+                let target = pooled.values()[pooled.values().len() / 2];
+                if iterate {
+                    group.throughput(Throughput::Elements(pooled.values().len() as u64));
+                }
+                with_croaring! {
+                    let query = |bm: &croaring::Bitmap| {
+                        if iterate {
+                            bm.iter().map(u64::from).sum::<u64>()
+                        } else {
+                            u64::from(bm.contains(target))
+                        }
+                    };
+                    let stored = if rle {
+                        pooled.croaring_rle()
+                    } else {
+                        pooled.croaring()
+                    };
+                    let mut buf = Vec::new();
+                    let frozen = stored.serialize_frozen_into(&mut buf);
+                    let portable = stored.serialize();
+                    group.bench_function(Id::new("croaring_frozen_view", batch_size), |b| {
+                        b.iter(|| {
+                            // SAFETY: the bytes are a whole frozen serialization,
+                            // aligned by serialize_frozen_into.
+                            let view = unsafe { croaring::BitmapView::deserialize_frozen(frozen) };
+                            query(&view)
+                        });
+                    });
+                    group.bench_function(Id::new("croaring_frozen_copy", batch_size), |b| {
+                        b.iter(|| {
+                            // SAFETY: as above.
+                            let view = unsafe { croaring::BitmapView::deserialize_frozen(frozen) };
+                            query(&view.to_bitmap())
+                        });
+                    });
+                    group.bench_function(Id::new("croaring_portable_view", batch_size), |b| {
+                        b.iter(|| {
+                            // SAFETY: the bytes are croaring's own portable
+                            // serialization.
+                            let view = unsafe { croaring::BitmapView::deserialize(&portable) };
+                            query(&view)
+                        });
+                    });
+                    group.bench_function(Id::new("croaring_portable", batch_size), |b| {
+                        b.iter(|| query(&croaring::Bitmap::try_deserialize(&portable).unwrap()));
+                    });
+                }
+                with_roaring! {
+                    let mut bytes = Vec::new();
+                    pooled.roaring().serialize_into(&mut bytes).unwrap();
+                    group.bench_function(Id::new("roaring", batch_size), |b| {
+                        b.iter(|| {
+                            let bm = RoaringBitmap::deserialize_from(bytes.as_slice()).unwrap();
+                            if iterate {
+                                bm.iter().map(u64::from).sum::<u64>()
+                            } else {
+                                u64::from(bm.contains(target))
+                            }
+                        });
+                    });
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// Round trips through the portable format between the two backends:
+/// serializing with one and deserializing with the other. The setup checks
+/// that each round trip preserves the values.
+///
+/// roaring 0.10 cannot read run containers, so croaring serializes its
+/// bitmaps without running `run_optimize` first.
+#[cfg(all(feature = "croaring", feature = "roaring"))]
+pub fn bench_interop_roundtrip(c: &mut Harness) {
+    for (input, workload, _) in serialization_inputs() {
+        let mut group = Group::new(c, format!("interop_roundtrip_{input}"));
+        group.tags(&["serde", "interop"]);
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            let (croaring, roaring) = (pooled.croaring(), pooled.roaring());
+
+            let from_croaring = RoaringBitmap::deserialize_from(croaring.serialize().as_slice())
+                .expect("roaring failed to read croaring's output");
+            assert!(
+                from_croaring.iter().eq(croaring.iter()),
+                "croaring to roaring round trip of {} {} changed the values",
+                input,
+                batch_size
+            );
+            let mut bytes = Vec::new();
+            roaring.serialize_into(&mut bytes).unwrap();
+            let from_roaring = croaring::Bitmap::try_deserialize(&bytes)
+                .expect("croaring failed to read roaring's output");
+            assert!(
+                from_roaring.iter().eq(roaring.iter()),
+                "roaring to croaring round trip of {} {} changed the values",
+                input,
+                batch_size
+            );
+
+            group.throughput(Throughput::Bytes(bytes.len() as u64));
+            group.bench_with_input(
+                Id::new("croaring_to_roaring", batch_size),
+                croaring,
+                |b, bm| {
+                    b.iter(|| RoaringBitmap::deserialize_from(bm.serialize().as_slice()).unwrap());
+                },
+            );
+            group.bench_with_input(
+                Id::new("roaring_to_croaring", batch_size),
+                roaring,
+                |b, bm| {
+                    b.iter(|| {
+                        let mut buf = Vec::with_capacity(bm.serialized_size());
+                        bm.serialize_into(&mut buf).unwrap();
+                        croaring::Bitmap::try_deserialize(&buf).unwrap()
+                    });
+                },
+            );
+        }
+        group.finish();
+    }
+}
+
+/// Building a bitmap of one backend from one of another.
+type ConvertOp<A, B> = fn(&A) -> B;
+
+/// Building one backend's bitmap of each distribution from the other's, as
+/// in a gradual migration between the two, through the iterator of the
+/// source compared with a round trip through the portable format.
+///
+/// `_collect` collects the source's iterator, `croaring_to_roaring_sorted`
+/// uses `from_sorted_iter` and `roaring_to_croaring_buffered` collects into
+/// a `Vec` for `Bitmap::of`. `_serde` is the round trip of
+/// `interop_roundtrip`.
+#[cfg(all(feature = "croaring", feature = "roaring"))]
+pub fn bench_interop_iter(c: &mut Harness) {
+    for distribution in Distribution::ALL {
+        let mut group = Group::new(c, format!("interop_iter_{distribution}"));
+        group.tags(&["iter", "interop", "distribution"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            let (croaring, roaring) = (pooled.croaring(), pooled.roaring());
+            assert!(
+                croaring.iter().collect::<RoaringBitmap>() == *roaring,
+                "croaring to roaring of {} {} changed the values",
+                distribution,
+                batch_size
+            );
+            group.throughput(Throughput::Elements(batch_size as u64));
+
+            let to_roaring: [(&str, ConvertOp<croaring::Bitmap, RoaringBitmap>); 3] = [
+                ("croaring_to_roaring_collect", |bm| bm.iter().collect()),
+                ("croaring_to_roaring_sorted", |bm| {
+                    RoaringBitmap::from_sorted_iter(bm.iter()).unwrap()
+                }),
+                ("croaring_to_roaring_serde", |bm| {
+                    RoaringBitmap::deserialize_from(bm.serialize().as_slice()).unwrap()
+                }),
+            ];
+            for (name, convert) in to_roaring {
+                group.bench_with_input(Id::new(name, batch_size), croaring, |b, bm| {
+                    b.iter(|| convert(bm));
+                });
+            }
+
+            let to_croaring: [(&str, ConvertOp<RoaringBitmap, croaring::Bitmap>); 3] = [
+                ("roaring_to_croaring_collect", |bm| bm.iter().collect()),
+                ("roaring_to_croaring_buffered", |bm| {
+                    croaring::Bitmap::of(&bm.iter().collect::<Vec<u32>>())
+                }),
+                ("roaring_to_croaring_serde", |bm| {
+                    let mut buf = Vec::with_capacity(bm.serialized_size());
+                    bm.serialize_into(&mut buf).unwrap();
+                    croaring::Bitmap::try_deserialize(&buf).unwrap()
+                }),
+            ];
+            for (name, convert) in to_croaring {
+                group.bench_with_input(Id::new(name, batch_size), roaring, |b, bm| {
+                    b.iter(|| convert(bm));
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Operations over the real-world corpora of [`roaring_bench::datasets`],
+/// when [`datasets::DIR_ENV`] is set. Each case name is the dataset, and the
+/// set operations combine each of its bitmaps with the next, as in
+/// CRoaring's own real-data benchmarks.
+pub fn bench_datasets(c: &mut Harness) {
+    use roaring_bench::datasets;
+
+    let Some(dir) = datasets::dir_from_env() else {
+        return;
+    };
+    let loaded = datasets::load_all(&dir)
+        .unwrap_or_else(|e| panic!("failed to load datasets from {}: {}", dir.display(), e));
+    if loaded.is_empty() {
+        if !harness::is_listing() {
+            println!("no datasets found under {}", dir.display());
+        }
+        return;
+    }
+
+    // Each backend's bitmaps of each dataset, in the order of `loaded`.
+    #[cfg(feature = "croaring")]
+    let croaring_inputs = loaded
+        .iter()
+        .map(|d| {
+            d.bitmaps
+                .iter()
+                .map(|v| croaring::Bitmap::of(v))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    #[cfg(feature = "roaring")]
+    let roaring_inputs = loaded
+        .iter()
+        .map(|d| {
+            d.bitmaps
+                .iter()
+                .map(|v| RoaringBitmap::from_sorted_iter(v.iter().copied()).unwrap())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    type Pairwise<T> = fn(&T, &T) -> u64;
+    let names = [
+        "dataset_intersection",
+        "dataset_union",
+        "dataset_difference",
+    ];
+    #[cfg(feature = "croaring")]
+    let croaring_ops: [Pairwise<croaring::Bitmap>; 3] = [
+        |a, b| a.and(b).cardinality(),
+        |a, b| a.or(b).cardinality(),
+        |a, b| a.andnot(b).cardinality(),
+    ];
+    #[cfg(feature = "roaring")]
+    let roaring_ops: [Pairwise<RoaringBitmap>; 3] = [
+        |a, b| (a & b).len(),
+        |a, b| (a | b).len(),
+        |a, b| (a - b).len(),
+    ];
+    for (op, name) in names.iter().enumerate() {
+        let mut group = Group::new(c, *name);
+        group.tags(&["readonly", "setops", "corpus"]);
+        for (i, dataset) in loaded.iter().enumerate() {
+            group.dataset(Fingerprint::of_all(&dataset.bitmaps));
+            group.throughput(Throughput::Elements(dataset.len() as u64));
+            with_croaring! {
+                let (croaring, croaring_op) = (&croaring_inputs[i], croaring_ops[op]);
+                group.bench_function(Id::new("croaring", &dataset.name), |b| {
+                    b.iter(|| {
+                        croaring
+                            .windows(2)
+                            .map(|w| croaring_op(&w[0], &w[1]))
+                            .sum::<u64>()
+                    });
+                });
+            }
+            with_roaring! {
+                let (roaring, roaring_op) = (&roaring_inputs[i], roaring_ops[op]);
+                group.bench_function(Id::new("roaring", &dataset.name), |b| {
+                    b.iter(|| {
+                        roaring
+                            .windows(2)
+                            .map(|w| roaring_op(&w[0], &w[1]))
+                            .sum::<u64>()
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+
+    let mut group = Group::new(c, "dataset_iterate");
+    group.tags(&["readonly", "iter", "corpus"]);
+    for (i, dataset) in loaded.iter().enumerate() {
+        group.dataset(Fingerprint::of_all(&dataset.bitmaps));
+        group.throughput(Throughput::Elements(dataset.len() as u64));
+        with_croaring! {
+            let croaring = &croaring_inputs[i];
+            group.bench_function(Id::new("croaring", &dataset.name), |b| {
+                b.iter(|| croaring.iter().map(|bm| bm.iter().count()).sum::<usize>());
+            });
+        }
+        with_roaring! {
+            let roaring = &roaring_inputs[i];
+            group.bench_function(Id::new("roaring", &dataset.name), |b| {
+                b.iter(|| roaring.iter().map(|bm| bm.iter().count()).sum::<usize>());
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The width of each range removed by the remove_range group.
+const REMOVE_RANGE_WIDTH: u32 = 1_024;
+
+/// How the remove groups empty a bitmap.
+#[derive(Clone, Copy)]
+enum Removal {
+    Sequential,
+    Shuffled,
+    Range,
+    Clear,
+}
+
+/// Emptying a bitmap of the `n` even values by removing them in ascending
+/// order, in a random order, by successive 1024-wide ranges, and with a
+/// single `clear`. Each iteration starts from a fresh copy, made outside of
+/// the measurement.
+pub fn bench_remove(c: &mut Harness) {
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("bench_remove");
+
+    let sizes = N
+        .iter()
+        .map(|&batch_size| {
+            let mut shuffled = pool::get(Workload::Even, batch_size).values().to_vec();
+            shuffled.shuffle(&mut rng);
+            (batch_size, shuffled)
+        })
+        .collect::<Vec<_>>();
+
+    for (name, removal) in [
+        ("remove_sequential", Removal::Sequential),
+        ("remove_shuffled", Removal::Shuffled),
+        ("remove_range", Removal::Range),
+        ("clear", Removal::Clear),
+    ] {
+        let mut group = Group::new(c, name);
+        group.tags(&["mutate"]);
+        for (batch_size, shuffled) in &sizes {
+            let batch_size = *batch_size;
+            let pooled = pool::get(Workload::Even, batch_size);
+            let order = match removal {
+                Removal::Shuffled => {
+                    group.dataset(Fingerprint::of(shuffled));
+                    shuffled.as_slice()
+                }
+                _ => pooled.values(),
+            };
+            group.throughput(Throughput::Elements(batch_size as u64));
+            for_each_impl!(remove_case(&mut group, batch_size, pooled, removal, order));
+        }
+        group.finish();
+    }
+}
+
+/// Remove the values of the pooled bitmap from a fresh copy, as `removal`.
+fn remove_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    removal: Removal,
+    order: &[u32],
+) {
+    use criterion::BatchSize;
+
+    group.bench_function(Id::new(B::NAME, batch_size), |b| {
+        b.iter_batched_ref(
+            || B::pooled(pooled).clone(),
+            |bm| match removal {
+                Removal::Range => {
+                    let width = REMOVE_RANGE_WIDTH as usize;
+                    let starts = (0..batch_size * 2).step_by(width);
+                    for start in starts {
+                        bm.remove_range(start..start + REMOVE_RANGE_WIDTH);
+                    }
+                }
+                Removal::Clear => bm.clear(),
+                Removal::Sequential | Removal::Shuffled => {
+                    for &v in order {
+                        bm.remove(v);
+                    }
+                }
+            },
+            BatchSize::SmallInput,
+        );
+    });
+}
+
+/// Building a bitmap from a prepared slice through each backend's batch
+/// APIs, from sorted input and from the same values shuffled.
+///
+/// `from_sorted_iter` requires sorted input, so it only appears in the
+/// sorted group.
+pub fn bench_bulk_construct(c: &mut Harness) {
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("bench_bulk_construct");
+
+    for sorted in [true, false] {
+        let mut group = Group::new(
+            c,
+            if sorted {
+                "bulk_construct_sorted"
+            } else {
+                "bulk_construct_unsorted"
+            },
+        );
+        group.tags(&["construct"]);
+        for &batch_size in &N {
+            let mut values = pool::get(generated(Distribution::UniformSparse, 0), batch_size)
+                .values()
+                .to_vec();
+            if !sorted {
+                values.shuffle(&mut rng);
+            }
+            group.dataset(Fingerprint::of(&values));
+            group.throughput(Throughput::Elements(batch_size as u64));
+
+            with_croaring! {
+                group.bench_with_input(Id::new("croaring_of", batch_size), &values, |b, v| {
+                    b.iter(|| croaring::Bitmap::of(v));
+                });
+                group.bench_with_input(Id::new("croaring_add_many", batch_size), &values, |b, v| {
+                    b.iter(|| {
+                        let mut bm = croaring::Bitmap::create();
+                        bm.add_many(v);
+                        bm
+                    });
+                });
+                group.bench_with_input(Id::new("croaring_collect", batch_size), &values, |b, v| {
+                    b.iter(|| v.iter().copied().collect::<croaring::Bitmap>());
+                });
+            }
+            if sorted {
+                with_roaring! {
+                    group.bench_with_input(
+                        Id::new("roaring_from_sorted_iter", batch_size),
+                        &values,
+                        |b, v| {
+                            b.iter(|| RoaringBitmap::from_sorted_iter(v.iter().copied()).unwrap());
+                        },
+                    );
+                }
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring_collect", batch_size), &values, |b, v| {
+                    b.iter(|| v.iter().copied().collect::<RoaringBitmap>());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Materializing a query result held in a `Vec<u32>` as a ready-to-use
+/// bitmap, sorted and shuffled, for each distribution: construction from
+/// the slice followed by croaring's `run_optimize`, where the bulk_construct
+/// groups stop at construction.
+///
+/// roaring has no run containers, so its bitmap is ready once built.
+pub fn bench_materialize(c: &mut Harness) {
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("bench_materialize");
+
+    for distribution in Distribution::ALL {
+        for sorted in [true, false] {
+            let order = if sorted { "sorted" } else { "unsorted" };
+            let mut group = Group::new(c, format!("materialize_{order}_{distribution}"));
+            group.tags(&["construct", "distribution", "rle"]);
+            for &batch_size in &N {
+                let mut values = pool::get(generated(distribution, 0), batch_size)
+                    .values()
+                    .to_vec();
+                if !sorted {
+                    values.shuffle(&mut rng);
+                }
+                group.dataset(Fingerprint::of(&values));
+                group.throughput(Throughput::Elements(batch_size as u64));
+
+                with_croaring! {
+                    group.bench_with_input(Id::new("croaring_of", batch_size), &values, |b, v| {
+                        b.iter(|| {
+                            let mut bm = croaring::Bitmap::of(v);
+                            bm.run_optimize();
+                            bm
+                        });
+                    });
+                    group.bench_with_input(
+                        Id::new("croaring_add_many", batch_size),
+                        &values,
+                        |b, v| {
+                            b.iter(|| {
+                                let mut bm = croaring::Bitmap::create();
+                                bm.add_many(v);
+                                bm.run_optimize();
+                                bm
+                            });
+                        },
+                    );
+                }
+                with_roaring! {
+                    if sorted {
+                        group.bench_with_input(
+                            Id::new("roaring_from_sorted_iter", batch_size),
+                            &values,
+                            |b, v| {
+                                b.iter(|| {
+                                    RoaringBitmap::from_sorted_iter(v.iter().copied()).unwrap()
+                                });
+                            },
+                        );
+                    }
+                    group.bench_with_input(Id::new("roaring_collect", batch_size), &values, |b, v| {
+                        b.iter(|| v.iter().copied().collect::<RoaringBitmap>());
+                    });
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// The universe of the bitmaps aggregated by the multi-operand groups.
+const MULTI_UNIVERSE: u32 = 1_000_000;
+
+/// The densities cycled through by the bitmaps of the multi-operand groups.
+const MULTI_DENSITIES: [f64; 4] = [0.001, 0.01, 0.1, 0.5];
+
+/// Union and intersection of K bitmaps of varying density, by folding with
+/// the pairwise operators and through each backend's multi-operand APIs.
+/// Throughput is reported in operand bitmaps.
+///
+/// croaring has no multi-operand intersection, so only its fold appears in
+/// the intersection group.
+pub fn bench_multi_op(c: &mut Harness) {
+    #[cfg(feature = "roaring")]
+    use roaring::MultiOps;
+    let mut rng = gen::rng("bench_multi_op");
+
+    const K: [usize; 4] = [2, 8, 64, 256];
+
+    let values = (0..K[K.len() - 1])
+        .map(|i| {
+            let density = MULTI_DENSITIES[i % MULTI_DENSITIES.len()];
+            let size = (MULTI_UNIVERSE as f64 * density) as u32;
+            gen::generate(Distribution::UniformSparse, size, density, &mut rng)
+        })
+        .collect::<Vec<_>>();
+    #[cfg(feature = "croaring")]
+    let croaring = values
+        .iter()
+        .map(|v| croaring::Bitmap::of(v))
+        .collect::<Vec<_>>();
+    #[cfg(feature = "roaring")]
+    let roaring = values
+        .iter()
+        .map(|v| RoaringBitmap::from_sorted_iter(v.iter().copied()).unwrap())
+        .collect::<Vec<_>>();
+
+    let mut group = Group::new(c, "multi_union");
+    group.tags(&["readonly", "setops"]);
+    for k in K {
+        group.dataset(Fingerprint::of_all(&values[..k]));
+        group.throughput(Throughput::Elements(k as u64));
+
+        with_croaring! {
+            let croaring = &croaring[..k];
+            let refs = croaring.iter().collect::<Vec<_>>();
+            group.bench_function(Id::new("croaring_fold", k), |b| {
+                b.iter(|| {
+                    croaring
+                        .iter()
+                        .fold(croaring::Bitmap::create(), |mut acc, bm| {
+                            acc.or_inplace(bm);
+                            acc
+                        })
+                });
+            });
+            group.bench_function(Id::new("croaring_fast_or", k), |b| {
+                b.iter(|| croaring::Bitmap::fast_or(&refs));
+            });
+            group.bench_function(Id::new("croaring_fast_or_heap", k), |b| {
+                b.iter(|| croaring::Bitmap::fast_or_heap(&refs));
+            });
+        }
+        with_roaring! {
+            let roaring = &roaring[..k];
+            group.bench_function(Id::new("roaring_fold", k), |b| {
+                b.iter(|| {
+                    roaring.iter().fold(RoaringBitmap::new(), |mut acc, bm| {
+                        acc |= bm;
+                        acc
+                    })
+                });
+            });
+            group.bench_function(Id::new("roaring_multiops", k), |b| {
+                b.iter(|| roaring.iter().union());
+            });
+        }
+    }
+    group.finish();
+
+    let mut group = Group::new(c, "multi_intersection");
+    group.tags(&["readonly", "setops"]);
+    for k in K {
+        group.dataset(Fingerprint::of_all(&values[..k]));
+        group.throughput(Throughput::Elements(k as u64));
+
+        with_croaring! {
+            let croaring = &croaring[..k];
+            group.bench_function(Id::new("croaring_fold", k), |b| {
+                b.iter(|| {
+                    croaring[1..]
+                        .iter()
+                        .fold(croaring[0].clone(), |mut acc, bm| {
+                            acc.and_inplace(bm);
+                            acc
+                        })
+                });
+            });
+        }
+        with_roaring! {
+            let roaring = &roaring[..k];
+            group.bench_function(Id::new("roaring_fold", k), |b| {
+                b.iter(|| {
+                    roaring[1..].iter().fold(roaring[0].clone(), |mut acc, bm| {
+                        acc &= bm;
+                        acc
+                    })
+                });
+            });
+            group.bench_function(Id::new("roaring_multiops", k), |b| {
+                b.iter(|| roaring.iter().intersection());
+            });
+        }
+    }
+    group.finish();
+}
+
+/// The inputs the rank and select groups query: sparse random values and
+/// the dense even values.
+fn rank_select_inputs() -> [(&'static str, Workload); 2] {
+    [
+        ("sparse", generated(Distribution::UniformSparse, 0)),
+        ("dense", Workload::Even),
+    ]
+}
+
+/// `rank` queries of values drawn uniformly from the span of each bitmap.
+pub fn bench_rank(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_rank");
+
+    for (input, workload) in rank_select_inputs() {
+        let mut group = Group::new(c, format!("rank_{input}"));
+        group.tags(&["readonly", "query"]);
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            let max = *pooled.values().last().unwrap();
+            let queries = (0..QUERIES)
+                .map(|_| rng.gen_range(0..=max))
+                .collect::<Vec<u32>>();
+            group.dataset(Fingerprint::of(&queries));
+            for_each_impl!(rank_case(&mut group, batch_size, pooled, &queries));
+        }
+        group.finish();
+    }
+}
+
+/// `select` queries of positions drawn uniformly from each bitmap.
+pub fn bench_select(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("bench_select");
+
+    for (input, workload) in rank_select_inputs() {
+        let mut group = Group::new(c, format!("select_{input}"));
+        group.tags(&["readonly", "query"]);
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for &batch_size in &N {
+            let pooled = pool::get(workload, batch_size);
+            let queries = (0..QUERIES)
+                .map(|_| rng.gen_range(0..batch_size))
+                .collect::<Vec<u32>>();
+            group.dataset(Fingerprint::of(&queries));
+            for_each_impl!(select_case(&mut group, batch_size, pooled, &queries));
+        }
+        group.finish();
+    }
+}
+
+/// The rank of each of `queries` in the pooled bitmap.
+fn rank_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    queries: &[u32],
+) {
+    group.bench_with_input(Id::new(B::NAME, batch_size), B::pooled(pooled), |b, bm| {
+        b.iter(|| queries.iter().map(|&v| bm.rank(v)).sum::<u64>());
+    });
+}
+
+/// The value at each of the positions `queries` in the pooled bitmap.
+fn select_case<B: BitmapImpl>(
+    group: &mut Group<'_, Measure>,
+    batch_size: u32,
+    pooled: &Pooled,
+    queries: &[u32],
+) {
+    group.bench_with_input(Id::new(B::NAME, batch_size), B::pooled(pooled), |b, bm| {
+        b.iter(|| queries.iter().filter_map(|&n| bm.select(n)).count());
+    });
+}
+
+/// The number of values in each page of the paginate groups.
+const PAGE_LIMIT: u32 = 100;
+
+/// The offsets of the page read by the paginate groups, as a percentage of
+/// the bitmap's cardinality.
+const PAGE_OFFSETS: [u32; 3] = [1, 50, 99];
+
+/// Reading a page of `PAGE_LIMIT` values from an offset into each bitmap,
+/// as in paginating over a result set.
+///
+/// The `_skip` participants step over the offset with
+/// `iter().skip(offset).take(limit)`. `_select` finds each value of the
+/// page with `select`, and `croaring_seek` selects only the first value and
+/// moves an iterator to it with `reset_at_or_after` (roaring 0.10 has no
+/// iterator that can be positioned).
+pub fn bench_paginate(c: &mut Harness) {
+    for (input, workload) in rank_select_inputs() {
+        for pct in PAGE_OFFSETS {
+            let mut group = Group::new(c, format!("paginate_{input}_offset_{pct}"));
+            group.tags(&["readonly", "query", "iter"]);
+            group.throughput(Throughput::Elements(PAGE_LIMIT as u64));
+            for &batch_size in &N {
+                let pooled = pool::get(workload, batch_size);
+                let offset = (batch_size as u64 * pct as u64 / 100) as u32;
+                let page = offset..offset.saturating_add(PAGE_LIMIT);
+
+                with_croaring! {
+                    let bm = pooled.croaring();
+                    group.bench_function(Id::new("croaring_skip", batch_size), |b| {
+                        b.iter(|| {
+                            bm.iter()
+                                .skip(offset as usize)
+                                .take(PAGE_LIMIT as usize)
+                                .map(u64::from)
+                                .sum::<u64>()
+                        });
+                    });
+                    group.bench_function(Id::new("croaring_select", batch_size), |b| {
+                        b.iter(|| {
+                            page.clone()
+                                .filter_map(|i| bm.select(i))
+                                .map(u64::from)
+                                .sum::<u64>()
+                        });
+                    });
+                    group.bench_function(Id::new("croaring_seek", batch_size), |b| {
+                        b.iter(|| {
+                            let start = match bm.select(offset) {
+                                Some(v) => v,
+                                None => return 0,
+                            };
+                            let mut iter = bm.iter();
+                            iter.reset_at_or_after(start);
+                            iter.take(PAGE_LIMIT as usize).map(u64::from).sum::<u64>()
+                        });
+                    });
+                }
+                with_roaring! {
+                    let bm = pooled.roaring();
+                    group.bench_function(Id::new("roaring_skip", batch_size), |b| {
+                        b.iter(|| {
+                            bm.iter()
+                                .skip(offset as usize)
+                                .take(PAGE_LIMIT as usize)
+                                .map(u64::from)
+                                .sum::<u64>()
+                        });
+                    });
+                    group.bench_function(Id::new("roaring_select", batch_size), |b| {
+                        b.iter(|| {
+                            page.clone()
+                                .filter_map(|i| bm.select(i))
+                                .map(u64::from)
+                                .sum::<u64>()
+                        });
+                    });
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// The cost of run-length optimisation on run-heavy, clustered and uniform
+/// random data, and then how iteration and intersection change on the
+/// optimised bitmaps.
+///
+/// roaring 0.10 has no run containers and so no `optimize` or
+/// `remove_run_compression`; it appears only in the follow-up groups, for
+/// reference. Each optimisation starts from a fresh copy, made outside of
+/// the measurement.
+pub fn bench_run_optimize(c: &mut Harness) {
+    use criterion::BatchSize;
+
+    let distributions = [
+        Distribution::RunHeavy,
+        Distribution::Clustered,
+        Distribution::UniformSparse,
+    ];
+    for distribution in distributions {
+        let mut group = Group::new(c, format!("run_optimize_{distribution}"));
+        group.tags(&["rle", "distribution"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                group.bench_function(Id::new("croaring_run_optimize", batch_size), |b| {
+                    b.iter_batched_ref(
+                        || pooled.croaring().clone(),
+                        |bm| bm.run_optimize(),
+                        BatchSize::SmallInput,
+                    );
+                });
+                group.bench_function(
+                    Id::new("croaring_remove_run_compression", batch_size),
+                    |b| {
+                        b.iter_batched_ref(
+                            || pooled.croaring_rle().clone(),
+                            |bm| bm.remove_run_compression(),
+                            BatchSize::SmallInput,
+                        );
+                    },
+                );
+            }
+        }
+        group.finish();
+
+        let mut group = Group::new(c, format!("run_optimized_iterate_{distribution}"));
+        group.tags(&["rle", "readonly", "iter", "distribution"]);
+        for &batch_size in &N {
+            let pooled = pool::get(generated(distribution, 0), batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                group.bench_with_input(
+                    Id::new("croaring", batch_size),
+                    pooled.croaring(),
+                    |b, bm| {
+                        b.iter(|| bm.iter().count());
+                    },
+                );
+                group.bench_with_input(
+                    Id::new("croaring_rle", batch_size),
+                    pooled.croaring_rle(),
+                    |b, bm| {
+                        b.iter(|| bm.iter().count());
+                    },
+                );
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring", batch_size), pooled.roaring(), |b, bm| {
+                    b.iter(|| bm.iter().count());
+                });
+            }
+        }
+        group.finish();
+
+        let mut group = Group::new(c, format!("run_optimized_intersect_{distribution}"));
+        group.tags(&["rle", "readonly", "setops", "distribution"]);
+        for &batch_size in &N {
+            let a = pool::get(generated(distribution, 0), batch_size);
+            let b = pool::get(generated(distribution, 1), batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64 * 2));
+            with_croaring! {
+                group.bench_function(Id::new("croaring", batch_size), |bench| {
+                    bench.iter(|| a.croaring().and(b.croaring()));
+                });
+                group.bench_function(Id::new("croaring_rle", batch_size), |bench| {
+                    bench.iter(|| a.croaring_rle().and(b.croaring_rle()));
+                });
+            }
+            with_roaring! {
+                group.bench_function(Id::new("roaring", batch_size), |bench| {
+                    bench.iter(|| a.roaring() & b.roaring());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Insertion, union, iteration and serialization of every `k`th value of
+/// [`gen::STRIDE_UNIVERSE`] for each of [`gen::STRIDES`], parameterised by
+/// `k`, so that differences between the backends can be put down to the
+/// container type each stride leaves.
+///
+/// The union is with the same stride shifted up by one, so its operands
+/// hold the same container type and share no values; at `k = 16` their
+/// union overflows the array containers into bitmap containers.
+pub fn bench_strides(c: &mut Harness) {
+    let universe = gen::STRIDE_UNIVERSE;
+
+    let mut insert = Group::new(c, "add_elements_stride");
+    insert.tags(&["construct"]);
+    for k in gen::STRIDES {
+        let values = pool::get(Workload::Stride(k), universe).values();
+        insert.throughput(Throughput::Elements(values.len() as u64));
+        with_croaring! {
+            insert.bench_with_input(Id::new("croaring", k), values, |b, values| {
+                b.iter(|| {
+                    let mut bm = croaring::Bitmap::create();
+                    for &v in values {
+                        bm.add(v);
+                    }
+                    bm
+                });
+            });
+        }
+        with_roaring! {
+            insert.bench_with_input(Id::new("roaring", k), values, |b, values| {
+                b.iter(|| {
+                    let mut bm = RoaringBitmap::new();
+                    for &v in values {
+                        bm.insert(v);
+                    }
+                    bm
+                });
+            });
+        }
+    }
+    insert.finish();
+
+    let mut union = Group::new(c, "union_stride");
+    union.tags(&["readonly", "setops"]);
+    for k in gen::STRIDES {
+        let pooled = pool::get(Workload::Stride(k), universe);
+        let shifted = pooled.values().iter().map(|v| v + 1).collect::<Vec<_>>();
+        union.throughput(Throughput::Elements(pooled.values().len() as u64 * 2));
+        with_croaring! {
+            let a = pooled.croaring();
+            let b = croaring::Bitmap::of(&shifted);
+            union.bench_function(Id::new("croaring", k), |bench| {
+                bench.iter(|| a.or(&b));
+            });
+        }
+        with_roaring! {
+            let a = pooled.roaring();
+            let b = RoaringBitmap::from_sorted_iter(shifted.iter().copied()).unwrap();
+            union.bench_function(Id::new("roaring", k), |bench| {
+                bench.iter(|| a | &b);
+            });
+        }
+    }
+    union.finish();
+
+    let mut iterate = Group::new(c, "iterate_sum_stride");
+    iterate.tags(&["readonly", "iter"]);
+    for k in gen::STRIDES {
+        let pooled = pool::get(Workload::Stride(k), universe);
+        iterate.throughput(Throughput::Elements(pooled.values().len() as u64));
+        with_croaring! {
+            iterate.bench_with_input(Id::new("croaring", k), pooled.croaring(), |b, bm| {
+                b.iter(|| bm.iter().map(u64::from).sum::<u64>());
+            });
+        }
+        with_roaring! {
+            iterate.bench_with_input(Id::new("roaring", k), pooled.roaring(), |b, bm| {
+                b.iter(|| bm.iter().map(u64::from).sum::<u64>());
+            });
+        }
+    }
+    iterate.finish();
+
+    let mut serialize = Group::new(c, "serialize_stride");
+    serialize.tags(&["serde"]);
+    for k in gen::STRIDES {
+        let pooled = pool::get(Workload::Stride(k), universe);
+        with_croaring! {
+            let bm = pooled.croaring();
+            serialize.throughput(Throughput::Bytes(bm.get_serialized_size_in_bytes() as u64));
+            serialize.bench_with_input(Id::new("croaring", k), bm, |b, bm| {
+                b.iter(|| bm.serialize());
+            });
+        }
+        with_roaring! {
+            let bm = pooled.roaring();
+            serialize.throughput(Throughput::Bytes(bm.serialized_size() as u64));
+            serialize.bench_with_input(Id::new("roaring", k), bm, |b, bm| {
+                b.iter(|| {
+                    let mut buf = Vec::with_capacity(bm.serialized_size());
+                    bm.serialize_into(&mut buf).unwrap();
+                    buf
+                });
+            });
+        }
+    }
+    serialize.finish();
+}
+
+/// The numbers of containers pushed back and forth across
+/// [`gen::ARRAY_MAX`] by the threshold_churn group.
+const THRESHOLD_CONTAINERS: [u32; 3] = [1, 64, 1_024];
+
+/// The numbers of single-value containers of the singleton groups.
+const SINGLETON_CONTAINERS: [u32; 3] = [256, 4_096, 65_536];
+
+/// Adversarial layouts maximising container management work.
+///
+/// threshold_churn inserts the two values taking each of a set of array
+/// containers to [`gen::ARRAY_MAX`] + 1 values, converting it to a bitset,
+/// then removes them again, converting it back (see [`gen::threshold`]).
+/// The singleton groups insert, remove and union bitmaps holding a single
+/// value in each container (see [`gen::singletons`]), whose union operands
+/// share every container but no values.
+pub fn bench_pathological(c: &mut Harness) {
+    use criterion::BatchSize;
+
+    let mut group = Group::new(c, "threshold_churn");
+    group.tags(&["mutate"]);
+    for containers in THRESHOLD_CONTAINERS {
+        let (values, pairs) = gen::threshold(containers);
+        group.dataset(Fingerprint::of(&values));
+        group.throughput(Throughput::Elements(4 * containers as u64));
+        with_croaring! {
+            let mut bm = croaring::Bitmap::of(&values);
+            group.bench_function(Id::new("croaring", containers), |b| {
+                b.iter(|| {
+                    for &[x, y] in &pairs {
+                        bm.add(x);
+                        bm.add(y);
+                        bm.remove(x);
+                        bm.remove(y);
+                    }
+                });
+            });
+        }
+        with_roaring! {
+            let mut bm = RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap();
+            group.bench_function(Id::new("roaring", containers), |b| {
+                b.iter(|| {
+                    for &[x, y] in &pairs {
+                        bm.insert(x);
+                        bm.insert(y);
+                        bm.remove(x);
+                        bm.remove(y);
+                    }
+                });
+            });
+        }
+    }
+    group.finish();
+
+    let mut insert = Group::new(c, "singletons_insert");
+    insert.tags(&["construct"]);
+    for containers in SINGLETON_CONTAINERS {
+        let values = gen::singletons(containers);
+        insert.dataset(Fingerprint::of(&values));
+        insert.throughput(Throughput::Elements(containers as u64));
+        with_croaring! {
+            insert.bench_with_input(Id::new("croaring", containers), &values, |b, values| {
+                b.iter(|| {
+                    let mut bm = croaring::Bitmap::create();
+                    for &v in values {
+                        bm.add(v);
+                    }
+                    bm
+                });
+            });
+        }
+        with_roaring! {
+            insert.bench_with_input(Id::new("roaring", containers), &values, |b, values| {
+                b.iter(|| {
+                    let mut bm = RoaringBitmap::new();
+                    for &v in values {
+                        bm.insert(v);
+                    }
+                    bm
+                });
+            });
+        }
+    }
+    insert.finish();
+
+    let mut remove = Group::new(c, "singletons_remove");
+    remove.tags(&["mutate"]);
+    for containers in SINGLETON_CONTAINERS {
+        let values = gen::singletons(containers);
+        remove.dataset(Fingerprint::of(&values));
+        remove.throughput(Throughput::Elements(containers as u64));
+        with_croaring! {
+            let bm = croaring::Bitmap::of(&values);
+            remove.bench_function(Id::new("croaring", containers), |b| {
+                b.iter_batched_ref(
+                    || bm.clone(),
+                    |bm| values.iter().for_each(|&v| bm.remove(v)),
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+        with_roaring! {
+            let bm = RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap();
+            remove.bench_function(Id::new("roaring", containers), |b| {
+                b.iter_batched_ref(
+                    || bm.clone(),
+                    |bm| {
+                        for &v in &values {
+                            bm.remove(v);
+                        }
+                    },
+                    BatchSize::SmallInput,
+                );
+            });
+        }
+    }
+    remove.finish();
+
+    let mut union = Group::new(c, "singletons_union");
+    union.tags(&["readonly", "setops"]);
+    for containers in SINGLETON_CONTAINERS {
+        let a = gen::singletons(containers);
+        let b = a.iter().map(|v| v + 1).collect::<Vec<_>>();
+        union.dataset(Fingerprint::of_all([&a, &b]));
+        union.throughput(Throughput::Elements(2 * containers as u64));
+        with_croaring! {
+            let (a, b) = (croaring::Bitmap::of(&a), croaring::Bitmap::of(&b));
+            union.bench_function(Id::new("croaring", containers), |bench| {
+                bench.iter(|| a.or(&b));
+            });
+        }
+        with_roaring! {
+            let a = RoaringBitmap::from_sorted_iter(a.iter().copied()).unwrap();
+            let b = RoaringBitmap::from_sorted_iter(b.iter().copied()).unwrap();
+            union.bench_function(Id::new("roaring", containers), |bench| {
+                bench.iter(|| &a | &b);
+            });
+        }
+    }
+    union.finish();
+}
+
+/// Every benchmark group, in run order.
+const TARGETS: &[fn(&mut Harness)] = &[
+    bench_reference,
+    bench_add,
+    bench_add_range,
+    bench_append_sorted,
+    bench_add_shuffled,
+    bench_collect_uint,
+    bench_iterate,
+    bench_iterate_batched,
+    bench_accessors,
+    bench_statistics,
+    bench_union,
+    bench_from_arrow,
+    bench_deletion_vector,
+    bench_gather,
+    bench_intersect_sorted_slice,
+    bench_dedup_count,
+    bench_clear_vs_new,
+    bench_contains,
+    bench_range_queries,
+    bench_inverted_index,
+    bench_sharded_index,
+    bench_olap_filter,
+    bench_authz,
+    bench_stream_dedup,
+    bench_graph,
+    bench_retention,
+    bench_sliding_window,
+    bench_compaction,
+    bench_snapshot,
+    bench_clone,
+    bench_framed_serialization,
+    bench_full_universe,
+    bench_range_construct,
+    bench_flip,
+    bench_xor_toggle,
+    bench_mixed_workload,
+    bench_churned_queries,
+    bench_intersect_merge,
+    bench_union_operand_order,
+    bench_batch_removal,
+    bench_ingest_optimize,
+    bench_treemap_migration,
+    bench_untrusted_deserialize,
+    bench_intersection,
+    bench_difference,
+    bench_symmetric_difference,
+    bench_asymmetric_set_ops,
+    bench_exclusion,
+    bench_cardinality_ops,
+    bench_predicates,
+    bench_inplace_set_ops,
+    bench_serialize,
+    bench_deserialize,
+    bench_cold_query,
+    bench_frozen_view,
+    #[cfg(all(feature = "croaring", feature = "roaring"))]
+    bench_interop_roundtrip,
+    #[cfg(all(feature = "croaring", feature = "roaring"))]
+    bench_interop_iter,
+    bench_datasets,
+    bench_remove,
+    bench_bulk_construct,
+    bench_materialize,
+    bench_multi_op,
+    bench_rank,
+    bench_select,
+    bench_paginate,
+    bench_run_optimize,
+    bench_strides,
+    bench_pathological,
+];
+
+/// The equivalent of `criterion_group!` and `criterion_main!` over
+/// [`TARGETS`], plus the `--list-cases` dry run, `--tags` selection, the
+/// `--large` size tier and the `--cold` cache variants (which criterion's
+/// own argument parsing would reject).
+///
+/// The tags in use are `construct`, `readonly`, `query`, `iter`, `setops`,
+/// `rle`, `serde`, `interop`, `mutate`, `workload` (the macro-benchmarks)
+/// and `reference` (the normalization groups).
+fn main() {
+    // Hand the tags to a copy of this process via the environment, so the
+    // flag never reaches criterion.
+    let mut args = std::env::args().skip(1).collect::<Vec<_>>();
+    let mut env = Vec::new();
+    if let Some((tags, rest)) = harness::split_tags_flag(&args) {
+        env.push((harness::TAGS_ENV, tags));
+        args = rest;
+    }
+    if let Some(rest) = scale::split_large_flag(&args) {
+        env.push((scale::LARGE_ENV, "1".to_string()));
+        args = rest;
+    }
+    if let Some(rest) = cache::split_cold_flag(&args) {
+        env.push((cache::COLD_ENV, "1".to_string()));
+        args = rest;
+    }
+    if !env.is_empty() {
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(args)
+            .envs(env)
+            .status()
+            .expect("failed to re-execute benchmark binary");
+        std::process::exit(status.code().unwrap_or(1));
+    }
+
+    if harness::list_cases_requested() {
+        // Accept criterion's FILTER argument, as a plain substring.
+        let filter = std::env::args().skip(1).find(|a| !a.starts_with('-'));
+        let cases = harness::plan(filter.as_deref(), || {
+            let mut c = config();
+            for target in TARGETS {
+                target(&mut c);
+            }
+        });
+        print_plan(&cases);
+        return;
+    }
+
+    let mut c = config().configure_from_args();
+    for target in TARGETS {
+        target(&mut c);
+    }
+    c.final_summary();
+
+    let violations = harness::memory_violations();
+    if !violations.is_empty() {
+        eprintln!("{} case(s) exceeded their memory budget:", violations.len());
+        for v in &violations {
+            eprintln!("  {}: {} bytes > {} bytes", v.case, v.peak, v.budget);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn print_plan(cases: &[harness::PlannedCase]) {
+    println!("{:<64} {:>9} {:>10}  tags", "case", "dataset", "estimate");
+    let mut total = std::time::Duration::ZERO;
+    for case in cases {
+        let estimate = if case.over_budget {
+            "skipped".to_string()
+        } else {
+            total += case.estimate;
+            let prefix = if case.from_history { "" } else { "~" };
+            format!("{prefix}{:.1}s", case.estimate.as_secs_f64())
+        };
+        let dataset = case
+            .dataset
+            .map_or_else(|| "-".to_string(), |d| d.to_string());
+        println!(
+            "{:<64} {dataset:>9} {estimate:>10}  {}",
+            format!("{}/{}", case.group, case.id),
+            case.tags.join(","),
+        );
+    }
+
+    let from_history = cases.iter().filter(|c| c.from_history).count();
+    let secs = total.as_secs();
+    println!(
+        "\n{} cases, estimated total {}h{:02}m{:02}s ({from_history} projected from previous \
+         results, the rest (~) assume criterion's default warm-up and measurement times)",
+        cases.len(),
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+    );
+}
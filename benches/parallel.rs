@@ -0,0 +1,424 @@
+//! Multi-threaded workloads, run on rayon thread pools of each of
+//! [`THREADS`] threads to show how each backend scales across cores.
+//!
+//! The work done per iteration is the same at every thread count, so the
+//! throughput of a group rises with the thread count only as far as the
+//! backend (and its allocator) lets it.
+
+// Without both libraries some of the shared setup goes unused.
+#![cfg_attr(not(all(feature = "croaring", feature = "roaring")), allow(unused))]
+
+use std::sync::Arc;
+
+use criterion::{criterion_group, criterion_main, measurement::WallTime, Criterion, Throughput};
+use rayon::prelude::*;
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
+use roaring_bench::{
+    gen::{self, Distribution},
+    harness::{Fingerprint, Group, Id},
+    pool::{self, Workload},
+    scale, with_croaring, with_roaring, N,
+};
+
+/// Always wall time, even with the `perf` feature: its instruction counter
+/// only counts the thread calling criterion, not the rayon workers (or the
+/// snapshot writer) doing the work.
+type Harness = Criterion<WallTime>;
+
+/// The thread counts each group is run with.
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// The number of shards the input of the build groups is dealt across.
+const SHARDS: u32 = 64;
+
+/// The number of queries run per iteration of the query groups.
+const QUERIES: usize = 256;
+
+/// The number of values in each query bitmap.
+const QUERY_SIZE: u32 = 1_000;
+
+/// The sizes large enough to be worth spreading across threads.
+fn sizes() -> impl Iterator<Item = u32> {
+    N.iter().copied().filter(|&n| n >= 100_000)
+}
+
+/// A rayon thread pool of `threads` threads.
+fn thread_pool(threads: usize) -> rayon::ThreadPool {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .expect("failed to build thread pool")
+}
+
+/// One past the largest of `values`.
+fn universe(values: &[u32]) -> u32 {
+    values.last().map_or(1, |&v| v + 1)
+}
+
+/// [`QUERIES`] sorted sets of up to [`QUERY_SIZE`] values drawn uniformly
+/// from `0..universe`.
+fn query_sets(rng: &mut impl rand::Rng, universe: u32) -> Vec<Vec<u32>> {
+    (0..QUERIES)
+        .map(|_| {
+            let mut q = (0..QUERY_SIZE)
+                .map(|_| rng.gen_range(0..universe))
+                .collect::<Vec<u32>>();
+            q.sort_unstable();
+            q.dedup();
+            q
+        })
+        .collect()
+}
+
+/// Building one bitmap per shard in parallel and merging them with rayon's
+/// tree-shaped reduction.
+///
+/// Values are dealt to shards by `value % SHARDS`, as a row id sharded
+/// index would, so every shard spans the whole universe and the unions
+/// merge overlapping containers.
+pub fn bench_build_union(c: &mut Harness) {
+    for size in sizes() {
+        let values = pool::get(
+            Workload::Generated {
+                distribution: Distribution::UniformSparse,
+                instance: 0,
+            },
+            size,
+        )
+        .values();
+        let mut shards = vec![Vec::new(); SHARDS as usize];
+        for &v in values {
+            shards[(v % SHARDS) as usize].push(v);
+        }
+
+        let mut group = Group::new(c, format!("parallel_build_union_{size}"));
+        group.tags(&["construct", "setops", "parallel"]);
+        group.throughput(Throughput::Elements(size as u64));
+        for threads in THREADS {
+            let pool = thread_pool(threads);
+            with_croaring! {
+                group.bench_function(Id::new("croaring", threads), |b| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            shards
+                                .par_iter()
+                                .map(|s| croaring::Bitmap::of(s))
+                                .reduce(croaring::Bitmap::create, |a, b| a | b)
+                        })
+                    });
+                });
+            }
+            with_roaring! {
+                group.bench_function(Id::new("roaring", threads), |b| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            shards
+                                .par_iter()
+                                .map(|s| s.iter().copied().collect::<RoaringBitmap>())
+                                .reduce(RoaringBitmap::new, |a, b| a | b)
+                        })
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Concurrent read-only intersections of small query bitmaps with one
+/// large bitmap shared between the threads through an `Arc`.
+pub fn bench_shared_queries(c: &mut Harness) {
+    let mut rng = gen::rng("parallel/bench_shared_queries");
+
+    for size in sizes() {
+        let pooled = pool::get(
+            Workload::Generated {
+                distribution: Distribution::Clustered,
+                instance: 0,
+            },
+            size,
+        );
+        let queries = query_sets(&mut rng, universe(pooled.values()));
+
+        let mut group = Group::new(c, format!("parallel_shared_intersect_{size}"));
+        group.tags(&["readonly", "setops", "parallel"]);
+        group.dataset(Fingerprint::of_all(&queries));
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        with_croaring! {
+            let index = Arc::new(pooled.croaring().clone());
+            let queries = queries.iter().map(|q| croaring::Bitmap::of(q)).collect::<Vec<_>>();
+            for threads in THREADS {
+                let pool = thread_pool(threads);
+                group.bench_function(Id::new("croaring", threads), |b| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            queries
+                                .par_iter()
+                                .map_with(Arc::clone(&index), |index, q| index.and(q).cardinality())
+                                .sum::<u64>()
+                        })
+                    });
+                });
+            }
+        }
+        with_roaring! {
+            let index = Arc::new(pooled.roaring().clone());
+            let queries = queries
+                .iter()
+                .map(|q| RoaringBitmap::from_sorted_iter(q.iter().copied()).unwrap())
+                .collect::<Vec<_>>();
+            for threads in THREADS {
+                let pool = thread_pool(threads);
+                group.bench_function(Id::new("roaring", threads), |b| {
+                    b.iter(|| {
+                        pool.install(|| {
+                            queries
+                                .par_iter()
+                                .map_with(Arc::clone(&index), |index, q| (&**index & q).len())
+                                .sum::<u64>()
+                        })
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// The number of writes the writer of the live snapshot groups makes between
+/// taking the lock or publishing a snapshot.
+const WRITE_BATCH: usize = 64;
+
+/// The operations of a bitmap the live snapshot groups need.
+trait LiveBitmap: Clone + Send + Sync {
+    /// Insert `v`, or remove it if `insert` is false.
+    fn set(&mut self, v: u32, insert: bool);
+
+    /// The number of `values` present.
+    fn hits(&self, values: &[u32]) -> u64;
+
+    /// The cardinality of the intersection with `other`.
+    fn and_len(&self, other: &Self) -> u64;
+}
+
+#[cfg(feature = "croaring")]
+impl LiveBitmap for croaring::Bitmap {
+    fn set(&mut self, v: u32, insert: bool) {
+        if insert {
+            self.add(v);
+        } else {
+            self.remove(v);
+        }
+    }
+
+    fn hits(&self, values: &[u32]) -> u64 {
+        values.iter().filter(|&&v| self.contains(v)).count() as u64
+    }
+
+    fn and_len(&self, other: &Self) -> u64 {
+        self.and_cardinality(other)
+    }
+}
+
+#[cfg(feature = "roaring")]
+impl LiveBitmap for RoaringBitmap {
+    fn set(&mut self, v: u32, insert: bool) {
+        if insert {
+            self.insert(v);
+        } else {
+            self.remove(v);
+        }
+    }
+
+    fn hits(&self, values: &[u32]) -> u64 {
+        values.iter().filter(|&&v| self.contains(v)).count() as u64
+    }
+
+    fn and_len(&self, other: &Self) -> u64 {
+        self.intersection_len(other)
+    }
+}
+
+/// How the bitmap of a live snapshot group is shared with its readers.
+#[derive(Debug, Clone, Copy)]
+enum Sharing {
+    /// The writer takes a write lock for each batch of writes, and readers a
+    /// read lock for each query.
+    RwLock,
+
+    /// The writer updates its own copy and publishes a clone of it after
+    /// each batch, and readers load the latest clone for each query.
+    ArcSwap,
+}
+
+/// The live bitmap of a participant, and the writer's position in its
+/// cycle of writes, kept across iterations.
+struct LiveState<B> {
+    lock: std::sync::RwLock<B>,
+    master: B,
+    published: roaring_bench::snapshot::SnapshotCell<B>,
+    writes: Vec<u32>,
+    cursor: usize,
+}
+
+impl<B: LiveBitmap> LiveState<B> {
+    fn new(bm: B, writes: Vec<u32>) -> Self {
+        Self {
+            lock: std::sync::RwLock::new(bm.clone()),
+            published: roaring_bench::snapshot::SnapshotCell::new(bm.clone()),
+            master: bm,
+            writes,
+            cursor: 0,
+        }
+    }
+
+    /// Run every query on `pool` while a writer thread makes batches of
+    /// writes, returning the sum of the query results.
+    ///
+    /// Each write inserts the next value of the cycle and removes the one
+    /// inserted half a cycle before, so the bitmap stays the same size.
+    /// Even queries intersect their bitmap with the snapshot, and odd ones
+    /// look up their values.
+    fn run(
+        &mut self,
+        sharing: Sharing,
+        pool: &rayon::ThreadPool,
+        queries: &[(Vec<u32>, B)],
+    ) -> u64 {
+        use std::sync::atomic::{AtomicBool, Ordering};
+
+        let Self {
+            lock,
+            master,
+            published,
+            writes,
+            cursor,
+        } = self;
+        let done = AtomicBool::new(false);
+        let mut write_batch = |bm: &mut B| {
+            for _ in 0..WRITE_BATCH {
+                bm.set(writes[*cursor % writes.len()], true);
+                bm.set(writes[(*cursor + writes.len() / 2) % writes.len()], false);
+                *cursor += 1;
+            }
+        };
+        let query = |i: usize, bm: &B| {
+            let (values, q) = &queries[i];
+            match i % 2 {
+                0 => bm.and_len(q),
+                _ => bm.hits(values),
+            }
+        };
+
+        std::thread::scope(|s| {
+            s.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    match sharing {
+                        Sharing::RwLock => write_batch(&mut lock.write().unwrap()),
+                        Sharing::ArcSwap => {
+                            write_batch(master);
+                            published.store(Arc::new(master.clone()));
+                        }
+                    }
+                }
+            });
+            let sum = pool.install(|| {
+                (0..queries.len())
+                    .into_par_iter()
+                    .map(|i| match sharing {
+                        Sharing::RwLock => query(i, &lock.read().unwrap()),
+                        Sharing::ArcSwap => query(i, &published.load()),
+                    })
+                    .sum::<u64>()
+            });
+            done.store(true, Ordering::Relaxed);
+            sum
+        })
+    }
+}
+
+/// Queries against a bitmap that a writer thread is concurrently updating,
+/// comparing a `RwLock<B>` with publishing clones through an `ArcSwap`, to
+/// show how a live bitmap is best shared.
+///
+/// The parameter is the number of reader threads, which run alongside the
+/// one writer. The writer writes continuously for the duration of the
+/// queries, in batches of [`WRITE_BATCH`].
+///
+/// [`roaring_bench::snapshot::SnapshotCell`] models `ArcSwap`'s load and
+/// store: a load holds a read lock only for as long as an `Arc` clone, and
+/// never while the snapshot is queried.
+pub fn bench_live_snapshots(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("parallel/bench_live_snapshots");
+
+    for size in sizes() {
+        let pooled = pool::get(
+            Workload::Generated {
+                distribution: Distribution::Clustered,
+                instance: 0,
+            },
+            size,
+        );
+        let universe = universe(pooled.values());
+        let queries = query_sets(&mut rng, universe);
+        // Values beyond the initial set, so removing them leaves it intact.
+        let writes = (0..4 * WRITE_BATCH)
+            .map(|_| rng.gen_range(universe..universe.saturating_mul(2)))
+            .collect::<Vec<u32>>();
+
+        let mut group = Group::new(c, format!("parallel_live_snapshot_{size}"));
+        group.tags(&["workload", "mutate", "parallel"]);
+        group.dataset(*Fingerprint::of_all(&queries).update(&writes));
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        let sharings = [("rwlock", Sharing::RwLock), ("arcswap", Sharing::ArcSwap)];
+        with_croaring! {
+            let queries = queries
+                .iter()
+                .map(|q| (q.clone(), croaring::Bitmap::of(q)))
+                .collect::<Vec<_>>();
+            for (name, sharing) in sharings {
+                let mut state = LiveState::new(pooled.croaring().clone(), writes.clone());
+                for threads in THREADS {
+                    let pool = thread_pool(threads);
+                    group.bench_function(Id::new(format!("croaring_{name}"), threads), |b| {
+                        b.iter(|| state.run(sharing, &pool, &queries));
+                    });
+                }
+            }
+        }
+        with_roaring! {
+            let queries = queries
+                .iter()
+                .map(|q| (q.clone(), RoaringBitmap::from_sorted_iter(q.iter().copied()).unwrap()))
+                .collect::<Vec<_>>();
+            for (name, sharing) in sharings {
+                let mut state = LiveState::new(pooled.roaring().clone(), writes.clone());
+                for threads in THREADS {
+                    let pool = thread_pool(threads);
+                    group.bench_function(Id::new(format!("roaring_{name}"), threads), |b| {
+                        b.iter(|| state.run(sharing, &pool, &queries));
+                    });
+                }
+            }
+        }
+        group.finish();
+    }
+}
+
+fn config() -> Harness {
+    let c = Criterion::default();
+    if scale::large_enabled() {
+        c.sample_size(scale::LARGE_SAMPLE_SIZE)
+    } else {
+        c
+    }
+}
+
+criterion_group!(
+    name = benches;
+    config = config();
+    targets = bench_build_union, bench_shared_queries, bench_live_snapshots
+);
+criterion_main!(benches);
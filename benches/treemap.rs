@@ -0,0 +1,312 @@
+//! The 64-bit counterparts of the main suite: croaring's `Treemap` against
+//! roaring's `RoaringTreemap`.
+//!
+//! Each group runs over two layouts: "dense" values `0..n`, all within the
+//! lowest 2^32 partition, and "spread" values dealt round-robin across
+//! [`BUCKETS`] high-32-bit partitions.
+
+// Without both libraries some of the shared setup goes unused.
+#![cfg_attr(not(all(feature = "croaring", feature = "roaring")), allow(unused))]
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+#[cfg(feature = "croaring")]
+use croaring::Treemap;
+#[cfg(feature = "roaring")]
+use roaring::RoaringTreemap;
+use roaring_bench::{
+    gen,
+    harness::{Fingerprint, Group, Id},
+    scale, with_croaring, with_roaring, N,
+};
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+type Measure = roaring_bench::perf::Instructions;
+#[cfg(not(all(feature = "perf", target_os = "linux")))]
+type Measure = criterion::measurement::WallTime;
+
+type Harness = Criterion<Measure>;
+
+#[cfg(all(feature = "perf", target_os = "linux"))]
+fn measurement() -> Measure {
+    roaring_bench::perf::Instructions::new().expect("failed to open perf instruction counter")
+}
+#[cfg(not(all(feature = "perf", target_os = "linux")))]
+fn measurement() -> Measure {
+    criterion::measurement::WallTime
+}
+
+/// The number of high-32-bit partitions the "spread" layout uses.
+const BUCKETS: u64 = 1_024;
+
+/// The number of lookups performed per iteration of the contains groups.
+const QUERIES: usize = 1_024;
+
+/// How the values of a group are laid out across the 64-bit space.
+#[derive(Clone, Copy)]
+enum Layout {
+    Dense,
+    Spread,
+}
+
+impl Layout {
+    const ALL: [Layout; 2] = [Layout::Dense, Layout::Spread];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Layout::Dense => "dense",
+            Layout::Spread => "spread",
+        }
+    }
+
+    /// The `i`-th of the values, ascending in `i`.
+    fn value(&self, i: u64) -> u64 {
+        match self {
+            Layout::Dense => i,
+            Layout::Spread => ((i % BUCKETS) << 32) | (i / BUCKETS),
+        }
+    }
+
+    /// The first `size` values, in ascending order.
+    fn values(&self, size: u32) -> Vec<u64> {
+        let mut values = (0..u64::from(size))
+            .map(|i| self.value(i))
+            .collect::<Vec<_>>();
+        values.sort_unstable();
+        values
+    }
+
+    /// Two sets of `size` values, the second starting halfway through the
+    /// first.
+    fn operands(&self, size: u32) -> (Vec<u64>, Vec<u64>) {
+        let values = self.values(size + size / 2);
+        let half = size as usize / 2;
+        (
+            values[..size as usize].to_vec(),
+            values[half..half + size as usize].to_vec(),
+        )
+    }
+}
+
+/// The fingerprint of 64-bit values, hashed as pairs of 32-bit halves.
+fn fingerprint(values: &[u64]) -> Fingerprint {
+    let halves = values
+        .iter()
+        .flat_map(|&v| [(v >> 32) as u32, v as u32])
+        .collect::<Vec<_>>();
+    Fingerprint::of(&halves)
+}
+
+/// Insert `range` into a croaring `Treemap`, which has no range insert of
+/// its own, one 2^32 partition at a time.
+#[cfg(feature = "croaring")]
+fn treemap_add_range(tm: &mut Treemap, range: std::ops::Range<u64>) {
+    let mut start = range.start;
+    while start < range.end {
+        let (hi, lo) = ((start >> 32) as u32, start as u32);
+        let end = range.end.min((u64::from(hi) + 1) << 32);
+        let hi_end = (end - 1) as u32;
+        tm.map
+            .entry(hi)
+            .or_insert_with(croaring::Bitmap::create)
+            .add_range(lo..=hi_end);
+        start = end;
+    }
+}
+
+pub fn bench_insert(c: &mut Harness) {
+    use rand::seq::SliceRandom;
+    let mut rng = gen::rng("treemap/bench_insert");
+
+    for layout in Layout::ALL {
+        for shuffled in [false, true] {
+            let order = if shuffled { "shuffled" } else { "sequential" };
+            let mut group = Group::new(c, format!("treemap_insert_{order}_{}", layout.name()));
+            group.tags(&["construct"]);
+            for &batch_size in &N {
+                let mut values = layout.values(batch_size);
+                if shuffled {
+                    values.shuffle(&mut rng);
+                    group.dataset(fingerprint(&values));
+                }
+                group.throughput(Throughput::Elements(batch_size as u64));
+                with_croaring! {
+                    group.bench_with_input(Id::new("croaring", batch_size), &values, |b, values| {
+                        b.iter(|| {
+                            let mut tm = Treemap::create();
+                            for &v in values {
+                                tm.add(v);
+                            }
+                            tm
+                        });
+                    });
+                }
+                with_roaring! {
+                    group.bench_with_input(Id::new("roaring", batch_size), &values, |b, values| {
+                        b.iter(|| {
+                            let mut tm = RoaringTreemap::new();
+                            for &v in values {
+                                tm.insert(v);
+                            }
+                            tm
+                        });
+                    });
+                }
+            }
+            group.finish();
+        }
+    }
+}
+
+/// Range inserts of `n` values, starting either at zero or just below a
+/// partition boundary so that the range straddles it.
+pub fn bench_insert_range(c: &mut Harness) {
+    for (name, start) in [("dense", 0), ("straddling", (1 << 32) - 1)] {
+        let mut group = Group::new(c, format!("treemap_insert_range_{name}"));
+        group.tags(&["construct"]);
+        for &batch_size in &N {
+            let range = start..start + u64::from(batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                group.bench_with_input(Id::new("croaring", batch_size), &range, |b, range| {
+                    b.iter(|| {
+                        let mut tm = Treemap::create();
+                        treemap_add_range(&mut tm, range.clone());
+                        tm
+                    });
+                });
+            }
+            with_roaring! {
+                group.bench_with_input(Id::new("roaring", batch_size), &range, |b, range| {
+                    b.iter(|| {
+                        let mut tm = RoaringTreemap::new();
+                        tm.insert_range(range.clone());
+                        tm
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+pub fn bench_contains(c: &mut Harness) {
+    use rand::Rng;
+    let mut rng = gen::rng("treemap/bench_contains");
+
+    for layout in Layout::ALL {
+        let mut group = Group::new(c, format!("treemap_contains_{}", layout.name()));
+        group.tags(&["readonly", "query"]);
+        group.throughput(Throughput::Elements(QUERIES as u64));
+        for &batch_size in &N {
+            let values = layout.values(batch_size);
+
+            // Half are hits, half are misses drawn from the next n values.
+            let queries = (0..QUERIES)
+                .map(|_| layout.value(rng.gen_range(0..2 * u64::from(batch_size))))
+                .collect::<Vec<u64>>();
+            group.dataset(fingerprint(&queries));
+
+            with_croaring! {
+                let croaring = Treemap::of(&values);
+                group.bench_with_input(Id::new("croaring", batch_size), &croaring, |b, tm| {
+                    b.iter(|| queries.iter().filter(|v| tm.contains(**v)).count());
+                });
+            }
+            with_roaring! {
+                let roaring = values.iter().collect::<RoaringTreemap>();
+                group.bench_with_input(Id::new("roaring", batch_size), &roaring, |b, tm| {
+                    b.iter(|| queries.iter().filter(|v| tm.contains(**v)).count());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Union and intersection of two sets of each layout's values, overlapping
+/// in half of their values.
+pub fn bench_set_ops(c: &mut Harness) {
+    for layout in Layout::ALL {
+        let mut union = Group::new(c, format!("treemap_union_{}", layout.name()));
+        union.tags(&["readonly", "setops"]);
+        for &batch_size in &N {
+            let (a, b) = layout.operands(batch_size);
+            union.throughput(Throughput::Elements((a.len() + b.len()) as u64));
+            with_croaring! {
+                let (croaring_a, croaring_b) = (Treemap::of(&a), Treemap::of(&b));
+                union.bench_function(Id::new("croaring", batch_size), |bench| {
+                    bench.iter(|| croaring_a.or(&croaring_b));
+                });
+            }
+            with_roaring! {
+                let roaring_a = a.iter().collect::<RoaringTreemap>();
+                let roaring_b = b.iter().collect::<RoaringTreemap>();
+                union.bench_function(Id::new("roaring", batch_size), |bench| {
+                    bench.iter(|| &roaring_a | &roaring_b);
+                });
+            }
+        }
+        union.finish();
+
+        let mut intersection = Group::new(c, format!("treemap_intersection_{}", layout.name()));
+        intersection.tags(&["readonly", "setops"]);
+        for &batch_size in &N {
+            let (a, b) = layout.operands(batch_size);
+            intersection.throughput(Throughput::Elements((a.len() + b.len()) as u64));
+            with_croaring! {
+                let (croaring_a, croaring_b) = (Treemap::of(&a), Treemap::of(&b));
+                intersection.bench_function(Id::new("croaring", batch_size), |bench| {
+                    bench.iter(|| croaring_a.and(&croaring_b));
+                });
+            }
+            with_roaring! {
+                let roaring_a = a.iter().collect::<RoaringTreemap>();
+                let roaring_b = b.iter().collect::<RoaringTreemap>();
+                intersection.bench_function(Id::new("roaring", batch_size), |bench| {
+                    bench.iter(|| &roaring_a & &roaring_b);
+                });
+            }
+        }
+        intersection.finish();
+    }
+}
+
+pub fn bench_iterate(c: &mut Harness) {
+    for layout in Layout::ALL {
+        let mut group = Group::new(c, format!("treemap_iterate_{}", layout.name()));
+        group.tags(&["readonly", "iter"]);
+        for &batch_size in &N {
+            let values = layout.values(batch_size);
+            group.throughput(Throughput::Elements(batch_size as u64));
+            with_croaring! {
+                let croaring = Treemap::of(&values);
+                group.bench_with_input(Id::new("croaring", batch_size), &croaring, |b, tm| {
+                    b.iter(|| tm.iter().count());
+                });
+            }
+            with_roaring! {
+                let roaring = values.iter().collect::<RoaringTreemap>();
+                group.bench_with_input(Id::new("roaring", batch_size), &roaring, |b, tm| {
+                    b.iter(|| tm.iter().count());
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+fn config() -> Harness {
+    let c = Criterion::default().with_measurement(measurement());
+    if scale::large_enabled() {
+        c.sample_size(scale::LARGE_SAMPLE_SIZE)
+    } else {
+        c
+    }
+}
+
+criterion_group!(
+    name = benches;
+    config = config();
+    targets = bench_insert, bench_insert_range, bench_contains, bench_set_ops, bench_iterate
+);
+criterion_main!(benches);
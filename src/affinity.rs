@@ -0,0 +1,74 @@
+//! Opt-in pinning of the benchmark thread to a single CPU core.
+//!
+//! At small N the differences between backends are a few nanoseconds, and the
+//! scheduler migrating the benchmark thread between cores adds more noise
+//! than that. Set [`PIN_CORE_ENV`] to the index of an (ideally isolated) core
+//! to pin the measurement thread to it.
+//!
+//! Pinning calls `sched_setaffinity(2)` directly rather than going through
+//! `core_affinity`, whose API pins to one core at a time: the same call
+//! restricts a thread to a set of cores, which [`crate::numa`] uses for the
+//! CPUs of a node. It is only supported on Linux.
+
+use std::env;
+
+/// The environment variable naming the core to pin the benchmark thread to.
+pub const PIN_CORE_ENV: &str = "ROARING_BENCH_PIN_CORE";
+
+/// Pin the calling thread to the core named by [`PIN_CORE_ENV`], returning
+/// the core index if pinning was requested.
+///
+/// # Panics
+///
+/// Panics if the variable is set but is not a valid core index, or the
+/// thread cannot be pinned to it - silently running unpinned would defeat
+/// the point of asking.
+pub fn pin_from_env() -> Option<usize> {
+    let core = env::var(PIN_CORE_ENV).ok()?;
+    let core = core
+        .trim()
+        .parse()
+        .unwrap_or_else(|_| panic!("{} must be a core index, got {:?}", PIN_CORE_ENV, core));
+
+    pin_current_thread(core).unwrap_or_else(|e| panic!("failed to pin to core {}: {}", core, e));
+    Some(core)
+}
+
+/// Restrict the calling thread to run only on `core`.
+pub fn pin_current_thread(core: usize) -> std::io::Result<()> {
+    restrict_current_thread(&[core])
+}
+
+/// Restrict the calling thread to run only on the given set of cores.
+#[cfg(target_os = "linux")]
+pub fn restrict_current_thread(cores: &[usize]) -> std::io::Result<()> {
+    if cores.iter().any(|&c| c >= libc::CPU_SETSIZE as usize) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            "core index exceeds CPU_SETSIZE",
+        ));
+    }
+
+    // SAFETY: cpu_set_t is a plain bitmask for which all zeros is the valid
+    // empty set, and every core has been checked to fit within it.
+    unsafe {
+        let mut set: libc::cpu_set_t = std::mem::zeroed();
+        libc::CPU_ZERO(&mut set);
+        for &core in cores {
+            libc::CPU_SET(core, &mut set);
+        }
+        if libc::sched_setaffinity(0, std::mem::size_of::<libc::cpu_set_t>(), &set) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    Ok(())
+}
+
+/// Restrict the calling thread to run only on the given set of cores.
+#[cfg(not(target_os = "linux"))]
+pub fn restrict_current_thread(_cores: &[usize]) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "thread pinning is only supported on linux",
+    ))
+}
@@ -0,0 +1,127 @@
+//! A criterion [`Measurement`] counting heap allocations, so the benchmarks
+//! show the allocator pressure of each backend rather than its time.
+//!
+//! Enabled by the `alloc-stats` feature, which switches the core benchmark
+//! groups to this measurement: criterion then reports (and saves under
+//! `target/criterion`) allocations per iteration instead of nanoseconds.
+//! The bytes those allocations requested are recorded alongside, and after
+//! each case [`crate::harness::Group`] prints them per iteration and writes
+//! them to [`BYTES_FILE`] in the case's directory.
+//!
+//! Allocations are counted by [`crate::allocator::Tracking`], which must be
+//! installed as the global allocator; croaring's are counted through its
+//! memory hooks (on Linux).
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use criterion::{
+    measurement::{Measurement, ValueFormatter},
+    Throughput,
+};
+
+use crate::allocator;
+
+/// The file within a benchmark's directory holding the mean bytes
+/// allocated per iteration of its most recent run.
+pub const BYTES_FILE: &str = "alloc_bytes";
+
+static WINDOW_ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static WINDOW_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// Counts the allocations made while criterion measures a routine.
+#[derive(Debug)]
+pub struct Allocations(());
+
+impl Allocations {
+    /// Start counting allocations, see [`allocator::enable_tracking`].
+    pub fn new() -> Self {
+        allocator::enable_tracking();
+        Self(())
+    }
+}
+
+impl Default for Allocations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The mean bytes requested per allocation measured since the last call,
+/// or `None` if there were none.
+///
+/// Multiplied by criterion's allocations per iteration this gives the
+/// bytes per iteration: every measured window (warm-up included) runs the
+/// same routine, so their ratio is that of the iterations it reports.
+pub fn take_bytes_per_allocation() -> Option<f64> {
+    let allocations = WINDOW_ALLOCATIONS.swap(0, Ordering::Relaxed);
+    let bytes = WINDOW_BYTES.swap(0, Ordering::Relaxed);
+    (allocations > 0).then(|| bytes as f64 / allocations as f64)
+}
+
+impl Measurement for Allocations {
+    type Intermediate = (u64, u64);
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {
+        (allocator::allocations(), allocator::allocated())
+    }
+
+    fn end(&self, (allocations, bytes): Self::Intermediate) -> Self::Value {
+        let allocations = allocator::allocations() - allocations;
+        WINDOW_ALLOCATIONS.fetch_add(allocations, Ordering::Relaxed);
+        WINDOW_BYTES.fetch_add(allocator::allocated() - bytes, Ordering::Relaxed);
+        allocations
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &AllocationFormatter
+    }
+}
+
+struct AllocationFormatter;
+
+impl ValueFormatter for AllocationFormatter {
+    fn scale_values(&self, typical: f64, values: &mut [f64]) -> &'static str {
+        let (factor, unit) = match typical {
+            t if t < 1e3 => (1.0, "allocs"),
+            t if t < 1e6 => (1e-3, "Kallocs"),
+            _ => (1e-6, "Mallocs"),
+        };
+        for v in values {
+            *v *= factor;
+        }
+        unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        let (n, unit) = match *throughput {
+            Throughput::Bytes(n) | Throughput::BytesDecimal(n) => (n, "allocs/B"),
+            Throughput::Elements(n) => (n, "allocs/elem"),
+        };
+        for v in values {
+            *v /= n as f64;
+        }
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "allocs"
+    }
+}
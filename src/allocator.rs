@@ -0,0 +1,199 @@
+//! The global allocator the benchmarks are running against.
+//!
+//! roaring's allocation-heavy paths are far more sensitive to allocator
+//! choice than croaring's, so results are labelled with the allocator they
+//! were measured under. Only the system allocator is currently wired up.
+//!
+//! The benchmark binary wraps it in [`Tracking`], which can count live and
+//! peak heap usage, and every allocation made, once [`enable_tracking`] is
+//! called. croaring allocates through C's `malloc` rather than the global
+//! allocator, so on Linux its allocations are counted through CRoaring's
+//! memory hooks instead.
+
+use std::{
+    alloc::{GlobalAlloc, Layout},
+    sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering},
+};
+
+/// The name of the global allocator in use.
+pub const NAME: &str = "system";
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED: AtomicU64 = AtomicU64::new(0);
+
+/// A [`GlobalAlloc`] wrapper tracking the bytes allocated through it.
+///
+/// Until [`enable_tracking`] is called the only overhead is one relaxed
+/// atomic load per call, so installing it does not perturb timings. Bytes
+/// allocated before tracking was enabled are not counted, and neither are
+/// their frees.
+#[derive(Debug, Default)]
+pub struct Tracking<A>(pub A);
+
+/// Start counting allocations made through [`Tracking`], and by croaring.
+///
+/// Call this before starting any threads that might use croaring.
+pub fn enable_tracking() {
+    #[cfg(all(feature = "croaring", target_os = "linux"))]
+    if !ENABLED.load(Ordering::SeqCst) {
+        croaring_hooks::install();
+    }
+    ENABLED.store(true, Ordering::SeqCst);
+}
+
+/// Returns true if a [`Tracking`] allocator is installed and has seen an
+/// allocation since tracking was enabled.
+pub fn is_tracking() -> bool {
+    INSTALLED.load(Ordering::Relaxed)
+}
+
+/// The bytes currently allocated since tracking was enabled.
+pub fn current() -> usize {
+    CURRENT.load(Ordering::Relaxed)
+}
+
+/// The largest value of [`current`] since the last [`reset_peak`].
+pub fn peak() -> usize {
+    PEAK.load(Ordering::Relaxed)
+}
+
+/// Reset the peak to the current usage, returning the current usage.
+pub fn reset_peak() -> usize {
+    let current = current();
+    PEAK.store(current, Ordering::Relaxed);
+    current
+}
+
+/// The number of allocations (including reallocations) made since tracking
+/// was enabled.
+pub fn allocations() -> u64 {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// The total bytes allocated since tracking was enabled, whether or not
+/// they have since been freed.
+pub fn allocated() -> u64 {
+    ALLOCATED.load(Ordering::Relaxed)
+}
+
+fn on_alloc(size: usize) {
+    INSTALLED.store(true, Ordering::Relaxed);
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED.fetch_add(size as u64, Ordering::Relaxed);
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+fn on_dealloc(size: usize) {
+    // Saturate, as the block may have been allocated before tracking began.
+    let _ = CURRENT.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |c| {
+        Some(c.saturating_sub(size))
+    });
+}
+
+// SAFETY: defers all allocation to the wrapped allocator.
+unsafe impl<A: GlobalAlloc> GlobalAlloc for Tracking<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.0.alloc(layout);
+        if !ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = self.0.alloc_zeroed(layout);
+        if !ptr.is_null() && ENABLED.load(Ordering::Relaxed) {
+            on_alloc(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.0.dealloc(ptr, layout);
+        if ENABLED.load(Ordering::Relaxed) {
+            on_dealloc(layout.size());
+        }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new = self.0.realloc(ptr, layout, new_size);
+        if !new.is_null() && ENABLED.load(Ordering::Relaxed) {
+            on_dealloc(layout.size());
+            on_alloc(new_size);
+        }
+        new
+    }
+}
+
+/// CRoaring memory hooks counting allocations with `malloc_usable_size()`,
+/// as its `free` hook is not given the allocation size.
+#[cfg(all(feature = "croaring", target_os = "linux"))]
+mod croaring_hooks {
+    use std::{os::raw::c_void, ptr};
+
+    use super::{on_alloc, on_dealloc};
+
+    pub(super) fn install() {
+        // SAFETY: the hooks are built on libc's allocator, which CRoaring
+        // uses by default, so blocks allocated before installation are
+        // still freed correctly.
+        unsafe {
+            croaring_sys::roaring_init_memory_hook(croaring_sys::roaring_memory_t {
+                malloc: Some(malloc),
+                realloc: Some(realloc),
+                calloc: Some(calloc),
+                free: Some(free),
+                aligned_malloc: Some(aligned_malloc),
+                aligned_free: Some(free),
+            });
+        }
+    }
+
+    unsafe fn track(ptr: *mut c_void) -> *mut c_void {
+        if !ptr.is_null() {
+            on_alloc(libc::malloc_usable_size(ptr));
+        }
+        ptr
+    }
+
+    unsafe extern "C" fn malloc(size: usize) -> *mut c_void {
+        track(libc::malloc(size))
+    }
+
+    unsafe extern "C" fn calloc(n: usize, size: usize) -> *mut c_void {
+        track(libc::calloc(n, size))
+    }
+
+    unsafe extern "C" fn realloc(old: *mut c_void, size: usize) -> *mut c_void {
+        let old_size = if old.is_null() {
+            0
+        } else {
+            libc::malloc_usable_size(old)
+        };
+        let new = libc::realloc(old, size);
+        if !new.is_null() {
+            on_dealloc(old_size);
+        }
+        track(new)
+    }
+
+    unsafe extern "C" fn aligned_malloc(alignment: usize, size: usize) -> *mut c_void {
+        let mut out = ptr::null_mut();
+        if libc::posix_memalign(&mut out, alignment, size) != 0 {
+            return ptr::null_mut();
+        }
+        track(out)
+    }
+
+    unsafe extern "C" fn free(ptr: *mut c_void) {
+        if !ptr.is_null() {
+            on_dealloc(libc::malloc_usable_size(ptr));
+        }
+        libc::free(ptr);
+    }
+}
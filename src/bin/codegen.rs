@@ -0,0 +1,81 @@
+//! Compare the suite built for the baseline target against `target-cpu=native`.
+//!
+//! Builds and runs the `quick` binary twice - once with the default target
+//! features and once with `-C target-cpu=native` - in separate target
+//! directories, then merges the results so the effect of CPU feature
+//! availability on each backend is visible side by side.
+//!
+//! Any arguments are forwarded to `quick`.
+//!
+//! ```text
+//! cargo run --release --bin codegen -- [quick args]
+//! ```
+
+use std::{collections::BTreeMap, env, process::Command};
+
+/// The (operation, size, backend, allocator) fields of a `quick` CSV row.
+type CaseKey = (String, u32, String, String);
+
+/// The rustflags each variant is built with.
+const VARIANTS: [(&str, &str); 2] = [("baseline", ""), ("native", "-C target-cpu=native")];
+
+fn main() {
+    let forwarded: Vec<String> = env::args().skip(1).collect();
+
+    let mut merged: BTreeMap<CaseKey, Vec<u128>> = BTreeMap::new();
+    for (label, rustflags) in VARIANTS {
+        eprintln!("running {label} build");
+        for (key, nanos) in run_quick(label, rustflags, &forwarded) {
+            merged.entry(key).or_default().push(nanos);
+        }
+    }
+
+    println!("operation,size,backend,allocator,baseline_nanos,native_nanos,native_speedup");
+    for ((operation, size, backend, allocator), nanos) in merged {
+        let (baseline, native) = match nanos[..] {
+            [b, n] => (b, n),
+            _ => continue,
+        };
+        println!(
+            "{operation},{size},{backend},{allocator},{baseline},{native},{:.3}",
+            baseline as f64 / native.max(1) as f64
+        );
+    }
+}
+
+/// Build and run `quick` with the given `rustflags`, returning the
+/// per-iteration nanoseconds of each case.
+fn run_quick(label: &str, rustflags: &str, forwarded: &[String]) -> Vec<(CaseKey, u128)> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let output = Command::new(cargo)
+        .args(["run", "--quiet", "--release", "--bin", "quick", "--"])
+        .arg("--csv")
+        .args(forwarded)
+        .env("RUSTFLAGS", rustflags)
+        .env("CARGO_TARGET_DIR", format!("target/codegen-{label}"))
+        .output()
+        .expect("failed to run cargo");
+
+    if !output.status.success() {
+        eprint!("{}", String::from_utf8_lossy(&output.stderr));
+        panic!("{} build of quick failed", label);
+    }
+
+    String::from_utf8(output.stdout)
+        .expect("quick emits utf8")
+        .lines()
+        // quick may print notes (core pinning, NUMA binding) before the CSV.
+        .skip_while(|line| !line.starts_with("operation,"))
+        .skip(1)
+        .map(|line| {
+            let fields: Vec<&str> = line.split(',').collect();
+            let key = (
+                fields[0].to_string(),
+                fields[1].parse().expect("size"),
+                fields[2].to_string(),
+                fields[3].to_string(),
+            );
+            (key, fields[5].parse().expect("nanos_per_iter"))
+        })
+        .collect()
+}
@@ -0,0 +1,138 @@
+//! Find where two backends swap places for an operation.
+//!
+//! By default this bisects over the set size at a fixed density. Use
+//! `--sweep density` to bisect over density at a fixed size instead, or
+//! `--heatmap` to emit a CSV of the winner at every size × density
+//! combination.
+//!
+//! ```text
+//! cargo run --release --bin crossover -- --op union_no_rle
+//!     [--sweep size [--density D] | --sweep density [--size N]] [--lo X] [--hi Y]
+//!     [--heatmap [--sizes 10,1000] [--densities 0.01,1]]
+//!     [--backends croaring,roaring] [--iterations N] [--samples K]
+//! ```
+
+use std::{env, fmt::Display, process, str::FromStr};
+
+use roaring_bench::{
+    crossover::{self, Sweep},
+    Backend, Operation, SuiteConfig,
+};
+
+fn main() {
+    let mut config = SuiteConfig {
+        densities: vec![0.001, 0.01, 0.1, 0.5, 1.0],
+        ..Default::default()
+    };
+
+    let mut operation = None;
+    let mut sweep_density = false;
+    let mut heatmap = false;
+    let (mut size, mut density) = (100_000, 1.0);
+    let (mut lo, mut hi): (Option<f64>, Option<f64>) = (None, None);
+    let mut backends = (Backend::CRoaring, Backend::Roaring);
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("missing value for {arg}");
+                process::exit(2);
+            })
+        };
+        match arg.as_str() {
+            "--op" => operation = Some(parse(&value())),
+            "--sweep" => match value().as_str() {
+                "size" => sweep_density = false,
+                "density" => sweep_density = true,
+                v => {
+                    eprintln!("--sweep must be size or density, got {v:?}");
+                    process::exit(2);
+                }
+            },
+            "--heatmap" => heatmap = true,
+            "--size" => size = parse(&value()),
+            "--density" => density = parse(&value()),
+            "--sizes" => config.sizes = value().split(',').map(parse).collect(),
+            "--densities" => config.densities = value().split(',').map(parse).collect(),
+            "--lo" => lo = Some(parse(&value())),
+            "--hi" => hi = Some(parse(&value())),
+            "--iterations" => config.iterations = parse(&value()),
+            "--samples" => config.samples = parse(&value()),
+            "--backends" => {
+                let v = value();
+                let (a, b) = v.split_once(',').unwrap_or_else(|| {
+                    eprintln!("--backends takes two comma separated names");
+                    process::exit(2);
+                });
+                backends = (parse(a), parse(b));
+            }
+            _ => {
+                eprintln!("unknown argument {arg}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let operation: Operation = operation.unwrap_or_else(|| {
+        eprintln!("--op is required");
+        process::exit(2);
+    });
+
+    if heatmap {
+        println!("size,density,winner,ratio");
+        for cell in crossover::heatmap(&config, operation, backends.0, backends.1) {
+            println!(
+                "{},{},{},{:.3}",
+                cell.size, cell.density, cell.winner, cell.ratio
+            );
+        }
+        return;
+    }
+
+    let (sweep, lo, hi) = if sweep_density {
+        (
+            Sweep::Density { size },
+            lo.unwrap_or(0.0001),
+            hi.unwrap_or(1.0),
+        )
+    } else {
+        (
+            Sweep::Size { density },
+            lo.unwrap_or(10.0),
+            hi.unwrap_or(1_000_000.0),
+        )
+    };
+    let dimension = if sweep_density { "density" } else { "N" };
+    if !(lo > 0.0 && lo < hi) {
+        eprintln!("--lo must be positive and below --hi, got {lo} and {hi}");
+        process::exit(2);
+    }
+    if !sweep_density && (lo.fract() != 0.0 || hi.fract() != 0.0) {
+        eprintln!("--lo and --hi must be whole sizes, got {lo} and {hi}");
+        process::exit(2);
+    }
+
+    match crossover::find_crossover(&config, operation, backends.0, backends.1, sweep, lo, hi) {
+        Some(c) => println!(
+            "{operation}: {} is faster below {dimension}={:.4}, {} from {dimension}={:.4} upwards",
+            c.below, c.at, c.above, c.at
+        ),
+        None => println!(
+            "{operation}: no crossover between {dimension}={lo} and {dimension}={hi} \
+             ({} and {} keep their ranking)",
+            backends.0, backends.1
+        ),
+    }
+}
+
+fn parse<T>(v: &str) -> T
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    v.parse().unwrap_or_else(|e| {
+        eprintln!("invalid value {v:?}: {e}");
+        process::exit(2);
+    })
+}
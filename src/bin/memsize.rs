@@ -0,0 +1,155 @@
+//! Report the memory footprint of each backend for every input distribution
+//! and size, as a markdown table (or CSV).
+//!
+//! ```text
+//! cargo run --release --bin memsize -- [--sizes 10,1000] [--density 0.1] [--csv]
+//! ```
+//!
+//! The heap usage is the growth in live bytes while building the bitmap
+//! from a prepared slice, counted by [`allocator::Tracking`]. croaring
+//! allocates through C's `malloc`, which is only counted on Linux.
+
+// With neither library enabled the report is empty.
+#![cfg_attr(not(any(feature = "croaring", feature = "roaring")), allow(unused))]
+
+use std::{alloc::System, env, process};
+
+use roaring_bench::{
+    allocator::{self, Tracking},
+    gen::{self, Distribution},
+    with_croaring, with_roaring, N,
+};
+
+#[global_allocator]
+static ALLOC: Tracking<System> = Tracking(System);
+
+/// The footprint of one backend's bitmap.
+struct Row {
+    distribution: Distribution,
+    size: u32,
+    backend: &'static str,
+    heap: usize,
+    serialized: usize,
+
+    /// Only reported by croaring.
+    containers: Option<u64>,
+}
+
+fn main() {
+    let mut sizes = N.to_vec();
+    let mut density = gen::DEFAULT_DENSITY;
+    let mut csv = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("missing value for {arg}");
+                process::exit(2);
+            })
+        };
+        match arg.as_str() {
+            "--sizes" => sizes = value().split(',').map(parse).collect(),
+            "--density" => density = parse(&value()),
+            "--csv" => csv = true,
+            _ => {
+                eprintln!("unknown argument {arg}");
+                process::exit(2);
+            }
+        }
+    }
+
+    allocator::enable_tracking();
+
+    let mut rows: Vec<Row> = Vec::new();
+    for distribution in Distribution::ALL {
+        for &size in &sizes {
+            let mut rng = gen::rng(&format!("memsize/{distribution}/{size}"));
+            let values = gen::generate(distribution, size, density, &mut rng);
+            let row = |backend, heap, serialized, containers| Row {
+                distribution,
+                size,
+                backend,
+                heap,
+                serialized,
+                containers,
+            };
+
+            with_croaring! {
+                let (heap, mut bm) = measure(|| croaring::Bitmap::of(&values));
+                let stats = bm.statistics();
+                rows.push(row(
+                    "croaring",
+                    heap,
+                    bm.get_serialized_size_in_bytes(),
+                    Some(stats.n_containers as u64),
+                ));
+
+                let before = allocator::current();
+                bm.run_optimize();
+                bm.shrink_to_fit();
+                let heap = (heap + allocator::current()).saturating_sub(before);
+                let stats = bm.statistics();
+                rows.push(row(
+                    "croaring_rle",
+                    heap,
+                    bm.get_serialized_size_in_bytes(),
+                    Some(stats.n_containers as u64),
+                ));
+            }
+
+            with_roaring! {
+                let (heap, bm) = measure(|| {
+                    roaring::RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap()
+                });
+                rows.push(row("roaring", heap, bm.serialized_size(), None));
+            }
+        }
+    }
+
+    if csv {
+        println!("distribution,size,backend,heap_bytes,serialized_bytes,containers");
+        for r in &rows {
+            println!(
+                "{},{},{},{},{},{}",
+                r.distribution,
+                r.size,
+                r.backend,
+                r.heap,
+                r.serialized,
+                r.containers.map(|c| c.to_string()).unwrap_or_default(),
+            );
+        }
+        return;
+    }
+
+    println!("| distribution | size | backend | heap bytes | bytes/value | serialized bytes | containers |");
+    println!("|---|---:|---|---:|---:|---:|---:|");
+    for r in &rows {
+        println!(
+            "| {} | {} | {} | {} | {:.2} | {} | {} |",
+            r.distribution,
+            r.size,
+            r.backend,
+            r.heap,
+            r.heap as f64 / r.size.max(1) as f64,
+            r.serialized,
+            r.containers.map(|c| c.to_string()).unwrap_or_default(),
+        );
+    }
+}
+
+/// Run `f`, returning the growth in live heap bytes while it ran along with
+/// its output.
+fn measure<T>(f: impl FnOnce() -> T) -> (usize, T) {
+    let before = allocator::current();
+    let out = f();
+    (allocator::current().saturating_sub(before), out)
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> T {
+    v.parse().unwrap_or_else(|_| {
+        eprintln!("invalid number {v}");
+        process::exit(2);
+    })
+}
@@ -0,0 +1,86 @@
+//! A criterion-free quick measurement of the benchmark suite.
+//!
+//! Runs each case a fixed number of times and reports the fastest of several
+//! samples. This is intended for fast iteration while working on the harness
+//! itself - use `cargo bench` for numbers worth comparing.
+//!
+//! ```text
+//! cargo run --release --bin quick -- [--iterations N] [--samples K] [--sizes 10,1000] [--csv]
+//! ```
+
+use std::{env, process};
+
+use roaring_bench::{affinity, numa, run_suite, SuiteConfig};
+
+fn main() {
+    if let Some(node) = numa::bind_from_env() {
+        println!("bound to NUMA node {node}");
+    }
+    if let Some(core) = affinity::pin_from_env() {
+        println!("pinned to core {core}");
+    }
+
+    let mut config = SuiteConfig::default();
+    let mut csv = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("missing value for {arg}");
+                process::exit(2);
+            })
+        };
+        match arg.as_str() {
+            "--iterations" => config.iterations = parse(&value()),
+            "--samples" => config.samples = parse(&value()),
+            "--sizes" => config.sizes = value().split(',').map(parse).collect(),
+            "--csv" => csv = true,
+            _ => {
+                eprintln!("unknown argument {arg}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let results = run_suite(&config);
+
+    if csv {
+        println!("operation,size,backend,allocator,iterations,nanos_per_iter");
+        for r in results {
+            println!(
+                "{},{},{},{},{},{}",
+                r.operation.name(),
+                r.size,
+                r.backend.name(),
+                r.allocator,
+                r.iterations,
+                r.per_iteration.as_nanos(),
+            );
+        }
+        return;
+    }
+
+    println!(
+        "{:<24} {:>10} {:<10} {:<10} {:>14} {:>14}",
+        "operation", "size", "backend", "allocator", "time/iter", "Melem/s"
+    );
+    for r in results {
+        println!(
+            "{:<24} {:>10} {:<10} {:<10} {:>14} {:>14.2}",
+            r.operation.name(),
+            r.size,
+            r.backend.name(),
+            r.allocator,
+            format!("{:?}", r.per_iteration),
+            r.elements_per_second() / 1e6,
+        );
+    }
+}
+
+fn parse(v: &str) -> u32 {
+    v.parse().unwrap_or_else(|_| {
+        eprintln!("invalid number {v}");
+        process::exit(2);
+    })
+}
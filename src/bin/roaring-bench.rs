@@ -0,0 +1,662 @@
+//! Run a chosen slice of the benchmark suite from the command line.
+//!
+//! Each of `--op`, `--lib` and `--dist` selects every operation, library or
+//! distribution whose name starts with one of its (comma separated) values,
+//! so `--op add` runs all of the insert operations. Sizes accept exponent
+//! notation, and `--large` adds the [`scale::LARGE_SIZES`] tier.
+//!
+//! ```text
+//! cargo run --release --bin roaring-bench -- run --op union --lib roaring --n 1e6 --dist zipf
+//!     [--large] [--density 0.1,1] [--iterations N] [--samples K] [--measurement-time SECS] [--csv]
+//! cargo run --release --bin roaring-bench -- run --scenario FILE [flags overriding it]
+//! cargo run --release --bin roaring-bench -- list
+//! cargo run --bin roaring-bench -- export [--dir target/criterion] [--format csv|json]
+//!     [--output FILE] [--markdown FILE]
+//! cargo run --bin roaring-bench -- report [--dir target/criterion] [--format markdown|html]
+//!     [--output FILE]
+//! cargo run --bin roaring-bench -- baseline save|compare <name> [--dir target/criterion]
+//!     [--baselines target/baselines] [--threshold PERCENT]
+//! cargo run --bin roaring-bench -- history record [--dir target/criterion]
+//!     [--history target/history.jsonl]
+//! cargo run --bin roaring-bench -- history trend [--op NAME] [--lib NAME] [--last RUNS]
+//!     [--format table|csv] [--history target/history.jsonl]
+//! ```
+//!
+//! Cases are measured by [`run_suite`], the same criterion-free timer used
+//! by the `quick` binary. `--scenario` reads the cases to run from a TOML
+//! file instead (see [`scenario`]), to which any other flags given apply on
+//! top.
+//!
+//! `export` instead flattens the results of a previous `cargo bench` into a
+//! single CSV or JSON file, `target/criterion/results.<format>` by default,
+//! and prints a markdown table of the croaring and roaring medians of each
+//! (operation, n, distribution) with the speedup of roaring over croaring.
+//!
+//! `report` renders the same comparison as a single markdown or HTML
+//! document, charting the two medians of every case alongside the machine,
+//! rustc and crate versions it was generated on - which should be those the
+//! results were measured with.
+//!
+//! `baseline save` snapshots the results of a previous `cargo bench` under
+//! a name, and `baseline compare` checks the latest results against such a
+//! snapshot, listing the cases whose median moved by more than the
+//! threshold (5% by default) and exiting with status 1 if any regressed.
+//!
+//! `history record` appends the results of a previous `cargo bench` to a
+//! JSON lines history with the time, commit, CPU and crate versions they
+//! were recorded with, and `history trend` prints the median of each case
+//! in every recorded run (or the last `--last`), with its change from the
+//! first and a sparkline, or as CSV for plotting elsewhere.
+
+use std::{fs, process, time::Duration};
+
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use roaring_bench::{
+    affinity, estimates,
+    export::{self, Row},
+    gen::Distribution,
+    history, numa,
+    regression::{self, Change},
+    report, run_suite, scale, scenario, Backend, Operation, SuiteConfig,
+};
+
+fn main() {
+    let matches = cli().get_matches();
+
+    match matches.subcommand() {
+        Some(("run", args)) => run(args),
+        Some(("export", args)) => export(args),
+        Some(("report", args)) => report(args),
+        Some(("baseline", args)) => baseline(args),
+        Some(("history", args)) => history(args),
+        Some(("list", _)) => {
+            println!("operations:");
+            Operation::ALL.iter().for_each(|o| println!("  {o}"));
+            println!("libraries:");
+            Backend::ALL.iter().for_each(|b| println!("  {b}"));
+            println!("distributions:");
+            Distribution::ALL.iter().for_each(|d| println!("  {d}"));
+        }
+        _ => unreachable!("a subcommand is required"),
+    }
+}
+
+fn cli() -> Command<'static> {
+    Command::new("roaring-bench")
+        .about("Run a chosen slice of the roaring benchmark suite")
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .subcommand(
+            Command::new("run")
+                .about("Measure the selected cases")
+                .arg(
+                    Arg::new("scenario")
+                        .long("scenario")
+                        .value_name("FILE")
+                        .help("Read the cases to run from a TOML scenario file"),
+                )
+                .arg(list(
+                    "op",
+                    "NAME",
+                    "Run the operations starting with NAME [default: all]",
+                ))
+                .arg(list(
+                    "lib",
+                    "NAME",
+                    "Run the libraries starting with NAME [default: all]",
+                ))
+                .arg(list(
+                    "dist",
+                    "NAME",
+                    "Draw input from the distributions starting with NAME [default: spaced]",
+                ))
+                .arg(
+                    list("n", "SIZE", "The set sizes to run [default: all]")
+                        .value_parser(scale::parse_size),
+                )
+                .arg(
+                    list(
+                        "density",
+                        "D",
+                        "The fractions of the universe populated [default: 1]",
+                    )
+                    .value_parser(clap::value_parser!(f64)),
+                )
+                .arg(
+                    Arg::new("iterations")
+                        .long("iterations")
+                        .value_name("N")
+                        .help("Executions of the operation per sample [default: 10]")
+                        .value_parser(clap::value_parser!(u32)),
+                )
+                .arg(
+                    Arg::new("samples")
+                        .long("samples")
+                        .value_name("K")
+                        .help("Samples per case, of which the fastest is reported [default: 5]")
+                        .value_parser(clap::value_parser!(u32)),
+                )
+                .arg(
+                    Arg::new("measurement-time")
+                        .long("measurement-time")
+                        .value_name("SECS")
+                        .help("Time spent measuring each case, overriding --iterations")
+                        .value_parser(seconds),
+                )
+                .arg(
+                    Arg::new("large")
+                        .long("large")
+                        .help("Add the 10M, 100M and full u32 range sizes")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("csv")
+                        .long("csv")
+                        .help("Print CSV instead of a table")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(Command::new("list").about("List the operations, libraries and distributions"))
+        .subcommand(
+            Command::new("export")
+                .about("Export the criterion results as one CSV or JSON file")
+                .arg(criterion_dir())
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["csv", "json"])
+                        .default_value("csv"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Where to write the results [default: <dir>/results.<format>]"),
+                )
+                .arg(
+                    Arg::new("markdown")
+                        .long("markdown")
+                        .value_name("FILE")
+                        .help("Write the comparison table to FILE instead of stdout"),
+                ),
+        )
+        .subcommand(
+            Command::new("report")
+                .about("Render a croaring vs roaring report of the criterion results")
+                .arg(criterion_dir())
+                .arg(
+                    Arg::new("format")
+                        .long("format")
+                        .value_parser(["markdown", "html"])
+                        .default_value("markdown"),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .value_name("FILE")
+                        .help("Where to write the report [default: stdout]"),
+                ),
+        )
+        .subcommand(
+            Command::new("baseline")
+                .about("Save the criterion results as a baseline, or check them against one")
+                .subcommand_required(true)
+                .arg(criterion_dir().global(true))
+                .arg(
+                    Arg::new("baselines")
+                        .long("baselines")
+                        .value_name("DIR")
+                        .help("The directory baselines are kept in")
+                        .default_value(regression::DEFAULT_DIR)
+                        .global(true),
+                )
+                .subcommand(
+                    Command::new("save")
+                        .about("Snapshot the criterion results as the baseline NAME")
+                        .arg(Arg::new("name").value_name("NAME").required(true)),
+                )
+                .subcommand(
+                    Command::new("compare")
+                        .about("Compare the criterion results against the baseline NAME")
+                        .arg(Arg::new("name").value_name("NAME").required(true))
+                        .arg(
+                            Arg::new("threshold")
+                                .long("threshold")
+                                .value_name("PERCENT")
+                                .help("The change in median flagged [default: 5]")
+                                .value_parser(clap::value_parser!(f64)),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("history")
+                .about("Record the criterion results in a history, or show their trend over time")
+                .subcommand_required(true)
+                .arg(
+                    Arg::new("history")
+                        .long("history")
+                        .value_name("FILE")
+                        .help("The history file")
+                        .default_value(history::DEFAULT_PATH)
+                        .global(true),
+                )
+                .subcommand(
+                    Command::new("record")
+                        .about("Append the criterion results to the history")
+                        .arg(criterion_dir()),
+                )
+                .subcommand(
+                    Command::new("trend")
+                        .about("Show the median of each case across the recorded runs")
+                        .arg(list(
+                            "op",
+                            "NAME",
+                            "Show the operations starting with NAME [default: all]",
+                        ))
+                        .arg(list(
+                            "lib",
+                            "NAME",
+                            "Show the backends starting with NAME [default: all]",
+                        ))
+                        .arg(
+                            Arg::new("last")
+                                .long("last")
+                                .value_name("RUNS")
+                                .help("Only show the most recent RUNS runs [default: all]")
+                                .value_parser(clap::value_parser!(usize)),
+                        )
+                        .arg(
+                            Arg::new("format")
+                                .long("format")
+                                .value_parser(["table", "csv"])
+                                .default_value("table"),
+                        ),
+                ),
+        )
+}
+
+fn run(args: &ArgMatches) {
+    if let Some(node) = numa::bind_from_env() {
+        println!("bound to NUMA node {node}");
+    }
+    if let Some(core) = affinity::pin_from_env() {
+        println!("pinned to core {core}");
+    }
+
+    let mut config = match args.get_one::<String>("scenario") {
+        Some(path) => scenario::load(path).unwrap_or_else(|e| {
+            eprintln!("failed to load scenario: {e}");
+            process::exit(1);
+        }),
+        None => SuiteConfig::default(),
+    };
+    merge(&mut config, args);
+
+    let results = run_suite(&config);
+    let distribution = |d: Option<Distribution>| d.map_or("spaced", |d| d.name());
+
+    if args.get_flag("csv") {
+        println!("operation,distribution,density,size,backend,allocator,iterations,nanos_per_iter");
+        for r in results {
+            println!(
+                "{},{},{},{},{},{},{},{}",
+                r.operation.name(),
+                distribution(r.distribution),
+                r.density,
+                r.size,
+                r.backend.name(),
+                r.allocator,
+                r.iterations,
+                r.per_iteration.as_nanos(),
+            );
+        }
+        return;
+    }
+
+    println!(
+        "{:<24} {:<14} {:>8} {:>10} {:<10} {:>14} {:>14}",
+        "operation", "distribution", "density", "size", "backend", "time/iter", "Melem/s"
+    );
+    for r in results {
+        println!(
+            "{:<24} {:<14} {:>8} {:>10} {:<10} {:>14} {:>14.2}",
+            r.operation.name(),
+            distribution(r.distribution),
+            r.density,
+            r.size,
+            r.backend.name(),
+            format!("{:?}", r.per_iteration),
+            r.elements_per_second() / 1e6,
+        );
+    }
+}
+
+/// Apply the flags given in `args` on top of `config`, leaving the settings
+/// of flags not given as they are.
+fn merge(config: &mut SuiteConfig, args: &ArgMatches) {
+    if args.contains_id("op") {
+        config.operations = select(args, "op", &Operation::ALL, Operation::name);
+    }
+    if args.contains_id("lib") {
+        config.backends = select(args, "lib", Backend::ALL, Backend::name);
+    }
+    if args.contains_id("dist") {
+        config.distributions = select(args, "dist", &Distribution::ALL, Distribution::name);
+    }
+    if let Some(sizes) = args.get_many::<u32>("n") {
+        config.sizes = sizes.copied().collect();
+    }
+    if args.get_flag("large") {
+        config.sizes.extend(scale::LARGE_SIZES);
+        config.sizes.sort_unstable();
+        config.sizes.dedup();
+    }
+    if let Some(densities) = args.get_many::<f64>("density") {
+        config.densities = densities.copied().collect();
+    }
+    if let Some(&iterations) = args.get_one::<u32>("iterations") {
+        config.iterations = iterations;
+    }
+    if let Some(&samples) = args.get_one::<u32>("samples") {
+        config.samples = samples;
+    }
+    if let Some(&t) = args.get_one::<Duration>("measurement-time") {
+        config.measurement_time = Some(t);
+    }
+}
+
+fn export(args: &ArgMatches) {
+    let dir = args.get_one::<String>("dir").expect("has a default");
+    let rows = load_rows(dir);
+
+    let format = args.get_one::<String>("format").expect("has a default");
+    let contents = match format.as_str() {
+        "json" => serde_json::to_string_pretty(&rows).expect("rows serialize") + "\n",
+        _ => export::csv(&rows),
+    };
+    let output = args
+        .get_one::<String>("output")
+        .cloned()
+        .unwrap_or_else(|| format!("{dir}/results.{format}"));
+    write(&output, &contents);
+    eprintln!("wrote {} results to {output}", rows.len());
+
+    let table = export::markdown(&export::speedups(&rows));
+    match args.get_one::<String>("markdown") {
+        Some(path) => write(path, &table),
+        None => print!("{table}"),
+    }
+}
+
+fn report(args: &ArgMatches) {
+    let dir = args.get_one::<String>("dir").expect("has a default");
+    let speedups = export::speedups(&load_rows(dir));
+    let env = report::Environment::collect();
+    let contents = match args
+        .get_one::<String>("format")
+        .expect("has a default")
+        .as_str()
+    {
+        "html" => report::html(&speedups, &env),
+        _ => report::markdown(&speedups, &env),
+    };
+    match args.get_one::<String>("output") {
+        Some(path) => {
+            write(path, &contents);
+            eprintln!("wrote a report of {} cases to {path}", speedups.len());
+        }
+        None => print!("{contents}"),
+    }
+}
+
+fn baseline(args: &ArgMatches) {
+    let (command, args) = args.subcommand().expect("a subcommand is required");
+    let dir = args.get_one::<String>("dir").expect("has a default");
+    let baselines = args.get_one::<String>("baselines").expect("has a default");
+    let name = args.get_one::<String>("name").expect("is required");
+    let rows = load_rows(dir);
+
+    if command == "save" {
+        let path = regression::save(baselines, name, &rows).unwrap_or_else(|e| {
+            eprintln!("failed to save baseline {name}: {e}");
+            process::exit(1);
+        });
+        println!("saved {} results to {}", rows.len(), path.display());
+        return;
+    }
+
+    let threshold = args
+        .get_one::<f64>("threshold")
+        .map_or(regression::DEFAULT_THRESHOLD, |p| p / 100.0);
+    let baseline = regression::load(baselines, name).unwrap_or_else(|e| {
+        eprintln!("failed to load baseline {name}: {e}");
+        process::exit(1);
+    });
+    let report = regression::compare(&baseline, &rows, threshold);
+
+    let changed = report
+        .deltas
+        .iter()
+        .filter(|d| d.change != Change::Unchanged)
+        .collect::<Vec<_>>();
+    if !changed.is_empty() {
+        println!(
+            "{:<10} {:<32} {:<14} {:<10} {:>10} {:>14} {:>14} {:>8}",
+            "change",
+            "operation",
+            "distribution",
+            "backend",
+            "n",
+            "baseline ns",
+            "current ns",
+            "delta"
+        );
+        for d in &changed {
+            let r = &d.current;
+            println!(
+                "{:<10} {:<32} {:<14} {:<10} {:>10} {:>14.1} {:>14.1} {:>+7.1}%",
+                d.change.to_string(),
+                r.operation,
+                r.distribution.map_or("-", |d| d.name()),
+                r.backend,
+                r.n.as_deref().unwrap_or("-"),
+                d.baseline_ns,
+                r.median_ns,
+                d.relative() * 100.0,
+            );
+        }
+        println!();
+    }
+
+    let regressed = report.regressions().count();
+    println!(
+        "{} cases compared against {name}: {regressed} regressed, {} improved beyond {:.1}% \
+         ({} only in one run)",
+        report.deltas.len(),
+        changed.len() - regressed,
+        threshold * 100.0,
+        report.unmatched,
+    );
+    if report.has_regressions() {
+        process::exit(1);
+    }
+}
+
+fn history(args: &ArgMatches) {
+    let (command, args) = args.subcommand().expect("a subcommand is required");
+    let path = args.get_one::<String>("history").expect("has a default");
+
+    if command == "record" {
+        let dir = args.get_one::<String>("dir").expect("has a default");
+        let entry = history::Entry::new(load_rows(dir));
+        history::append(path, &entry).unwrap_or_else(|e| {
+            eprintln!("failed to record history: {e}");
+            process::exit(1);
+        });
+        println!(
+            "recorded {} results at {} ({}) in {path}",
+            entry.rows.len(),
+            history::format_timestamp(entry.timestamp),
+            entry.short_commit(),
+        );
+        return;
+    }
+
+    let mut entries = history::load(path).unwrap_or_else(|e| {
+        eprintln!("failed to load history: {e}");
+        process::exit(1);
+    });
+    if let Some(&last) = args.get_one::<usize>("last") {
+        entries.drain(..entries.len().saturating_sub(last));
+    }
+    let matches = |arg: &str, name: &str| {
+        args.get_many::<String>(arg)
+            .is_none_or(|mut f| f.any(|f| name.starts_with(f.as_str())))
+    };
+    let series = history::trend(&entries)
+        .into_iter()
+        .filter(|s| matches("op", &s.operation) && matches("lib", &s.backend))
+        .collect::<Vec<_>>();
+    if series.is_empty() {
+        eprintln!("no recorded results match in {path}");
+        process::exit(1);
+    }
+
+    if args.get_one::<String>("format").expect("has a default") == "csv" {
+        print!("{}", history::csv(&entries, &series));
+        return;
+    }
+
+    for (i, e) in entries.iter().enumerate() {
+        let crates = e
+            .environment
+            .crates
+            .iter()
+            .map(|(name, version)| format!("{name} {version}"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        println!(
+            "run {:<3} {} {:<8} {}",
+            i + 1,
+            history::format_timestamp(e.timestamp),
+            e.short_commit(),
+            crates,
+        );
+    }
+    println!();
+    println!(
+        "{:<32} {:<14} {:<10} {:>10} {:>14} {:>14} {:>8}  trend",
+        "operation", "distribution", "backend", "n", "first ns", "last ns", "change"
+    );
+    for s in &series {
+        println!(
+            "{:<32} {:<14} {:<10} {:>10} {:>14.1} {:>14.1} {:>+7.1}%  {}",
+            s.operation,
+            s.distribution.map_or("-", |d| d.name()),
+            s.backend,
+            s.n.as_deref().unwrap_or("-"),
+            s.first_ns(),
+            s.last_ns(),
+            s.relative() * 100.0,
+            s.sparkline(),
+        );
+    }
+}
+
+/// The flattened results of the criterion run in `dir`, exiting if there
+/// are none.
+fn load_rows(dir: &str) -> Vec<Row> {
+    let measurements = estimates::load(dir).unwrap_or_else(|e| {
+        eprintln!("failed to load criterion results from {dir}: {e}");
+        process::exit(1);
+    });
+    if measurements.is_empty() {
+        eprintln!("no criterion results found in {dir}");
+        process::exit(1);
+    }
+    export::rows(&measurements)
+}
+
+fn criterion_dir() -> Arg<'static> {
+    Arg::new("dir")
+        .long("dir")
+        .value_name("DIR")
+        .help("The criterion output directory")
+        .default_value(estimates::DEFAULT_DIR)
+}
+
+fn write(path: &str, contents: &str) {
+    fs::write(path, contents).unwrap_or_else(|e| {
+        eprintln!("failed to write {path}: {e}");
+        process::exit(1);
+    });
+}
+
+/// A repeatable, comma separated option.
+fn list(name: &'static str, value: &'static str, help: &'static str) -> Arg<'static> {
+    Arg::new(name)
+        .long(name)
+        .value_name(value)
+        .help(help)
+        .action(ArgAction::Append)
+        .value_delimiter(',')
+}
+
+/// The items of `all` whose name starts with one of the values of `arg`, or
+/// all of them if it was not given.
+fn select<T: Copy>(
+    args: &ArgMatches,
+    arg: &str,
+    all: &[T],
+    name: fn(&T) -> &'static str,
+) -> Vec<T> {
+    let filters = match args.get_many::<String>(arg) {
+        Some(f) => f.collect::<Vec<_>>(),
+        None => return all.to_vec(),
+    };
+    for f in &filters {
+        if !all.iter().any(|v| name(v).starts_with(f.as_str())) {
+            let names = all.iter().map(name).collect::<Vec<_>>().join(", ");
+            eprintln!("no --{arg} matches {f:?}, expected one of: {names}");
+            process::exit(2);
+        }
+    }
+    all.iter()
+        .copied()
+        .filter(|v| filters.iter().any(|f| name(v).starts_with(f.as_str())))
+        .collect()
+}
+
+fn seconds(v: &str) -> Result<Duration, String> {
+    match v.parse::<f64>() {
+        Ok(secs) if secs > 0.0 => Ok(Duration::from_secs_f64(secs)),
+        _ => Err(format!("invalid number of seconds {v:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_keeps_scenario_measurement_time() {
+        let mut config = scenario::parse("measurement_time = 2.5\niterations = 3\n").unwrap();
+        let matches = cli().get_matches_from(["roaring-bench", "run", "--samples", "7"]);
+        let (_, args) = matches.subcommand().unwrap();
+        merge(&mut config, args);
+
+        assert_eq!(config.measurement_time, Some(Duration::from_secs_f64(2.5)));
+        assert_eq!(config.iterations, 3);
+        assert_eq!(config.samples, 7);
+    }
+
+    #[test]
+    fn merge_overrides_scenario_measurement_time() {
+        let mut config = scenario::parse("measurement_time = 2.5\n").unwrap();
+        let matches = cli().get_matches_from(["roaring-bench", "run", "--measurement-time", "1"]);
+        let (_, args) = matches.subcommand().unwrap();
+        merge(&mut config, args);
+
+        assert_eq!(config.measurement_time, Some(Duration::from_secs(1)));
+    }
+}
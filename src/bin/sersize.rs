@@ -0,0 +1,138 @@
+//! Report the serialized size of each backend and format for every input
+//! distribution and size, as a markdown table (or CSV), to read alongside
+//! the byte throughput of the serialization benchmarks.
+//!
+//! ```text
+//! cargo run --release --bin sersize -- [--sizes 10,1000] [--density 0.1] [--csv]
+//! ```
+//!
+//! croaring is measured in its portable format (shared with roaring and the
+//! other Roaring implementations), its native format (a tag byte followed
+//! by the portable format or, when smaller, a flat array of the values) and
+//! its frozen format, each before and after `run_optimize`. The ratio is that
+//! of the serialized size to the 4 bytes per value of a plain `&[u32]`.
+
+// With neither library enabled the report is empty.
+#![cfg_attr(not(any(feature = "croaring", feature = "roaring")), allow(unused))]
+
+use std::{env, process};
+
+use roaring_bench::{
+    gen::{self, Distribution},
+    with_croaring, with_roaring, N,
+};
+
+/// The serialized size of one backend's bitmap in one format.
+struct Row {
+    distribution: Distribution,
+    size: u32,
+    backend: &'static str,
+    format: &'static str,
+    bytes: usize,
+}
+
+fn main() {
+    let mut sizes = N.to_vec();
+    let mut density = gen::DEFAULT_DENSITY;
+    let mut csv = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next().unwrap_or_else(|| {
+                eprintln!("missing value for {arg}");
+                process::exit(2);
+            })
+        };
+        match arg.as_str() {
+            "--sizes" => sizes = value().split(',').map(parse).collect(),
+            "--density" => density = parse(&value()),
+            "--csv" => csv = true,
+            _ => {
+                eprintln!("unknown argument {arg}");
+                process::exit(2);
+            }
+        }
+    }
+
+    let mut rows: Vec<Row> = Vec::new();
+    for distribution in Distribution::ALL {
+        for &size in &sizes {
+            let mut rng = gen::rng(&format!("sersize/{distribution}/{size}"));
+            let values = gen::generate(distribution, size, density, &mut rng);
+            let mut row = |backend, format, bytes| {
+                rows.push(Row {
+                    distribution,
+                    size,
+                    backend,
+                    format,
+                    bytes,
+                })
+            };
+
+            with_croaring! {
+                let mut bm = croaring::Bitmap::of(&values);
+                for backend in ["croaring", "croaring_rle"] {
+                    if backend == "croaring_rle" {
+                        bm.run_optimize();
+                    }
+                    row(backend, "portable", bm.get_serialized_size_in_bytes());
+                    row(backend, "native", native_size(&bm));
+                    row(backend, "frozen", bm.get_frozen_serialized_size_in_bytes());
+                }
+            }
+
+            with_roaring! {
+                let bm = roaring::RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap();
+                row("roaring", "portable", bm.serialized_size());
+            }
+        }
+    }
+
+    if csv {
+        println!("distribution,size,backend,format,serialized_bytes");
+        for r in &rows {
+            println!(
+                "{},{},{},{},{}",
+                r.distribution, r.size, r.backend, r.format, r.bytes,
+            );
+        }
+        return;
+    }
+
+    println!("| distribution | size | backend | format | serialized bytes | bytes/value | ratio |");
+    println!("|---|---:|---|---|---:|---:|---:|");
+    for r in &rows {
+        let per_value = r.bytes as f64 / r.size.max(1) as f64;
+        println!(
+            "| {} | {} | {} | {} | {} | {:.2} | {:.3} |",
+            r.distribution,
+            r.size,
+            r.backend,
+            r.format,
+            r.bytes,
+            per_value,
+            per_value / 4.0,
+        );
+    }
+}
+
+/// The size of `bm` in croaring's native format, which the safe bindings do
+/// not expose.
+#[cfg(feature = "croaring")]
+fn native_size(bm: &croaring::Bitmap) -> usize {
+    // SAFETY: croaring::Bitmap is a #[repr(transparent)] wrapper around
+    // roaring_bitmap_t (see roaring_bench::cow), and the call only reads it.
+    unsafe {
+        croaring_sys::roaring_bitmap_size_in_bytes(
+            (bm as *const croaring::Bitmap).cast::<croaring_sys::roaring_bitmap_t>(),
+        )
+    }
+}
+
+fn parse<T: std::str::FromStr>(v: &str) -> T {
+    v.parse().unwrap_or_else(|_| {
+        eprintln!("invalid number {v}");
+        process::exit(2);
+    })
+}
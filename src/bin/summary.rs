@@ -0,0 +1,176 @@
+//! Summarise the latest criterion results as one table per benchmark group,
+//! with a column per backend and the fastest of each row highlighted.
+//!
+//! Differences within the measured confidence intervals are reported as "no
+//! significant difference" rather than as a misleadingly precise ratio.
+//!
+//! ```text
+//! cargo run --bin summary -- [--normalize] [--equivalence <percent>] [[label=]criterion dir ...]
+//! ```
+//!
+//! Results from several directories (for example, gathered on different
+//! machines) are merged into one report, with each backend column prefixed
+//! by the directory's label. `--normalize` reports every result in the
+//! calibration units of its own run (see [`roaring_bench::normalize`]),
+//! making results from machines of different speeds comparable.
+//!
+//! `--equivalence <percent>` additionally reports backends as equivalent
+//! when a TOST equivalence test shows their times differ by less than the
+//! given percentage of the fastest.
+//!
+//! Highlighting uses bold text when stdout is a terminal (and `NO_COLOR` is
+//! unset), or a trailing `*` otherwise.
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env,
+    io::{self, IsTerminal},
+    process,
+};
+
+use roaring_bench::{
+    estimates::{self, Measurement},
+    export::format_nanos,
+    normalize, summary,
+};
+
+fn main() {
+    let mut normalize = false;
+    let mut equivalence = None;
+    let mut dirs = Vec::new();
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--normalize" => normalize = true,
+            "--equivalence" => {
+                let v = args.next().and_then(|v| v.parse::<f64>().ok());
+                match v {
+                    Some(percent) if percent > 0.0 => equivalence = Some(percent / 100.0),
+                    _ => {
+                        eprintln!("--equivalence requires a positive percentage");
+                        process::exit(1);
+                    }
+                }
+            }
+            _ => dirs.push(arg),
+        }
+    }
+    if dirs.is_empty() {
+        dirs.push(estimates::DEFAULT_DIR.to_string());
+    }
+    let prefix = dirs.len() > 1;
+
+    let mut measurements = Vec::new();
+    for arg in &dirs {
+        let (label, dir) = arg.split_once('=').unwrap_or((arg, arg));
+        let mut run = estimates::load(dir).unwrap_or_else(|e| {
+            eprintln!("failed to load criterion results from {dir}: {e}");
+            process::exit(1);
+        });
+        if normalize {
+            run = normalize::normalize(&run).unwrap_or_else(|| {
+                eprintln!(
+                    "{dir} has no {} and {} results to normalize by",
+                    normalize::NULL_BASELINE_GROUP,
+                    normalize::CALIBRATION_GROUP
+                );
+                process::exit(1);
+            });
+        }
+        if prefix {
+            for m in &mut run {
+                m.function = format!("{label}/{}", m.function);
+            }
+        }
+        measurements.extend(run);
+    }
+    if measurements.is_empty() {
+        eprintln!("no criterion results found in {}", dirs.join(", "));
+        process::exit(1);
+    }
+    let format = if normalize {
+        |v: f64| format!("{v:.3} cal")
+    } else {
+        format_nanos
+    };
+
+    let bold = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let verdicts = summary::compare(&measurements, equivalence)
+        .into_iter()
+        .map(|c| ((c.group, c.value), c.verdict))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut groups: BTreeMap<&str, Vec<&Measurement>> = BTreeMap::new();
+    for m in &measurements {
+        groups.entry(&m.group).or_default().push(m);
+    }
+
+    for (group, ms) in groups {
+        let functions = ms
+            .iter()
+            .map(|m| m.function.as_str())
+            .collect::<BTreeSet<_>>();
+
+        // Order rows numerically where the values are sizes.
+        let mut rows: BTreeMap<_, BTreeMap<&str, f64>> = BTreeMap::new();
+        for m in &ms {
+            let value = m.value.as_deref();
+            let key = (value.and_then(|v| v.parse::<f64>().ok()).map(OrdF64), value);
+            rows.entry(key)
+                .or_default()
+                .insert(&m.function, m.typical().point);
+        }
+
+        println!("{group}");
+        print!("  {:>10}", "value");
+        for f in &functions {
+            print!(" {f:>14}");
+        }
+        println!("  verdict");
+
+        for ((_, value), times) in rows {
+            let best = times
+                .values()
+                .copied()
+                .min_by(f64::total_cmp)
+                .unwrap_or_default();
+            print!("  {:>10}", value.unwrap_or("-"));
+            for f in &functions {
+                match times.get(f) {
+                    Some(&ns) if ns == best && times.len() > 1 && bold => {
+                        print!(" \x1b[1m{:>14}\x1b[0m", format(ns))
+                    }
+                    Some(&ns) if ns == best && times.len() > 1 => {
+                        print!(" {:>14}", format(ns) + "*")
+                    }
+                    Some(&ns) => print!(" {:>14}", format(ns)),
+                    None => print!(" {:>14}", "-"),
+                }
+            }
+            let verdict = verdicts.get(&(group.to_string(), value.map(str::to_string)));
+            match verdict {
+                Some(v) => println!("  {v}"),
+                None => println!(),
+            }
+        }
+        println!();
+    }
+}
+
+/// A totally ordered f64, for use as a map key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct OrdF64(f64);
+
+impl Eq for OrdF64 {}
+
+impl PartialOrd for OrdF64 {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OrdF64 {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
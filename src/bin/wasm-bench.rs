@@ -0,0 +1,11 @@
+//! Print the WebAssembly micro-benchmarks of `roaring_bench::wasm` as the
+//! CSV of `roaring-bench export`, run under wasmtime or natively to compare
+//! the two.
+//!
+//! ```text
+//! cargo run --release --bin wasm-bench --no-default-features --features wasm > native.csv
+//! ```
+
+fn main() {
+    print!("{}", roaring_bench::wasm::run_csv());
+}
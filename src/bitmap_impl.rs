@@ -0,0 +1,474 @@
+//! A library-agnostic interface to the bitmaps under test, so a benchmark
+//! group can be written once, generically, and run against every library.
+//!
+//! Each enabled library has an adapter implementing [`BitmapImpl`] with the
+//! library's own method for each operation, and [`for_each_impl!`] calls a
+//! generic function once per enabled adapter. A new library takes part in
+//! the generic groups with an adapter here and a line in that macro.
+//!
+//! Every adapter method is `#[inline]`, so a routine written against the
+//! trait compiles to the same calls as one written against the library.
+//! Groups measuring an API only one library has (run containers, frozen
+//! views, copy-on-write and so on) are written against it directly.
+//!
+//! [`for_each_impl!`]: crate::for_each_impl
+
+use std::ops::Range;
+#[cfg(feature = "croaring")]
+use std::{marker::PhantomData, mem::MaybeUninit};
+
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
+
+use crate::pool::Pooled;
+
+/// The operations of a bitmap library shared by the generic benchmark
+/// groups.
+pub trait BitmapImpl: Clone + PartialEq {
+    /// The participant name of the library in benchmark ids.
+    const NAME: &'static str;
+
+    /// Whether the library has run containers, so that
+    /// [`BitmapImpl::pooled_rle`] differs from [`BitmapImpl::pooled`].
+    const RUN_CONTAINERS: bool;
+
+    /// The iterator returned by [`BitmapImpl::iter`].
+    type Iter<'a>: Iterator<Item = u32>
+    where
+        Self: 'a;
+
+    /// The iterator returned by [`BitmapImpl::iter_rev`].
+    type RevIter<'a>: Iterator<Item = u32>
+    where
+        Self: 'a;
+
+    /// An empty bitmap.
+    fn new() -> Self;
+
+    /// A bitmap of `values`, which are sorted.
+    fn from_sorted(values: &[u32]) -> Self;
+
+    /// The bitmap of `pooled`.
+    fn pooled(pooled: &Pooled) -> &Self;
+
+    /// The bitmap of `pooled` after run-length optimisation, or the same as
+    /// [`BitmapImpl::pooled`] for a library without run containers.
+    fn pooled_rle(pooled: &Pooled) -> &Self;
+
+    fn insert(&mut self, v: u32);
+
+    fn insert_range(&mut self, range: Range<u32>);
+
+    fn remove(&mut self, v: u32);
+
+    fn remove_range(&mut self, range: Range<u32>);
+
+    /// Remove every value.
+    fn clear(&mut self);
+
+    fn contains(&self, v: u32) -> bool;
+
+    fn cardinality(&self) -> u64;
+
+    /// The number of values less than or equal to `v`.
+    fn rank(&self, v: u32) -> u64;
+
+    /// The value of rank `n` (from 0), if there are more than `n` values.
+    fn select(&self, n: u32) -> Option<u32>;
+
+    fn union(&self, other: &Self) -> Self;
+
+    fn intersect(&self, other: &Self) -> Self;
+
+    fn difference(&self, other: &Self) -> Self;
+
+    fn symmetric_difference(&self, other: &Self) -> Self;
+
+    /// [`BitmapImpl::intersect`], consuming both operands so that the
+    /// library may reuse the allocation of one for the result.
+    fn intersect_owned(self, other: Self) -> Self;
+
+    /// [`BitmapImpl::difference`], consuming both operands.
+    fn difference_owned(self, other: Self) -> Self;
+
+    /// [`BitmapImpl::symmetric_difference`], consuming both operands.
+    fn symmetric_difference_owned(self, other: Self) -> Self;
+
+    /// Returns true if every value of `self` is in `other`.
+    fn is_subset(&self, other: &Self) -> bool;
+
+    /// Returns true if every value of `other` is in `self`.
+    fn is_superset(&self, other: &Self) -> bool;
+
+    /// Returns true if no value is in both `self` and `other`.
+    fn is_disjoint(&self, other: &Self) -> bool;
+
+    /// The length of [`BitmapImpl::serialize`].
+    fn serialized_size(&self) -> usize;
+
+    /// The library's serialization of the portable format.
+    fn serialize(&self) -> Vec<u8>;
+
+    /// # Panics
+    ///
+    /// Panics if `bytes` are not a valid serialization.
+    fn deserialize(bytes: &[u8]) -> Self;
+
+    /// The values in ascending order.
+    fn iter(&self) -> Self::Iter<'_>;
+
+    /// The values in descending order.
+    fn iter_rev(&self) -> Self::RevIter<'_>;
+}
+
+#[cfg(feature = "croaring")]
+impl BitmapImpl for croaring::Bitmap {
+    const NAME: &'static str = "croaring";
+    const RUN_CONTAINERS: bool = true;
+
+    type Iter<'a> = croaring::bitmap::BitmapIterator<'a>;
+    type RevIter<'a> = CroaringRevIter<'a>;
+
+    #[inline]
+    fn new() -> Self {
+        croaring::Bitmap::create()
+    }
+
+    #[inline]
+    fn from_sorted(values: &[u32]) -> Self {
+        croaring::Bitmap::of(values)
+    }
+
+    #[inline]
+    fn pooled(pooled: &Pooled) -> &Self {
+        pooled.croaring()
+    }
+
+    #[inline]
+    fn pooled_rle(pooled: &Pooled) -> &Self {
+        pooled.croaring_rle()
+    }
+
+    #[inline]
+    fn insert(&mut self, v: u32) {
+        self.add(v);
+    }
+
+    #[inline]
+    fn insert_range(&mut self, range: Range<u32>) {
+        self.add_range(range);
+    }
+
+    #[inline]
+    fn remove(&mut self, v: u32) {
+        croaring::Bitmap::remove(self, v);
+    }
+
+    #[inline]
+    fn remove_range(&mut self, range: Range<u32>) {
+        croaring::Bitmap::remove_range(self, range);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        croaring::Bitmap::clear(self);
+    }
+
+    #[inline]
+    fn contains(&self, v: u32) -> bool {
+        croaring::Bitmap::contains(self, v)
+    }
+
+    #[inline]
+    fn cardinality(&self) -> u64 {
+        croaring::Bitmap::cardinality(self)
+    }
+
+    #[inline]
+    fn rank(&self, v: u32) -> u64 {
+        croaring::Bitmap::rank(self, v)
+    }
+
+    #[inline]
+    fn select(&self, n: u32) -> Option<u32> {
+        croaring::Bitmap::select(self, n)
+    }
+
+    #[inline]
+    fn union(&self, other: &Self) -> Self {
+        self.or(other)
+    }
+
+    #[inline]
+    fn intersect(&self, other: &Self) -> Self {
+        self.and(other)
+    }
+
+    #[inline]
+    fn difference(&self, other: &Self) -> Self {
+        self.andnot(other)
+    }
+
+    #[inline]
+    fn symmetric_difference(&self, other: &Self) -> Self {
+        self.xor(other)
+    }
+
+    #[inline]
+    fn intersect_owned(self, other: Self) -> Self {
+        self & other
+    }
+
+    #[inline]
+    fn difference_owned(self, other: Self) -> Self {
+        self - other
+    }
+
+    #[inline]
+    fn symmetric_difference_owned(self, other: Self) -> Self {
+        self ^ other
+    }
+
+    #[inline]
+    fn is_subset(&self, other: &Self) -> bool {
+        croaring::Bitmap::is_subset(self, other)
+    }
+
+    /// croaring has no superset check, so this is
+    /// [`BitmapImpl::is_subset`] with the operands swapped.
+    #[inline]
+    fn is_superset(&self, other: &Self) -> bool {
+        croaring::Bitmap::is_subset(other, self)
+    }
+
+    /// croaring has no disjointness check, so this is the negation of its
+    /// `intersect`, which stops at the first shared value.
+    #[inline]
+    fn is_disjoint(&self, other: &Self) -> bool {
+        !croaring::Bitmap::intersect(self, other)
+    }
+
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        self.get_serialized_size_in_bytes()
+    }
+
+    #[inline]
+    fn serialize(&self) -> Vec<u8> {
+        croaring::Bitmap::serialize(self)
+    }
+
+    #[inline]
+    fn deserialize(bytes: &[u8]) -> Self {
+        croaring::Bitmap::try_deserialize(bytes).unwrap()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        croaring::Bitmap::iter(self)
+    }
+
+    #[inline]
+    fn iter_rev(&self) -> Self::RevIter<'_> {
+        CroaringRevIter::new(self)
+    }
+}
+
+/// The values of a croaring bitmap in descending order.
+///
+/// croaring's Rust iterator only runs forwards, so this drives CRoaring's
+/// own reverse iterator.
+#[cfg(feature = "croaring")]
+pub struct CroaringRevIter<'a> {
+    iter: croaring_sys::roaring_uint32_iterator_t,
+    bitmap: PhantomData<&'a croaring::Bitmap>,
+}
+
+#[cfg(feature = "croaring")]
+impl<'a> CroaringRevIter<'a> {
+    fn new(bitmap: &'a croaring::Bitmap) -> Self {
+        let raw = (bitmap as *const croaring::Bitmap).cast::<croaring_sys::roaring_bitmap_t>();
+        let mut iter = MaybeUninit::uninit();
+        // SAFETY: croaring::Bitmap is a #[repr(transparent)] wrapper around
+        // roaring_bitmap_t, and the iterator is initialised before it is
+        // read. It holds no pointers into itself, so may be moved.
+        let iter = unsafe {
+            croaring_sys::roaring_init_iterator_last(raw, iter.as_mut_ptr());
+            iter.assume_init()
+        };
+        Self {
+            iter,
+            bitmap: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "croaring")]
+impl Iterator for CroaringRevIter<'_> {
+    type Item = u32;
+
+    #[inline]
+    fn next(&mut self) -> Option<u32> {
+        if !self.iter.has_value {
+            return None;
+        }
+        let v = self.iter.current_value;
+        // SAFETY: the iterator was initialised by new, and the bitmap it
+        // points into is borrowed for the lifetime of self.
+        unsafe {
+            croaring_sys::roaring_previous_uint32_iterator(&mut self.iter);
+        }
+        Some(v)
+    }
+}
+
+#[cfg(feature = "roaring")]
+impl BitmapImpl for RoaringBitmap {
+    const NAME: &'static str = "roaring";
+    const RUN_CONTAINERS: bool = false;
+
+    type Iter<'a> = roaring::bitmap::Iter<'a>;
+    type RevIter<'a> = std::iter::Rev<roaring::bitmap::Iter<'a>>;
+
+    #[inline]
+    fn new() -> Self {
+        RoaringBitmap::new()
+    }
+
+    #[inline]
+    fn from_sorted(values: &[u32]) -> Self {
+        RoaringBitmap::from_sorted_iter(values.iter().copied()).unwrap()
+    }
+
+    #[inline]
+    fn pooled(pooled: &Pooled) -> &Self {
+        pooled.roaring()
+    }
+
+    #[inline]
+    fn pooled_rle(pooled: &Pooled) -> &Self {
+        pooled.roaring()
+    }
+
+    #[inline]
+    fn insert(&mut self, v: u32) {
+        RoaringBitmap::insert(self, v);
+    }
+
+    #[inline]
+    fn insert_range(&mut self, range: Range<u32>) {
+        RoaringBitmap::insert_range(self, range);
+    }
+
+    #[inline]
+    fn remove(&mut self, v: u32) {
+        RoaringBitmap::remove(self, v);
+    }
+
+    #[inline]
+    fn remove_range(&mut self, range: Range<u32>) {
+        RoaringBitmap::remove_range(self, range);
+    }
+
+    #[inline]
+    fn clear(&mut self) {
+        RoaringBitmap::clear(self);
+    }
+
+    #[inline]
+    fn contains(&self, v: u32) -> bool {
+        RoaringBitmap::contains(self, v)
+    }
+
+    #[inline]
+    fn cardinality(&self) -> u64 {
+        self.len()
+    }
+
+    #[inline]
+    fn rank(&self, v: u32) -> u64 {
+        RoaringBitmap::rank(self, v)
+    }
+
+    #[inline]
+    fn select(&self, n: u32) -> Option<u32> {
+        RoaringBitmap::select(self, n)
+    }
+
+    #[inline]
+    fn union(&self, other: &Self) -> Self {
+        self | other
+    }
+
+    #[inline]
+    fn intersect(&self, other: &Self) -> Self {
+        self & other
+    }
+
+    #[inline]
+    fn difference(&self, other: &Self) -> Self {
+        self - other
+    }
+
+    #[inline]
+    fn symmetric_difference(&self, other: &Self) -> Self {
+        self ^ other
+    }
+
+    #[inline]
+    fn intersect_owned(self, other: Self) -> Self {
+        self & other
+    }
+
+    #[inline]
+    fn difference_owned(self, other: Self) -> Self {
+        self - other
+    }
+
+    #[inline]
+    fn symmetric_difference_owned(self, other: Self) -> Self {
+        self ^ other
+    }
+
+    #[inline]
+    fn is_subset(&self, other: &Self) -> bool {
+        RoaringBitmap::is_subset(self, other)
+    }
+
+    #[inline]
+    fn is_superset(&self, other: &Self) -> bool {
+        RoaringBitmap::is_superset(self, other)
+    }
+
+    #[inline]
+    fn is_disjoint(&self, other: &Self) -> bool {
+        RoaringBitmap::is_disjoint(self, other)
+    }
+
+    #[inline]
+    fn serialized_size(&self) -> usize {
+        RoaringBitmap::serialized_size(self)
+    }
+
+    #[inline]
+    fn serialize(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(RoaringBitmap::serialized_size(self));
+        self.serialize_into(&mut buf).unwrap();
+        buf
+    }
+
+    #[inline]
+    fn deserialize(bytes: &[u8]) -> Self {
+        RoaringBitmap::deserialize_from(bytes).unwrap()
+    }
+
+    #[inline]
+    fn iter(&self) -> Self::Iter<'_> {
+        RoaringBitmap::iter(self)
+    }
+
+    #[inline]
+    fn iter_rev(&self) -> Self::RevIter<'_> {
+        RoaringBitmap::iter(self).rev()
+    }
+}
@@ -0,0 +1,117 @@
+//! A minimal uncompressed bitset, used as a dense-workload baseline.
+//!
+//! This stands in for `fixedbitset::FixedBitSet` and `bitvec`, which share
+//! its layout: one bit per value of the universe in a flat `Vec<u64>`.
+
+use std::{
+    iter::FromIterator,
+    ops::{BitAndAssign, BitOrAssign, BitXorAssign, SubAssign},
+};
+
+/// A set of `u32` values stored as one bit per value below the largest
+/// inserted, growing as needed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FlatBitSet {
+    words: Vec<u64>,
+}
+
+impl FlatBitSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An empty set with room for the values `0..bits` without growing.
+    pub fn with_capacity(bits: u32) -> Self {
+        Self {
+            words: Vec::with_capacity((bits as usize).div_ceil(64)),
+        }
+    }
+
+    pub fn insert(&mut self, value: u32) {
+        let word = value as usize / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+        self.words[word] |= 1 << (value % 64);
+    }
+
+    pub fn contains(&self, value: u32) -> bool {
+        self.words
+            .get(value as usize / 64)
+            .is_some_and(|w| w & (1 << (value % 64)) != 0)
+    }
+
+    /// The number of values in the set.
+    pub fn len(&self) -> u64 {
+        self.words.iter().map(|w| u64::from(w.count_ones())).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    /// The values of the set, in ascending order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.words.iter().enumerate().flat_map(|(i, &w)| {
+            let base = i as u32 * 64;
+            let mut w = w;
+            std::iter::from_fn(move || {
+                if w == 0 {
+                    return None;
+                }
+                let bit = w.trailing_zeros();
+                w &= w - 1;
+                Some(base + bit)
+            })
+        })
+    }
+}
+
+impl FromIterator<u32> for FlatBitSet {
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for v in iter {
+            set.insert(v);
+        }
+        set
+    }
+}
+
+impl BitOrAssign<&FlatBitSet> for FlatBitSet {
+    fn bitor_assign(&mut self, rhs: &FlatBitSet) {
+        if rhs.words.len() > self.words.len() {
+            self.words.resize(rhs.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a |= b;
+        }
+    }
+}
+
+impl BitAndAssign<&FlatBitSet> for FlatBitSet {
+    fn bitand_assign(&mut self, rhs: &FlatBitSet) {
+        self.words.truncate(rhs.words.len());
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a &= b;
+        }
+    }
+}
+
+impl SubAssign<&FlatBitSet> for FlatBitSet {
+    fn sub_assign(&mut self, rhs: &FlatBitSet) {
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a &= !b;
+        }
+    }
+}
+
+impl BitXorAssign<&FlatBitSet> for FlatBitSet {
+    fn bitxor_assign(&mut self, rhs: &FlatBitSet) {
+        if rhs.words.len() > self.words.len() {
+            self.words.resize(rhs.words.len(), 0);
+        }
+        for (a, b) in self.words.iter_mut().zip(&rhs.words) {
+            *a ^= b;
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! A minimal Bloom filter, used as an approximate-membership baseline.
+
+use std::iter::FromIterator;
+
+/// A Bloom filter over `u32` values using double hashing.
+#[derive(Debug, Clone)]
+pub struct BloomFilter {
+    bits: Vec<u64>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    /// Size a filter for `expected` values at the target `false_positive_rate`.
+    pub fn with_rate(expected: usize, false_positive_rate: f64) -> Self {
+        let n = expected.max(1) as f64;
+        let ln2 = std::f64::consts::LN_2;
+
+        let num_bits = (-n * false_positive_rate.ln() / (ln2 * ln2))
+            .ceil()
+            .max(64.0) as u64;
+        let num_hashes = ((num_bits as f64 / n) * ln2).round().max(1.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(64) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, value: u32) {
+        for bit in self.bit_indexes(value) {
+            self.bits[(bit / 64) as usize] |= 1 << (bit % 64);
+        }
+    }
+
+    /// Returns true if `value` may be in the set, and false if it is
+    /// definitely not.
+    pub fn contains(&self, value: u32) -> bool {
+        self.bit_indexes(value)
+            .all(|bit| self.bits[(bit / 64) as usize] & (1 << (bit % 64)) != 0)
+    }
+
+    fn bit_indexes(&self, value: u32) -> impl Iterator<Item = u64> {
+        let h = mix(value as u64);
+        let (h1, h2) = (h & 0xFFFF_FFFF, (h >> 32) | 1);
+        let num_bits = self.num_bits;
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+}
+
+impl FromIterator<u32> for BloomFilter {
+    /// Build a filter with a 1% false positive rate.
+    fn from_iter<I: IntoIterator<Item = u32>>(iter: I) -> Self {
+        let values = iter.into_iter().collect::<Vec<_>>();
+        let mut filter = Self::with_rate(values.len(), 0.01);
+        for v in values {
+            filter.insert(v);
+        }
+        filter
+    }
+}
+
+/// The splitmix64 finaliser.
+fn mix(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,99 @@
+//! Cold-cache variants of benchmark groups.
+//!
+//! Criterion runs a routine in a tight loop, so after the first iteration
+//! its bitmaps are served from the CPU caches, hiding the cache misses that
+//! dominate when they are one of many in a large heap. Set [`COLD_ENV`] (or
+//! pass [`COLD_FLAG`] to the benchmark binary) to add a `{group}_cold` copy
+//! of the groups that support it, in which every iteration is preceded by
+//! writing to each cache line of an [`EVICT_BYTES`] buffer, evicting the
+//! bitmaps (and leaving the evicted lines dirty, as a busy heap would).
+//!
+//! The eviction runs as criterion setup, outside of the measurement, but
+//! every iteration is then timed individually, so a cold case also includes
+//! the cost of reading the clock.
+
+use std::{
+    env,
+    hint::black_box,
+    sync::{Mutex, OnceLock},
+};
+
+use criterion::{measurement::Measurement, BatchSize, Bencher};
+
+/// The environment variable adding the cold variants when set to anything
+/// other than `0`.
+pub const COLD_ENV: &str = "ROARING_BENCH_COLD";
+
+/// The command line flag equivalent of [`COLD_ENV`], see
+/// [`split_cold_flag`].
+pub const COLD_FLAG: &str = "--cold";
+
+/// The size of the buffer written between iterations, larger than the last
+/// level cache of most machines.
+pub const EVICT_BYTES: usize = 64 << 20;
+
+/// The assumed size of a cache line.
+const LINE_BYTES: usize = 64;
+
+static BUFFER: OnceLock<Mutex<Vec<u8>>> = OnceLock::new();
+
+/// Returns true if [`COLD_ENV`] is set to anything other than `0`.
+pub fn cold_enabled() -> bool {
+    env::var(COLD_ENV).is_ok_and(|v| v.trim() != "0")
+}
+
+/// Remove [`COLD_FLAG`] from `args`, returning the remaining arguments, or
+/// `None` if it is not present.
+pub fn split_cold_flag(args: &[String]) -> Option<Vec<String>> {
+    if !args.iter().any(|a| a == COLD_FLAG) {
+        return None;
+    }
+    Some(args.iter().filter(|a| *a != COLD_FLAG).cloned().collect())
+}
+
+/// Whether to run a group hot and, if [`cold_enabled`], cold.
+pub fn variants() -> &'static [bool] {
+    if cold_enabled() {
+        &[false, true]
+    } else {
+        &[false]
+    }
+}
+
+/// `group`, with the `_cold` suffix if `cold`.
+pub fn group_name(group: String, cold: bool) -> String {
+    if cold {
+        format!("{group}_cold")
+    } else {
+        group
+    }
+}
+
+/// Allocate the eviction buffer, so that it is not counted against the
+/// first cold case.
+pub fn prepare() {
+    buffer();
+}
+
+/// Write to every cache line of the eviction buffer.
+pub fn evict() {
+    let mut buf = buffer().lock().unwrap();
+    for line in buf.chunks_mut(LINE_BYTES) {
+        line[0] = line[0].wrapping_add(1);
+    }
+    black_box(&mut *buf);
+}
+
+/// Time `routine` with `b`, evicting the caches before each iteration if
+/// `cold`.
+pub fn iter<M: Measurement, O>(b: &mut Bencher<'_, M>, cold: bool, mut routine: impl FnMut() -> O) {
+    if cold {
+        b.iter_batched(evict, |()| routine(), BatchSize::PerIteration);
+    } else {
+        b.iter(routine);
+    }
+}
+
+fn buffer() -> &'static Mutex<Vec<u8>> {
+    BUFFER.get_or_init(|| Mutex::new(vec![1; EVICT_BYTES]))
+}
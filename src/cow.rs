@@ -0,0 +1,32 @@
+//! Access to croaring's copy-on-write mode, which the safe bindings do not
+//! expose.
+
+use std::mem;
+
+/// From `roaring.h`.
+const ROARING_FLAG_COW: u8 = 0x1;
+
+// The cast below relies on Bitmap wrapping nothing but the C struct, which is
+// the same assumption croaring's own `BitmapView` deref makes.
+const _: () =
+    assert!(mem::size_of::<croaring::Bitmap>() == mem::size_of::<croaring_sys::roaring_bitmap_t>());
+
+/// Enable or disable copy-on-write for `bitmap`, equivalent to CRoaring's
+/// `roaring_bitmap_set_copy_on_write()`.
+///
+/// With copy-on-write enabled, cloning a bitmap shares its containers with
+/// the clone, and a container is only copied when either side first mutates
+/// it. CRoaring requires every bitmap interacting with a COW bitmap to also
+/// be COW, and the container reference counts are not atomic - COW bitmaps
+/// sharing containers must not be used from multiple threads.
+pub fn set_copy_on_write(bitmap: &mut croaring::Bitmap, enabled: bool) {
+    // SAFETY: croaring::Bitmap is a #[repr(transparent)] wrapper around
+    // roaring_bitmap_t, checked above to have the same size.
+    let raw =
+        unsafe { &mut *(bitmap as *mut croaring::Bitmap).cast::<croaring_sys::roaring_bitmap_t>() };
+    if enabled {
+        raw.high_low_container.flags |= ROARING_FLAG_COW;
+    } else {
+        raw.high_low_container.flags &= !ROARING_FLAG_COW;
+    }
+}
@@ -0,0 +1,150 @@
+//! Locating where the ranking of two backends flips, across set size and
+//! density.
+
+use crate::{run_case, Backend, Operation, SuiteConfig};
+
+/// The dimension a crossover search sweeps over.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Sweep {
+    /// Vary the set size at a fixed density.
+    Size { density: f64 },
+
+    /// Vary the density at a fixed set size.
+    Density { size: u32 },
+}
+
+/// The point along a [`Sweep`] at which `above` overtakes `below`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Crossover {
+    pub operation: Operation,
+    pub sweep: Sweep,
+
+    /// The faster backend at the low end of the searched range.
+    pub below: Backend,
+
+    /// The faster backend at the high end of the searched range.
+    pub above: Backend,
+
+    /// The first size (or density) at which `above` was measured as faster.
+    pub at: f64,
+}
+
+/// Bisect over `lo..=hi` to find where the faster of backends `a` and `b`
+/// changes for `operation`, measuring each probed point with the iteration
+/// and sample counts of `config`.
+///
+/// Points are probed geometrically, and the search stops once the bracketing
+/// points are within 5% of each other (or, sweeping sizes, which `lo` and
+/// `hi` are rounded to, once they are adjacent). Returns `None` if the same
+/// backend wins at both ends of the range (which includes the case of an
+/// even number of flips within it).
+pub fn find_crossover(
+    config: &SuiteConfig,
+    operation: Operation,
+    a: Backend,
+    b: Backend,
+    sweep: Sweep,
+    lo: f64,
+    hi: f64,
+) -> Option<Crossover> {
+    let winner = |at: f64| {
+        let (size, density) = match sweep {
+            Sweep::Size { density } => (at.round() as u32, density),
+            Sweep::Density { size } => (size, at),
+        };
+        compare(config, operation, a, b, size, density).0
+    };
+
+    // Sizes are discrete, so stop once the bracket is adjacent integers,
+    // leaving no size strictly between them to probe.
+    let sizes = matches!(sweep, Sweep::Size { .. });
+    let done = |lo: f64, hi: f64| hi / lo <= 1.05 || sizes && hi - lo < 2.0;
+
+    let (lo, hi) = if sizes {
+        (lo.round(), hi.round())
+    } else {
+        (lo, hi)
+    };
+    let (mut lo, mut hi) = (lo.max(f64::MIN_POSITIVE), hi);
+    let below = winner(lo);
+    let above = winner(hi);
+    if below == above {
+        return None;
+    }
+
+    while !done(lo, hi) {
+        let mut mid = (lo * hi).sqrt();
+        if sizes {
+            mid = mid.round().clamp(lo + 1.0, hi - 1.0);
+        }
+        if winner(mid) == below {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+
+    Some(Crossover {
+        operation,
+        sweep,
+        below,
+        above,
+        at: hi,
+    })
+}
+
+/// One cell of a [`heatmap`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeatmapCell {
+    pub size: u32,
+    pub density: f64,
+    pub winner: Backend,
+
+    /// How many times faster the winner was than the other backend.
+    pub ratio: f64,
+}
+
+/// Measure `a` against `b` at every (size, density) combination, producing
+/// a "which backend wins" dataset suitable for plotting as a decision chart.
+pub fn heatmap(
+    config: &SuiteConfig,
+    operation: Operation,
+    a: Backend,
+    b: Backend,
+) -> Vec<HeatmapCell> {
+    let mut cells = Vec::new();
+    for &density in &config.densities {
+        for &size in &config.sizes {
+            let (winner, ratio) = compare(config, operation, a, b, size, density);
+            cells.push(HeatmapCell {
+                size,
+                density,
+                winner,
+                ratio,
+            });
+        }
+    }
+    cells
+}
+
+/// Return the faster of `a` and `b` for the case, and the ratio by which it
+/// was faster.
+fn compare(
+    config: &SuiteConfig,
+    operation: Operation,
+    a: Backend,
+    b: Backend,
+    size: u32,
+    density: f64,
+) -> (Backend, f64) {
+    let time_a = run_case(config, operation, a, size, density, None).per_iteration;
+    let time_b = run_case(config, operation, b, size, density, None).per_iteration;
+    let ratio = |fast: std::time::Duration, slow: std::time::Duration| {
+        slow.as_secs_f64() / fast.as_secs_f64().max(f64::MIN_POSITIVE)
+    };
+    if time_a <= time_b {
+        (a, ratio(time_a, time_b))
+    } else {
+        (b, ratio(time_b, time_a))
+    }
+}
@@ -0,0 +1,110 @@
+//! Loading of the real-world corpora published by the CRoaring project in
+//! [real-roaring-datasets].
+//!
+//! Each dataset is a directory of text files, each file holding the
+//! comma-separated values of one bitmap. The corpora are not downloaded by
+//! the suite; clone the repository (and unzip the datasets of interest) and
+//! point [`DIR_ENV`] at it.
+//!
+//! [real-roaring-datasets]: https://github.com/RoaringBitmap/real-roaring-datasets
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable holding the directory the datasets are loaded
+/// from.
+pub const DIR_ENV: &str = "ROARING_BENCH_DATASETS";
+
+/// The datasets of the corpus, loaded when present under [`DIR_ENV`].
+pub const NAMES: &[&str] = &[
+    "census-income",
+    "census-income_srt",
+    "census1881",
+    "census1881_srt",
+    "dimension_003",
+    "dimension_008",
+    "dimension_033",
+    "uscensus2000",
+    "weather_sept_85",
+    "weather_sept_85_srt",
+    "wikileaks-noquotes",
+    "wikileaks-noquotes_srt",
+];
+
+/// The bitmaps of one dataset, in file name order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dataset {
+    pub name: String,
+
+    /// The values of each bitmap, ascending and without duplicates.
+    pub bitmaps: Vec<Vec<u32>>,
+}
+
+impl Dataset {
+    /// The total number of values across all bitmaps.
+    pub fn len(&self) -> usize {
+        self.bitmaps.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// The dataset directory set by [`DIR_ENV`], if any.
+pub fn dir_from_env() -> Option<PathBuf> {
+    env::var_os(DIR_ENV).map(PathBuf::from)
+}
+
+/// Load every dataset of [`NAMES`] found under `dir`, skipping those that
+/// are absent.
+pub fn load_all(dir: impl AsRef<Path>) -> io::Result<Vec<Dataset>> {
+    let dir = dir.as_ref();
+    let mut out = Vec::new();
+    for name in NAMES {
+        if dir.join(name).is_dir() {
+            out.push(load(dir, name)?);
+        }
+    }
+    Ok(out)
+}
+
+/// Load the dataset `name` from its directory under `dir`.
+pub fn load(dir: impl AsRef<Path>, name: &str) -> io::Result<Dataset> {
+    let mut files = fs::read_dir(dir.as_ref().join(name))?
+        .map(|e| e.map(|e| e.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    files.retain(|p| p.extension().is_some_and(|e| e == "txt"));
+    files.sort();
+
+    let bitmaps = files
+        .iter()
+        .map(|p| parse(&fs::read_to_string(p)?).map_err(|e| with_path(p, e)))
+        .collect::<io::Result<Vec<_>>>()?;
+    Ok(Dataset {
+        name: name.to_string(),
+        bitmaps,
+    })
+}
+
+/// Parse the comma-separated values of one bitmap.
+fn parse(s: &str) -> io::Result<Vec<u32>> {
+    let mut values = s
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| {
+            v.parse::<u32>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{v:?}: {e}")))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    values.sort_unstable();
+    values.dedup();
+    Ok(values)
+}
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {e}", path.display()))
+}
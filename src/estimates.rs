@@ -0,0 +1,287 @@
+//! Loading of the results criterion writes under `target/criterion`.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+/// The default location of criterion's output, relative to the crate root.
+pub const DEFAULT_DIR: &str = "target/criterion";
+
+/// The file within a benchmark's directory holding the fingerprint of its
+/// input, see [`crate::harness::Fingerprint`].
+pub const DATASET_FILE: &str = "dataset";
+
+/// The length, in bytes, criterion truncates each directory name of a
+/// benchmark id to.
+const MAX_DIRECTORY_NAME_LEN: usize = 64;
+
+/// A point estimate with its confidence interval, in nanoseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(from = "RawEstimate")]
+pub struct Estimate {
+    pub point: f64,
+    pub lower: f64,
+    pub upper: f64,
+}
+
+impl Estimate {
+    /// Returns true if the confidence intervals of `self` and `other`
+    /// overlap.
+    pub fn overlaps(&self, other: &Estimate) -> bool {
+        self.lower <= other.upper && other.lower <= self.upper
+    }
+}
+
+/// The results of the most recent run of a single benchmark.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    /// The criterion benchmark group, i.e. the operation.
+    pub group: String,
+
+    /// The function within the group, i.e. the backend.
+    pub function: String,
+
+    /// The input parameter, i.e. the size.
+    pub value: Option<String>,
+
+    pub mean: Estimate,
+    pub median: Estimate,
+
+    /// Only present when criterion used linear sampling.
+    pub slope: Option<Estimate>,
+
+    /// The fingerprint of the benchmark's input, if it recorded one.
+    pub dataset: Option<String>,
+
+    /// The mean time of one iteration in each of criterion's samples, in
+    /// ns. Empty if the raw samples were not found.
+    pub samples: Vec<f64>,
+}
+
+impl Measurement {
+    /// The estimate criterion itself reports as "time": the slope when
+    /// available, otherwise the mean.
+    pub fn typical(&self) -> &Estimate {
+        self.slope.as_ref().unwrap_or(&self.mean)
+    }
+}
+
+/// Load the latest results of every benchmark found under `dir`.
+pub fn load(dir: impl AsRef<Path>) -> io::Result<Vec<Measurement>> {
+    let mut out = Vec::new();
+    for path in find_runs(dir.as_ref())? {
+        let benchmark: Benchmark = read_json(&path.join("benchmark.json"))?;
+        let estimates: Estimates = read_json(&path.join("estimates.json"))?;
+        out.push(Measurement {
+            group: benchmark.group_id,
+            function: benchmark.function_id.unwrap_or_default(),
+            value: benchmark.value_str,
+            mean: estimates.mean,
+            median: estimates.median,
+            slope: estimates.slope,
+            dataset: read_dataset(path.parent().unwrap_or(&path)),
+            samples: read_samples(&path),
+        });
+    }
+    out.sort_by(|a, b| (&a.group, &a.function, &a.value).cmp(&(&b.group, &b.function, &b.value)));
+    Ok(out)
+}
+
+/// Load the latest result of a single benchmark under `dir`, returning
+/// `None` if it has not been run.
+pub fn load_case(
+    dir: impl AsRef<Path>,
+    group: &str,
+    function: &str,
+    value: Option<&str>,
+) -> io::Result<Option<Measurement>> {
+    let case = case_dir(dir, group, function, value);
+    let path = case.join("new");
+    if !path.join("estimates.json").is_file() {
+        return Ok(None);
+    }
+    let estimates: Estimates = read_json(&path.join("estimates.json"))?;
+    Ok(Some(Measurement {
+        group: group.to_string(),
+        function: function.to_string(),
+        value: value.map(str::to_string),
+        mean: estimates.mean,
+        median: estimates.median,
+        slope: estimates.slope,
+        dataset: read_dataset(&case),
+        samples: read_samples(&path),
+    }))
+}
+
+/// The directory criterion writes the results of a single benchmark to.
+pub fn case_dir(
+    dir: impl AsRef<Path>,
+    group: &str,
+    function: &str,
+    value: Option<&str>,
+) -> PathBuf {
+    let mut path = dir.as_ref().join(filename_safe(group));
+    path.push(filename_safe(function));
+    if let Some(v) = value {
+        path.push(filename_safe(v));
+    }
+    path
+}
+
+fn read_samples(run: &Path) -> Vec<f64> {
+    match read_json::<Samples>(&run.join("sample.json")) {
+        Ok(s) => s
+            .iters
+            .iter()
+            .zip(&s.times)
+            .map(|(iters, time)| time / iters)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+fn read_dataset(case: &Path) -> Option<String> {
+    let s = fs::read_to_string(case.join(DATASET_FILE)).ok()?;
+    Some(s.trim().to_string())
+}
+
+/// The directory name criterion uses for an ID component, as computed by
+/// its `make_filename_safe`: reserved characters replaced, truncated to
+/// [`MAX_DIRECTORY_NAME_LEN`] bytes and, on Windows, trimmed and lowercased.
+fn filename_safe(s: &str) -> String {
+    let mut s = s.replace(
+        &['?', '"', '/', '\\', '*', '<', '>', ':', '|', '^'][..],
+        "_",
+    );
+    let mut len = s.len().min(MAX_DIRECTORY_NAME_LEN);
+    while !s.is_char_boundary(len) {
+        len -= 1;
+    }
+    s.truncate(len);
+    if cfg!(target_os = "windows") {
+        s = s.trim_end().to_lowercase();
+    }
+    s
+}
+
+/// Recursively find the `new` directories holding the latest run of each
+/// benchmark.
+fn find_runs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut runs = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if !path.is_dir() || path.ends_with("report") {
+            continue;
+        }
+        if path.ends_with("new") {
+            if path.join("estimates.json").is_file() {
+                runs.push(path);
+            }
+            continue;
+        }
+        runs.extend(find_runs(&path)?);
+    }
+    Ok(runs)
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T> {
+    let data = fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+#[derive(Deserialize)]
+struct Benchmark {
+    group_id: String,
+    function_id: Option<String>,
+    value_str: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Estimates {
+    mean: Estimate,
+    median: Estimate,
+    slope: Option<Estimate>,
+}
+
+#[derive(Deserialize)]
+struct Samples {
+    iters: Vec<f64>,
+    times: Vec<f64>,
+}
+
+#[derive(Deserialize)]
+struct RawEstimate {
+    confidence_interval: ConfidenceInterval,
+    point_estimate: f64,
+}
+
+#[derive(Deserialize)]
+struct ConfidenceInterval {
+    lower_bound: f64,
+    upper_bound: f64,
+}
+
+impl From<RawEstimate> for Estimate {
+    fn from(v: RawEstimate) -> Self {
+        Self {
+            point: v.point_estimate,
+            lower: v.confidence_interval.lower_bound,
+            upper: v.confidence_interval.upper_bound,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filename_safe_truncates_like_criterion() {
+        assert_eq!(filename_safe("union/a:b"), "union_a_b");
+
+        let long = "x".repeat(100);
+        assert_eq!(filename_safe(&long), "x".repeat(64));
+
+        // A multi-byte character straddling the limit is dropped whole.
+        let straddling = format!("{}é", "x".repeat(63));
+        assert_eq!(filename_safe(&straddling), "x".repeat(63));
+    }
+
+    #[test]
+    fn load_case_finds_long_ids() {
+        let dir =
+            std::env::temp_dir().join(format!("roaring-bench-estimates-{}", std::process::id()));
+        let group = format!("intersection_{}", "very_long_operation_name_".repeat(4));
+        let function = "roaring";
+        let value = "1000";
+
+        let run = dir
+            .join(&group[..64])
+            .join(function)
+            .join(value)
+            .join("new");
+        fs::create_dir_all(&run).unwrap();
+        let estimate =
+            r#"{"confidence_interval":{"lower_bound":1.0,"upper_bound":3.0},"point_estimate":2.0}"#;
+        fs::write(
+            run.join("estimates.json"),
+            format!(r#"{{"mean":{estimate},"median":{estimate},"slope":null}}"#),
+        )
+        .unwrap();
+
+        let found = load_case(&dir, &group, function, Some(value)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        let found = found.expect("the truncated directory is found");
+        assert_eq!(found.group, group);
+        assert_eq!(found.median.point, 2.0);
+    }
+}
@@ -0,0 +1,232 @@
+//! Flattening of criterion results into a single table, for export as CSV
+//! or JSON and for a croaring against roaring comparison.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{estimates::Measurement, gen::Distribution};
+
+/// The median time of one benchmark, in nanoseconds.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Row {
+    /// The benchmark group with any distribution suffix removed.
+    pub operation: String,
+
+    /// The distribution named by the suffix of the group, if any.
+    pub distribution: Option<Distribution>,
+    pub backend: String,
+
+    /// The input parameter, i.e. the size.
+    pub n: Option<String>,
+    pub median_ns: f64,
+    pub median_lower_ns: f64,
+    pub median_upper_ns: f64,
+    pub mean_ns: f64,
+}
+
+/// One row per measurement, ordered by (operation, distribution, n,
+/// backend).
+pub fn rows(measurements: &[Measurement]) -> Vec<Row> {
+    let mut rows = measurements
+        .iter()
+        .map(|m| {
+            let (operation, distribution) = split_distribution(&m.group);
+            Row {
+                operation: operation.to_string(),
+                distribution,
+                backend: m.function.clone(),
+                n: m.value.clone(),
+                median_ns: m.median.point,
+                median_lower_ns: m.median.lower,
+                median_upper_ns: m.median.upper,
+                mean_ns: m.mean.point,
+            }
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|a, b| {
+        (&a.operation, a.distribution, n_key(&a.n), &a.backend)
+            .partial_cmp(&(&b.operation, b.distribution, n_key(&b.n), &b.backend))
+            .expect("sizes are not NaN")
+    });
+    rows
+}
+
+/// Render `rows` as CSV, with a header line.
+pub fn csv(rows: &[Row]) -> String {
+    let mut out = String::from(
+        "operation,distribution,backend,n,median_ns,median_lower_ns,median_upper_ns,mean_ns\n",
+    );
+    for r in rows {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            r.operation,
+            r.distribution.map(|d| d.name()).unwrap_or_default(),
+            r.backend,
+            r.n.as_deref().unwrap_or_default(),
+            r.median_ns,
+            r.median_lower_ns,
+            r.median_upper_ns,
+            r.mean_ns,
+        )
+        .expect("writing to a string");
+    }
+    out
+}
+
+/// The croaring and roaring medians of one (operation, n, distribution).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Speedup {
+    pub operation: String,
+    pub distribution: Option<Distribution>,
+    pub n: Option<String>,
+    pub croaring_ns: f64,
+    pub roaring_ns: f64,
+}
+
+impl Speedup {
+    /// The croaring median over the roaring median, so values above 1 mean
+    /// roaring is faster.
+    pub fn ratio(&self) -> f64 {
+        self.croaring_ns / self.roaring_ns.max(f64::MIN_POSITIVE)
+    }
+}
+
+/// Pair the croaring and roaring rows of each case measured by both, in the
+/// order of `rows`.
+pub fn speedups(rows: &[Row]) -> Vec<Speedup> {
+    let mut cases: BTreeMap<_, (Option<f64>, Option<f64>)> = BTreeMap::new();
+    let mut order = Vec::new();
+    for r in rows {
+        let key = (&r.operation, r.distribution, &r.n);
+        let entry = cases.entry(key).or_insert_with(|| {
+            order.push(key);
+            (None, None)
+        });
+        match r.backend.as_str() {
+            "croaring" => entry.0 = Some(r.median_ns),
+            "roaring" => entry.1 = Some(r.median_ns),
+            _ => {}
+        }
+    }
+    order
+        .into_iter()
+        .filter_map(|key| match cases[&key] {
+            (Some(croaring_ns), Some(roaring_ns)) => Some(Speedup {
+                operation: key.0.clone(),
+                distribution: key.1,
+                n: key.2.clone(),
+                croaring_ns,
+                roaring_ns,
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Render `speedups` as a markdown table.
+pub fn markdown(speedups: &[Speedup]) -> String {
+    let mut out = String::from(
+        "| operation | distribution | n | croaring median | roaring median | roaring speedup |\n\
+         |---|---|---:|---:|---:|---:|\n",
+    );
+    for s in speedups {
+        writeln!(
+            out,
+            "| {} | {} | {} | {} | {} | {:.2}x |",
+            s.operation,
+            s.distribution.map(|d| d.name()).unwrap_or("-"),
+            s.n.as_deref().unwrap_or("-"),
+            format_nanos(s.croaring_ns),
+            format_nanos(s.roaring_ns),
+            s.ratio(),
+        )
+        .expect("writing to a string");
+    }
+    out
+}
+
+/// Split the distribution suffix the distribution-parameterised groups
+/// append to their operation name from `group`.
+fn split_distribution(group: &str) -> (&str, Option<Distribution>) {
+    Distribution::ALL
+        .iter()
+        .find_map(|&d| {
+            let operation = group.strip_suffix(d.name())?.strip_suffix('_')?;
+            Some((operation, Some(d)))
+        })
+        .unwrap_or((group, None))
+}
+
+/// Orders sizes numerically, and anything else after them.
+pub(crate) fn n_key(n: &Option<String>) -> (f64, Option<&str>) {
+    let v = n.as_deref();
+    (v.and_then(|v| v.parse().ok()).unwrap_or(f64::INFINITY), v)
+}
+
+/// Format a duration in nanoseconds with a unit suited to its magnitude.
+pub fn format_nanos(ns: f64) -> String {
+    match ns {
+        ns if ns < 1e3 => format!("{ns:.1} ns"),
+        ns if ns < 1e6 => format!("{:.2} µs", ns / 1e3),
+        ns if ns < 1e9 => format!("{:.2} ms", ns / 1e6),
+        ns => format!("{:.2} s", ns / 1e9),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_distribution_suffixes() {
+        assert_eq!(
+            split_distribution("union_no_rle_overlap_50"),
+            ("union_no_rle_overlap_50", None)
+        );
+        assert_eq!(
+            split_distribution("add_elements_run_heavy"),
+            ("add_elements", Some(Distribution::RunHeavy))
+        );
+        assert_eq!(
+            split_distribution("contains_uniform_sparse"),
+            ("contains", Some(Distribution::UniformSparse))
+        );
+        // A distribution name alone is not a suffix of an operation.
+        assert_eq!(split_distribution("clustered"), ("clustered", None));
+        assert_eq!(split_distribution("addclustered"), ("addclustered", None));
+    }
+
+    #[test]
+    fn n_key_orders_sizes_numerically_then_others() {
+        let mut ns = ["1000", "full", "10", "100000", "near_full", "2"]
+            .iter()
+            .map(|n| Some(n.to_string()))
+            .chain([None])
+            .collect::<Vec<_>>();
+        ns.sort_by(|a, b| n_key(a).partial_cmp(&n_key(b)).unwrap());
+
+        let ns = ns.iter().map(|n| n.as_deref()).collect::<Vec<_>>();
+        assert_eq!(
+            ns,
+            [
+                Some("2"),
+                Some("10"),
+                Some("1000"),
+                Some("100000"),
+                None,
+                Some("full"),
+                Some("near_full"),
+            ]
+        );
+    }
+
+    #[test]
+    fn format_nanos_units() {
+        assert_eq!(format_nanos(12.34), "12.3 ns");
+        assert_eq!(format_nanos(1_500.0), "1.50 µs");
+        assert_eq!(format_nanos(2_345_678.0), "2.35 ms");
+        assert_eq!(format_nanos(3e9), "3.00 s");
+    }
+}
@@ -0,0 +1,106 @@
+//! A minimal length-prefixed framing layer, modelling the message size limit
+//! of RPC transports such as gRPC.
+
+use std::io::{self, Read, Write};
+
+/// The default maximum payload size of a single frame.
+pub const DEFAULT_FRAME_SIZE: usize = 16 * 1024;
+
+/// A [`Write`] adaptor splitting the byte stream into frames of at most
+/// `frame_size` bytes, each prefixed with its payload length as a
+/// little-endian `u32`.
+///
+/// Bytes are buffered until a full frame is available - call
+/// [`FrameWriter::finish`] to emit any trailing partial frame.
+#[derive(Debug)]
+pub struct FrameWriter<W> {
+    inner: W,
+    frame_size: usize,
+    buf: Vec<u8>,
+}
+
+impl<W: Write> FrameWriter<W> {
+    pub fn new(inner: W, frame_size: usize) -> Self {
+        assert!(frame_size > 0, "frame size must be non-zero");
+        Self {
+            inner,
+            frame_size,
+            buf: Vec::with_capacity(frame_size),
+        }
+    }
+
+    /// Write out any buffered bytes as a final frame, returning the inner
+    /// writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.emit()?;
+        Ok(self.inner)
+    }
+
+    fn emit(&mut self) -> io::Result<()> {
+        if self.buf.is_empty() {
+            return Ok(());
+        }
+        self.inner
+            .write_all(&(self.buf.len() as u32).to_le_bytes())?;
+        self.inner.write_all(&self.buf)?;
+        self.buf.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for FrameWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let n = data.len().min(self.frame_size - self.buf.len());
+        self.buf.extend_from_slice(&data[..n]);
+        if self.buf.len() == self.frame_size {
+            self.emit()?;
+        }
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A [`Read`] adaptor over a stream produced by [`FrameWriter`], yielding the
+/// concatenated frame payloads.
+///
+/// Each read returns bytes from at most one frame, as a transport delivering
+/// one message at a time would.
+#[derive(Debug)]
+pub struct FrameReader<R> {
+    inner: R,
+
+    /// The payload bytes of the current frame not yet read.
+    remaining: usize,
+}
+
+impl<R: Read> FrameReader<R> {
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            remaining: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for FrameReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 {
+            let mut header = [0; 4];
+            match self.inner.read_exact(&mut header) {
+                Ok(()) => self.remaining = u32::from_le_bytes(header) as usize,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(0),
+                Err(e) => return Err(e),
+            }
+        }
+        let len = out.len().min(self.remaining);
+        let n = self.inner.read(&mut out[..len])?;
+        if n == 0 && len > 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        self.remaining -= n;
+        Ok(n)
+    }
+}
@@ -0,0 +1,259 @@
+//! Generation of benchmark input following configurable value
+//! distributions.
+//!
+//! Contiguous inputs such as `0..size` are dense and hit the run container
+//! fast paths, which real data rarely does. Each [`Distribution`] instead
+//! places `size` distinct values within a universe of `size / density`.
+//!
+//! All random input is drawn from [`rng`], seeded from [`SEED_ENV`], so
+//! that every run (and every machine) benchmarks the same data.
+
+use std::{collections::HashSet, env, fmt, str::FromStr, sync::OnceLock};
+
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::{harness::Fingerprint, zipf::Zipf};
+
+/// The environment variable holding the seed random input is generated
+/// from.
+pub const SEED_ENV: &str = "ROARING_BENCH_SEED";
+
+/// The seed used when [`SEED_ENV`] is not set.
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+/// The density used where a benchmark does not choose one.
+pub const DEFAULT_DENSITY: f64 = 0.1;
+
+/// The number of values in each cluster of [`Distribution::Clustered`].
+const CLUSTER_SIZE: u32 = 256;
+
+/// The span of the universe each cluster of [`Distribution::Clustered`]
+/// draws its values from.
+const CLUSTER_SPAN: u32 = 1024;
+
+/// The exponent of the container popularity of [`Distribution::Zipfian`].
+const ZIPF_EXPONENT: f64 = 1.0;
+
+/// The longest run of [`Distribution::RunHeavy`].
+const MAX_RUN: u32 = 256;
+
+/// The strides of [`stride`] benchmarked, chosen by the container type
+/// each leaves: every other value fills bitmap containers, every 16th value
+/// exactly fills array containers (4096 values each), and the wider strides
+/// leave increasingly sparse array containers. No stride forms a run.
+pub const STRIDES: [u32; 4] = [2, 16, 64, 1000];
+
+/// The universe [`stride`] input is spread over, 64 containers wide.
+pub const STRIDE_UNIVERSE: u32 = 1 << 22;
+
+/// The seed set by [`SEED_ENV`], or [`DEFAULT_SEED`].
+///
+/// # Panics
+///
+/// Panics if the variable is set to something other than an integer.
+pub fn seed() -> u64 {
+    static SEED: OnceLock<u64> = OnceLock::new();
+    *SEED.get_or_init(|| match env::var(SEED_ENV) {
+        Ok(v) => v
+            .parse()
+            .unwrap_or_else(|_| panic!("invalid {} value {:?}", SEED_ENV, v)),
+        Err(_) => DEFAULT_SEED,
+    })
+}
+
+/// A random number generator for the input of `stream`, derived from
+/// [`seed`].
+///
+/// Each stream (typically the name of the benchmark function using it) is
+/// independent of the others, so the input of one group does not depend
+/// on which groups were set up before it.
+pub fn rng(stream: &str) -> StdRng {
+    let bytes = stream.bytes().map(u32::from).collect::<Vec<_>>();
+    StdRng::seed_from_u64(seed() ^ Fingerprint::of(&bytes).as_u64())
+}
+
+/// How values are laid out within the universe.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Distribution {
+    /// Values drawn uniformly from the whole universe.
+    UniformSparse,
+
+    /// Values drawn uniformly from within a fixed span around randomly
+    /// placed cluster centres.
+    Clustered,
+
+    /// Values whose 16-bit container key follows a zipfian popularity, so a
+    /// few containers are dense and most are sparse.
+    Zipfian,
+
+    /// Runs of up to 256 consecutive values separated by random gaps.
+    RunHeavy,
+}
+
+impl Distribution {
+    pub const ALL: [Distribution; 4] = [
+        Distribution::UniformSparse,
+        Distribution::Clustered,
+        Distribution::Zipfian,
+        Distribution::RunHeavy,
+    ];
+
+    /// The name used for this distribution in benchmark group names.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Distribution::UniformSparse => "uniform_sparse",
+            Distribution::Clustered => "clustered",
+            Distribution::Zipfian => "zipfian",
+            Distribution::RunHeavy => "run_heavy",
+        }
+    }
+}
+
+impl fmt::Display for Distribution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Distribution {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|d| d.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown distribution {s:?}"))
+    }
+}
+
+/// The size of the universe holding `size` values at `density`, capped at
+/// `u32::MAX` and never smaller than `size`.
+pub fn universe(size: u32, density: f64) -> u32 {
+    let universe = size as f64 / density.clamp(f64::MIN_POSITIVE, 1.0);
+    (universe.min(u32::MAX as f64) as u32).max(size)
+}
+
+/// Generate `size` distinct values following `distribution` within
+/// [`universe`]`(size, density)`, in ascending order.
+pub fn generate<R: Rng + ?Sized>(
+    distribution: Distribution,
+    size: u32,
+    density: f64,
+    rng: &mut R,
+) -> Vec<u32> {
+    let universe = universe(size, density);
+    if universe == size {
+        return (0..size).collect();
+    }
+
+    match distribution {
+        Distribution::UniformSparse => fill(size, universe, rng, |rng| rng.gen_range(0..universe)),
+        Distribution::Clustered => {
+            let span = CLUSTER_SPAN.min(universe);
+            let centres = (0..size.div_ceil(CLUSTER_SIZE))
+                .map(|_| rng.gen_range(0..=universe - span))
+                .collect::<Vec<u32>>();
+            fill(size, universe, rng, |rng| {
+                centres[rng.gen_range(0..centres.len())] + rng.gen_range(0..span)
+            })
+        }
+        Distribution::Zipfian => {
+            let containers = (universe as u64).div_ceil(1 << 16) as usize;
+            let popularity = Zipf::new(containers, ZIPF_EXPONENT);
+            fill(size, universe, rng, |rng| {
+                let key = popularity.sample(rng) as u32;
+                (key << 16 | rng.gen::<u16>() as u32).min(universe - 1)
+            })
+        }
+        Distribution::RunHeavy => {
+            let mut runs = Vec::new();
+            let mut total = 0;
+            while total < size {
+                let run = rng.gen_range(1..=MAX_RUN).min(size - total);
+                runs.push(run);
+                total += run;
+            }
+
+            // Share the space not covered by runs out between the gaps
+            // preceding each of them.
+            let free = (universe - size) as f64;
+            let weights = runs.iter().map(|_| rng.gen::<f64>()).collect::<Vec<_>>();
+            let sum = weights.iter().sum::<f64>().max(f64::MIN_POSITIVE);
+            let mut values = Vec::with_capacity(size as usize);
+            let mut next = 0;
+            for (run, weight) in runs.into_iter().zip(weights) {
+                // Rounding must not push the remaining runs past the end.
+                let remaining = size - values.len() as u32;
+                next = (next + (free * weight / sum) as u32).min(universe - remaining);
+                values.extend(next..next + run);
+                next += run;
+            }
+            values
+        }
+    }
+}
+
+/// Draw from `draw` until `size` distinct values are held, falling back to
+/// uniform draws over the universe once skewed draws stop finding new
+/// values.
+fn fill<R: Rng + ?Sized>(
+    size: u32,
+    universe: u32,
+    rng: &mut R,
+    mut draw: impl FnMut(&mut R) -> u32,
+) -> Vec<u32> {
+    let mut values = HashSet::with_capacity(size as usize);
+    let mut attempts = 0usize;
+    while values.len() < size as usize {
+        let v = if attempts < 16 * size as usize {
+            draw(rng)
+        } else {
+            rng.gen_range(0..universe)
+        };
+        values.insert(v);
+        attempts += 1;
+    }
+    let mut values = values.into_iter().collect::<Vec<_>>();
+    values.sort_unstable();
+    values
+}
+
+/// Every `k`th value of `0..universe`, starting from zero.
+///
+/// Unlike the [`Distribution`]s, every container of the result holds the
+/// same number of values, so each stride leaves a predictable container
+/// type (see [`STRIDES`]).
+pub fn stride(k: u32, universe: u32) -> Vec<u32> {
+    (0..universe).step_by(k.max(1) as usize).collect()
+}
+
+/// The most values an array container holds before it is converted to a
+/// bitset.
+pub const ARRAY_MAX: u32 = 4_096;
+
+/// The start of each of the first `containers` 2^16 blocks, so that every
+/// value is alone in its container.
+pub fn singletons(containers: u32) -> Vec<u32> {
+    (0..containers.min(1 << 16)).map(|k| k << 16).collect()
+}
+
+/// The first `containers` 2^16 blocks each holding [`ARRAY_MAX`] - 1
+/// values (every 16th value of the block but its last), and for each block
+/// the pair of values taking it past [`ARRAY_MAX`].
+///
+/// Inserting a pair converts its array container to a bitset, and removing
+/// it again converts it back.
+pub fn threshold(containers: u32) -> (Vec<u32>, Vec<[u32; 2]>) {
+    let blocks = 0..containers.min(1 << 16);
+    let values = blocks
+        .clone()
+        .flat_map(|k| (0..ARRAY_MAX - 1).map(move |i| (k << 16) | (i * 16)))
+        .collect();
+    let pairs = blocks
+        .map(|k| [(k << 16) | ((ARRAY_MAX - 1) * 16), (k << 16) | 1])
+        .collect();
+    (values, pairs)
+}
@@ -0,0 +1,573 @@
+//! A thin wrapper around criterion's [`BenchmarkGroup`] that knows the name
+//! of each case it runs, so the suite can apply per-case policy (such as the
+//! time budget) before handing the case to criterion.
+
+use std::{
+    env, fmt, fs,
+    sync::{Mutex, OnceLock},
+    time::Duration,
+};
+
+use criterion::{
+    measurement::{Measurement, WallTime},
+    Bencher, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+
+use crate::{allocator, estimates};
+
+/// The environment variable holding the per-case time budget, in seconds.
+pub const BUDGET_ENV: &str = "ROARING_BENCH_BUDGET";
+
+/// The environment variable holding the comma-separated tags selecting
+/// which groups run, see [`Group::tags`].
+pub const TAGS_ENV: &str = "ROARING_BENCH_TAGS";
+
+/// The command line flag equivalent of [`TAGS_ENV`], see [`split_tags_flag`].
+pub const TAGS_FLAG: &str = "--tags";
+
+/// The environment variable holding the peak-memory budgets, see
+/// [`memory_budgets_from_env`].
+pub const MEM_BUDGET_ENV: &str = "ROARING_BENCH_MEM_BUDGET";
+
+/// The command line flag selecting the dry-run mode of [`plan`].
+pub const LIST_CASES_FLAG: &str = "--list-cases";
+
+// Criterion's defaults, used to project the runtime of a case.
+const WARM_UP_TIME: Duration = Duration::from_secs(3);
+const MEASUREMENT_TIME: Duration = Duration::from_secs(5);
+const SAMPLE_SIZE: usize = 100;
+
+/// Read the per-case time budget from [`BUDGET_ENV`], if set.
+///
+/// # Panics
+///
+/// Panics if the variable is set to something other than a positive number.
+pub fn budget_from_env() -> Option<Duration> {
+    let v = env::var(BUDGET_ENV).ok()?;
+    match v.parse::<f64>() {
+        Ok(secs) if secs > 0.0 => Some(Duration::from_secs_f64(secs)),
+        _ => panic!("invalid {} value {:?}", BUDGET_ENV, v),
+    }
+}
+
+/// Returns true if [`LIST_CASES_FLAG`] was passed on the command line.
+pub fn list_cases_requested() -> bool {
+    env::args().any(|a| a == LIST_CASES_FLAG)
+}
+
+/// The tags selected by [`TAGS_ENV`], if set.
+pub fn tags_from_env() -> Option<Vec<String>> {
+    let v = env::var(TAGS_ENV).ok()?;
+    Some(
+        v.split(',')
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .map(str::to_string)
+            .collect(),
+    )
+}
+
+/// Remove [`TAGS_FLAG`] (as `--tags a,b` or `--tags=a,b`) from `args`,
+/// returning its value and the remaining arguments, or `None` if it is not
+/// present.
+///
+/// Criterion rejects unknown flags, so the benchmark binary uses this to
+/// move the tags into [`TAGS_ENV`] before handing the rest to criterion.
+pub fn split_tags_flag(args: &[String]) -> Option<(String, Vec<String>)> {
+    let prefix = format!("{TAGS_FLAG}=");
+    let mut rest = Vec::with_capacity(args.len());
+    let mut tags = None;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        if arg == TAGS_FLAG {
+            tags = Some(iter.next().cloned().unwrap_or_default());
+        } else if let Some(v) = arg.strip_prefix(&prefix) {
+            tags = Some(v.to_string());
+        } else {
+            rest.push(arg.clone());
+        }
+    }
+    tags.map(|t| (t, rest))
+}
+
+/// A peak-memory limit for the cases whose `group/function/parameter` name
+/// starts with `prefix`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryBudget {
+    pub prefix: String,
+    pub bytes: usize,
+}
+
+/// Parse the memory budgets from [`MEM_BUDGET_ENV`], a comma-separated
+/// list of `prefix=MiB` pairs, such as `inverted_index/roaring=512`.
+///
+/// When several prefixes match a case, the longest applies.
+///
+/// # Panics
+///
+/// Panics if the variable is set but malformed.
+pub fn memory_budgets_from_env() -> Vec<MemoryBudget> {
+    let v = match env::var(MEM_BUDGET_ENV) {
+        Ok(v) => v,
+        Err(_) => return Vec::new(),
+    };
+    v.split(',')
+        .filter(|s| !s.trim().is_empty())
+        .map(|pair| {
+            let parsed = pair
+                .split_once('=')
+                .and_then(|(prefix, mib)| Some((prefix.trim(), mib.trim().parse::<f64>().ok()?)));
+            match parsed {
+                Some((prefix, mib)) if mib >= 0.0 => MemoryBudget {
+                    prefix: prefix.to_string(),
+                    bytes: (mib * 1024.0 * 1024.0) as usize,
+                },
+                _ => panic!("invalid {} entry {:?}", MEM_BUDGET_ENV, pair),
+            }
+        })
+        .collect()
+}
+
+/// A case whose peak memory exceeded its [`MemoryBudget`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryViolation {
+    pub case: String,
+    pub peak: usize,
+    pub budget: usize,
+}
+
+static VIOLATIONS: Mutex<Vec<MemoryViolation>> = Mutex::new(Vec::new());
+
+/// The memory budget violations recorded so far.
+pub fn memory_violations() -> Vec<MemoryViolation> {
+    VIOLATIONS.lock().unwrap().clone()
+}
+
+/// A case that would be run, as recorded by [`plan`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedCase {
+    pub group: String,
+    pub id: Id,
+
+    /// The projected runtime of the case.
+    pub estimate: Duration,
+
+    /// True if `estimate` was projected from a previous result, rather than
+    /// assuming criterion's default warm-up and measurement times.
+    pub from_history: bool,
+
+    /// The input fingerprint recorded for the case.
+    pub dataset: Option<Fingerprint>,
+
+    /// The tags of the case's group.
+    pub tags: Vec<&'static str>,
+
+    /// True if the case would be skipped by the time budget.
+    pub over_budget: bool,
+}
+
+/// The cases recorded while [`plan`] is running.
+struct Listing {
+    filter: Option<String>,
+    cases: Vec<PlannedCase>,
+}
+
+static LISTING: Mutex<Option<Listing>> = Mutex::new(None);
+
+/// Call `f`, with every [`Group`] recording the cases it would run (whose ID
+/// contains `filter`, if given) instead of running them, and return the
+/// recorded cases.
+///
+/// The groups' input generation still runs, as it happens before the cases
+/// are declared.
+pub fn plan(filter: Option<&str>, f: impl FnOnce()) -> Vec<PlannedCase> {
+    *LISTING.lock().unwrap() = Some(Listing {
+        filter: filter.map(str::to_string),
+        cases: Vec::new(),
+    });
+    f();
+    LISTING
+        .lock()
+        .unwrap()
+        .take()
+        .map(|l| l.cases)
+        .unwrap_or_default()
+}
+
+/// Returns true while [`plan`] is recording cases, when the informational
+/// output of the groups' setup would be mixed into the plan.
+pub fn is_listing() -> bool {
+    LISTING.lock().unwrap().is_some()
+}
+
+/// The identity of a case within a [`Group`], mirroring [`BenchmarkId`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Id {
+    pub function: String,
+    pub parameter: Option<String>,
+}
+
+impl Id {
+    pub fn new(function: impl Into<String>, parameter: impl fmt::Display) -> Self {
+        Self {
+            function: function.into(),
+            parameter: Some(parameter.to_string()),
+        }
+    }
+}
+
+impl From<&str> for Id {
+    fn from(function: &str) -> Self {
+        Self {
+            function: function.to_string(),
+            parameter: None,
+        }
+    }
+}
+
+impl From<String> for Id {
+    fn from(function: String) -> Self {
+        Self {
+            function,
+            parameter: None,
+        }
+    }
+}
+
+impl fmt::Display for Id {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.parameter {
+            Some(p) => write!(f, "{}/{p}", self.function),
+            None => f.write_str(&self.function),
+        }
+    }
+}
+
+/// A short, stable hash of a benchmark's generated input.
+///
+/// Recorded alongside each case's results, so results produced from
+/// different inputs (a different seed or distribution) are recognisable as
+/// different workloads even when their benchmark IDs match.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    /// The fingerprint of `values`.
+    pub fn of(values: &[u32]) -> Self {
+        let mut f = Self::default();
+        f.update(values);
+        f
+    }
+
+    /// The fingerprint of an input made of several parts.
+    pub fn of_all<'a, T>(parts: impl IntoIterator<Item = &'a T>) -> Self
+    where
+        T: AsRef<[u32]> + 'a,
+    {
+        let mut f = Self::default();
+        for part in parts {
+            f.update(part.as_ref());
+        }
+        f
+    }
+
+    /// Mix `values` into the fingerprint, for inputs made of several parts.
+    pub fn update(&mut self, values: &[u32]) -> &mut Self {
+        for v in values {
+            for byte in v.to_le_bytes() {
+                self.0 = (self.0 ^ byte as u64).wrapping_mul(Self::FNV_PRIME);
+            }
+        }
+        self
+    }
+
+    /// The full 64-bit hash, of which [`Display`](fmt::Display) shows a
+    /// folded 32 bits.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl Default for Fingerprint {
+    fn default() -> Self {
+        Self(Self::FNV_OFFSET)
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:08x}", (self.0 >> 32) as u32 ^ self.0 as u32)
+    }
+}
+
+/// A criterion benchmark group that applies the suite's per-case policy.
+///
+/// Cases whose runtime, projected from the previous run's results, exceeds
+/// the time budget from [`budget_from_env`] are skipped and reported as
+/// such. Cases without a previous result always run.
+///
+/// When tags are selected with [`TAGS_ENV`], only the cases of groups with
+/// at least one of the selected tags run, and the rest are skipped silently
+/// (as with criterion's name filter).
+///
+/// Cases with a [`MemoryBudget`] have the growth in heap usage while they
+/// run (excluding their pre-built input) measured with the tracking
+/// allocator, and record a [`MemoryViolation`] if it exceeds the budget.
+///
+/// With the `alloc-stats` feature, the bytes allocated per iteration of each
+/// case are printed and saved alongside its results, see
+/// [`crate::alloc_stats`].
+///
+/// The [`Fingerprint`] set with [`Group::dataset`] is written next to the
+/// results of every subsequent case. A warning is printed when it differs
+/// from that of the previous run, as criterion's change report would then be
+/// comparing different workloads.
+pub struct Group<'a, M: Measurement = WallTime> {
+    inner: BenchmarkGroup<'a, M>,
+    name: String,
+    sample_size: usize,
+    budget: Option<Duration>,
+    dataset: Option<Fingerprint>,
+    tags: Vec<&'static str>,
+}
+
+impl<'a, M: Measurement> Group<'a, M> {
+    pub fn new(c: &'a mut Criterion<M>, name: impl Into<String>) -> Self {
+        let name = name.into();
+        Self {
+            inner: c.benchmark_group(name.clone()),
+            name,
+            sample_size: SAMPLE_SIZE,
+            budget: budget_from_env(),
+            dataset: None,
+            tags: Vec::new(),
+        }
+    }
+
+    /// Tag the cases of this group, for selection with [`TAGS_ENV`].
+    pub fn tags(&mut self, tags: &[&'static str]) -> &mut Self {
+        self.tags.extend_from_slice(tags);
+        self
+    }
+
+    fn selected(&self) -> bool {
+        static SELECTED: OnceLock<Option<Vec<String>>> = OnceLock::new();
+        match SELECTED.get_or_init(tags_from_env) {
+            Some(selected) => self.tags.iter().any(|t| selected.iter().any(|s| s == t)),
+            None => true,
+        }
+    }
+
+    /// Record `fingerprint` as the input of the cases that follow.
+    pub fn dataset(&mut self, fingerprint: Fingerprint) -> &mut Self {
+        self.dataset = Some(fingerprint);
+        self
+    }
+
+    pub fn throughput(&mut self, throughput: Throughput) -> &mut Self {
+        self.inner.throughput(throughput);
+        self
+    }
+
+    pub fn sample_size(&mut self, n: usize) -> &mut Self {
+        self.inner.sample_size(n);
+        self.sample_size = n;
+        self
+    }
+
+    pub fn bench_function<F>(&mut self, id: impl Into<Id>, mut f: F) -> &mut Self
+    where
+        F: FnMut(&mut Bencher<'_, M>),
+    {
+        self.bench_with_input(id, &(), |b, _| f(b))
+    }
+
+    pub fn bench_with_input<I: ?Sized, F>(
+        &mut self,
+        id: impl Into<Id>,
+        input: &I,
+        f: F,
+    ) -> &mut Self
+    where
+        F: FnMut(&mut Bencher<'_, M>, &I),
+    {
+        let id = id.into();
+        if !self.selected() {
+            return self;
+        }
+        if let Some(listing) = LISTING.lock().unwrap().as_mut() {
+            let name = format!("{}/{id}", self.name);
+            if listing
+                .filter
+                .as_ref()
+                .is_none_or(|f| name.contains(f.as_str()))
+            {
+                let (estimate, from_history) = self.projected(&id);
+                listing.cases.push(PlannedCase {
+                    group: self.name.clone(),
+                    over_budget: self.over_budget(&id).is_some(),
+                    id,
+                    estimate,
+                    from_history,
+                    dataset: self.dataset,
+                    tags: self.tags.clone(),
+                });
+            }
+            return self;
+        }
+        if let Some(projected) = self.over_budget(&id) {
+            println!(
+                "{}/{id}: skipped, projected {:.1}s exceeds the {:.1}s budget",
+                self.name,
+                projected.as_secs_f64(),
+                self.budget.unwrap_or_default().as_secs_f64(),
+            );
+            return self;
+        }
+
+        let dir = estimates::case_dir(
+            estimates::DEFAULT_DIR,
+            &self.name,
+            &id.function,
+            id.parameter.as_deref(),
+        );
+        let saved_at = || {
+            fs::metadata(dir.join("new/estimates.json"))
+                .and_then(|m| m.modified())
+                .ok()
+        };
+        let before = saved_at();
+        let previous = self.dataset.and_then(|_| self.previous(&id)?.dataset);
+        let memory_budget = self.memory_budget(&id);
+        let base = allocator::reset_peak();
+        #[cfg(feature = "alloc-stats")]
+        crate::alloc_stats::take_bytes_per_allocation();
+
+        match id.parameter.clone() {
+            Some(p) => {
+                self.inner
+                    .bench_with_input(BenchmarkId::new(id.function.clone(), p), input, f)
+            }
+            None => self.inner.bench_with_input(id.function.clone(), input, f),
+        };
+
+        if let Some(budget) = memory_budget {
+            assert!(
+                allocator::is_tracking(),
+                "{} requires the tracking allocator to be installed and enabled",
+                MEM_BUDGET_ENV
+            );
+            let peak = allocator::peak().saturating_sub(base);
+            if peak > budget {
+                let case = format!("{}/{id}", self.name);
+                println!(
+                    "{case}: peak memory {:.2} MiB exceeds the {:.2} MiB budget",
+                    peak as f64 / (1 << 20) as f64,
+                    budget as f64 / (1 << 20) as f64,
+                );
+                VIOLATIONS
+                    .lock()
+                    .unwrap()
+                    .push(MemoryViolation { case, peak, budget });
+            }
+        }
+
+        // Only record the dataset if criterion saved new results, rather than
+        // skipping the case (due to a filter, or in `--test` mode).
+        let ran = saved_at().is_some_and(|after| Some(after) != before);
+        if let (Some(dataset), true) = (self.dataset, ran) {
+            if let Some(previous) = previous.filter(|p| *p != dataset.to_string()) {
+                println!(
+                    "{}/{id}: warning, input changed from dataset {previous} to {dataset}, \
+                     change estimates are not comparable",
+                    self.name
+                );
+            }
+            let _ = fs::write(dir.join(estimates::DATASET_FILE), format!("{dataset}\n"));
+        }
+        #[cfg(feature = "alloc-stats")]
+        if ran {
+            self.record_bytes(&id, &dir);
+        }
+        self
+    }
+
+    /// Print and save the bytes allocated per iteration of the case `id`
+    /// that just ran, from the allocations per iteration criterion saved
+    /// to `dir`.
+    #[cfg(feature = "alloc-stats")]
+    fn record_bytes(&self, id: &Id, dir: &std::path::Path) {
+        use crate::alloc_stats;
+
+        let per_allocation = alloc_stats::take_bytes_per_allocation().unwrap_or(0.0);
+        let allocations = match self.previous(id) {
+            Some(m) => m.typical().point.max(0.0),
+            None => return,
+        };
+        let bytes = allocations * per_allocation;
+        println!(
+            "{}/{id}: {allocations:.1} allocations, {bytes:.0} bytes per iteration",
+            self.name
+        );
+        let _ = fs::write(dir.join(alloc_stats::BYTES_FILE), format!("{bytes}\n"));
+    }
+
+    pub fn finish(self) {
+        self.inner.finish();
+    }
+
+    /// The memory budget of `id` in bytes, if it has one.
+    fn memory_budget(&self, id: &Id) -> Option<usize> {
+        static BUDGETS: OnceLock<Vec<MemoryBudget>> = OnceLock::new();
+        let name = format!("{}/{id}", self.name);
+        BUDGETS
+            .get_or_init(memory_budgets_from_env)
+            .iter()
+            .filter(|b| name.starts_with(&b.prefix))
+            .max_by_key(|b| b.prefix.len())
+            .map(|b| b.bytes)
+    }
+
+    /// The result of the previous run of `id`, if any.
+    fn previous(&self, id: &Id) -> Option<estimates::Measurement> {
+        estimates::load_case(
+            estimates::DEFAULT_DIR,
+            &self.name,
+            &id.function,
+            id.parameter.as_deref(),
+        )
+        .ok()?
+    }
+
+    /// Project the runtime of `id` from its previous result, if any, or
+    /// otherwise assume that it runs for criterion's default warm-up and
+    /// measurement times.
+    ///
+    /// Criterion runs each case for at least its warm-up and measurement
+    /// times, and for at least one iteration per sample, so a slow case
+    /// takes roughly `max(warm-up, t) + max(measurement, t * samples)`.
+    fn projected(&self, id: &Id) -> (Duration, bool) {
+        match self.previous(id) {
+            Some(previous) => {
+                let per_iter = Duration::from_secs_f64(previous.typical().point.max(0.0) / 1e9);
+                let projected = WARM_UP_TIME.max(per_iter)
+                    + MEASUREMENT_TIME.max(per_iter * self.sample_size as u32);
+                (projected, true)
+            }
+            None => (WARM_UP_TIME + MEASUREMENT_TIME, false),
+        }
+    }
+
+    /// Return the projected runtime of `id` if it exceeds the budget. Cases
+    /// without a previous result are never over budget.
+    fn over_budget(&self, id: &Id) -> Option<Duration> {
+        let budget = self.budget?;
+        match self.projected(id) {
+            (projected, true) if projected > budget => Some(projected),
+            _ => None,
+        }
+    }
+}
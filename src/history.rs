@@ -0,0 +1,355 @@
+//! A record of the results of successive runs, for tracking how the
+//! medians of each case drift across releases of the benchmarked crates.
+//!
+//! Each recorded run is an [`Entry`] of the [`crate::export::rows`] of a
+//! criterion run with the time, the commit of this crate and the
+//! [`Environment`] (CPU, rustc and the locked crate versions) it was
+//! recorded in, appended as one line of JSON to a file, [`DEFAULT_PATH`] by
+//! default. JSON lines is the only store: appending never rewrites earlier
+//! runs, and the file can be kept under version control or concatenated
+//! from several machines.
+//!
+//! [`trend`] gathers the median of each case, matched by (operation,
+//! distribution, backend, n) as in [`crate::regression`], across the runs
+//! recording it.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+    process::Command,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    export::{n_key, Row},
+    gen::Distribution,
+    report::Environment,
+};
+
+/// The default history file, relative to the crate root.
+pub const DEFAULT_PATH: &str = "target/history.jsonl";
+
+/// The characters of a [`Series::sparkline`], from the lowest median to the
+/// highest.
+const SPARKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// One recorded run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// When the run was recorded, in seconds since the unix epoch.
+    pub timestamp: u64,
+
+    /// The commit of this crate the run was measured at, from
+    /// `git rev-parse HEAD`.
+    pub commit: Option<String>,
+    pub environment: Environment,
+    pub rows: Vec<Row>,
+}
+
+impl Entry {
+    /// An entry of `rows`, recorded now on this machine, which should be
+    /// the one they were measured on.
+    pub fn new(rows: Vec<Row>) -> Self {
+        let commit = Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+
+        Self {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |d| d.as_secs()),
+            commit,
+            environment: Environment::collect(),
+            rows,
+        }
+    }
+
+    /// The abbreviated commit, or `-` if unknown.
+    pub fn short_commit(&self) -> &str {
+        self.commit.as_deref().map_or("-", |c| &c[..c.len().min(8)])
+    }
+}
+
+/// Append `entry` to the history at `path`, creating it (and its parent
+/// directories) if needed.
+pub fn append(path: impl AsRef<Path>, entry: &Entry) -> io::Result<()> {
+    let path = path.as_ref();
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string(entry).map_err(io::Error::other)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut f| writeln!(f, "{json}"))
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+}
+
+/// Load every entry of the history at `path`, oldest first.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Vec<Entry>> {
+    let path = path.as_ref();
+    let lines = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+
+    let mut entries = lines
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(i, l)| {
+            serde_json::from_str::<Entry>(l).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: line {}: {e}", path.display(), i + 1),
+                )
+            })
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|e| e.timestamp);
+    Ok(entries)
+}
+
+/// The median of a case in one run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Point {
+    /// The index of the run in the entries given to [`trend`].
+    pub run: usize,
+    pub median_ns: f64,
+}
+
+/// The medians of one case across the runs recording it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Series {
+    pub operation: String,
+    pub distribution: Option<Distribution>,
+    pub backend: String,
+    pub n: Option<String>,
+
+    /// One point per run recording the case, oldest first.
+    pub points: Vec<Point>,
+}
+
+impl Series {
+    pub fn first_ns(&self) -> f64 {
+        self.points[0].median_ns
+    }
+
+    pub fn last_ns(&self) -> f64 {
+        self.points[self.points.len() - 1].median_ns
+    }
+
+    /// The change in median from the first run to the last as a fraction
+    /// of the first, positive when slower.
+    pub fn relative(&self) -> f64 {
+        self.last_ns() / self.first_ns().max(f64::MIN_POSITIVE) - 1.0
+    }
+
+    /// The medians as a line of block characters, one per point, scaled
+    /// from the lowest to the highest.
+    pub fn sparkline(&self) -> String {
+        let (min, max) = self
+            .points
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                (min.min(p.median_ns), max.max(p.median_ns))
+            });
+        let top = (SPARKS.len() - 1) as f64;
+        self.points
+            .iter()
+            .map(|p| match max - min {
+                range if range > 0.0 => {
+                    SPARKS[((p.median_ns - min) / range * top).round() as usize]
+                }
+                _ => SPARKS[SPARKS.len() / 2],
+            })
+            .collect()
+    }
+}
+
+/// The series of every case in `entries`, ordered by (operation,
+/// distribution, n, backend) as the rows of a run are.
+pub fn trend(entries: &[Entry]) -> Vec<Series> {
+    let mut series: BTreeMap<_, Series> = BTreeMap::new();
+    for (run, entry) in entries.iter().enumerate() {
+        for r in &entry.rows {
+            let key = (
+                r.operation.clone(),
+                r.distribution.map(|d| d.name()),
+                r.backend.clone(),
+                r.n.clone(),
+            );
+            series
+                .entry(key)
+                .or_insert_with(|| Series {
+                    operation: r.operation.clone(),
+                    distribution: r.distribution,
+                    backend: r.backend.clone(),
+                    n: r.n.clone(),
+                    points: Vec::new(),
+                })
+                .points
+                .push(Point {
+                    run,
+                    median_ns: r.median_ns,
+                });
+        }
+    }
+
+    let mut series = series.into_values().collect::<Vec<_>>();
+    series.sort_by(|a, b| {
+        (&a.operation, a.distribution, n_key(&a.n), &a.backend)
+            .partial_cmp(&(&b.operation, b.distribution, n_key(&b.n), &b.backend))
+            .expect("sizes are not NaN")
+    });
+    series
+}
+
+/// Render the points of `series` as CSV, one line per (case, run), for
+/// plotting the trend with another tool.
+pub fn csv(entries: &[Entry], series: &[Series]) -> String {
+    let mut out = String::from("recorded,commit,operation,distribution,backend,n,median_ns\n");
+    for s in series {
+        for p in &s.points {
+            let entry = &entries[p.run];
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{}\n",
+                format_timestamp(entry.timestamp),
+                entry.commit.as_deref().unwrap_or(""),
+                s.operation,
+                s.distribution.map_or("", |d| d.name()),
+                s.backend,
+                s.n.as_deref().unwrap_or(""),
+                p.median_ns,
+            ));
+        }
+    }
+    out
+}
+
+/// `timestamp`, in seconds since the unix epoch, as an ISO 8601 UTC time.
+pub fn format_timestamp(timestamp: u64) -> String {
+    let (days, secs) = (timestamp / 86_400, timestamp % 86_400);
+
+    // The proleptic Gregorian date of a day count, from Howard Hinnant's
+    // civil_from_days.
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3_600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(operation: &str, backend: &str, n: u32, median_ns: f64) -> Row {
+        Row {
+            operation: operation.to_string(),
+            distribution: None,
+            backend: backend.to_string(),
+            n: Some(n.to_string()),
+            median_ns,
+            median_lower_ns: median_ns,
+            median_upper_ns: median_ns,
+            mean_ns: median_ns,
+        }
+    }
+
+    fn entry(timestamp: u64, rows: Vec<Row>) -> Entry {
+        Entry {
+            timestamp,
+            commit: None,
+            environment: Environment::default(),
+            rows,
+        }
+    }
+
+    #[test]
+    fn format_timestamp_dates() {
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(951_868_800), "2000-03-01T00:00:00Z");
+        assert_eq!(format_timestamp(1_704_067_199), "2023-12-31T23:59:59Z");
+    }
+
+    #[test]
+    fn trend_groups_runs() {
+        let entries = [
+            entry(
+                1,
+                vec![
+                    row("union", "roaring", 10, 5.0),
+                    row("union", "roaring", 2, 1.0),
+                ],
+            ),
+            entry(2, vec![row("union", "roaring", 10, 10.0)]),
+        ];
+        let series = trend(&entries);
+
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].n.as_deref(), Some("2"));
+        assert_eq!(
+            series[0].points,
+            [Point {
+                run: 0,
+                median_ns: 1.0
+            }]
+        );
+
+        let s = &series[1];
+        assert_eq!(
+            (s.operation.as_str(), s.n.as_deref()),
+            ("union", Some("10"))
+        );
+        assert_eq!(
+            s.points,
+            [
+                Point {
+                    run: 0,
+                    median_ns: 5.0
+                },
+                Point {
+                    run: 1,
+                    median_ns: 10.0
+                },
+            ]
+        );
+        assert_eq!(s.relative(), 1.0);
+        assert_eq!(s.sparkline(), "▁█");
+    }
+
+    #[test]
+    fn sparkline_of_constant_series_is_uniform() {
+        let entries = (0..4)
+            .map(|t| entry(t, vec![row("union", "roaring", 10, 7.0)]))
+            .collect::<Vec<_>>();
+        let line = trend(&entries)[0].sparkline();
+
+        assert_eq!(line.chars().count(), 4);
+        assert!(
+            line.chars().all(|c| c == SPARKS[SPARKS.len() / 2]),
+            "{}",
+            line
+        );
+    }
+}
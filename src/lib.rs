@@ -0,0 +1,57 @@
+//! Shared code for comparing the [`croaring`] and [`roaring`] bitmap
+//! implementations.
+//!
+//! The criterion benchmarks live in `benches/`; this library exposes the same
+//! comparison as a plain function call ([`run_suite`]) so other tools can
+//! embed it without shelling out to `cargo bench`.
+
+// With neither library enabled there is no `Backend` to run, leaving the
+// code handling one unused.
+#![cfg_attr(
+    not(any(feature = "croaring", feature = "roaring")),
+    allow(unused, unreachable_code)
+)]
+
+pub mod affinity;
+#[cfg(feature = "alloc-stats")]
+pub mod alloc_stats;
+pub mod allocator;
+pub mod bitmap_impl;
+pub mod bitset;
+pub mod bloom;
+pub mod cache;
+#[cfg(feature = "croaring")]
+pub mod cow;
+pub mod crossover;
+pub mod datasets;
+pub mod estimates;
+pub mod export;
+pub mod frame;
+pub mod gen;
+pub mod harness;
+pub mod history;
+pub mod mix;
+pub mod normalize;
+pub mod numa;
+mod participant;
+#[cfg(all(feature = "perf", target_os = "linux"))]
+pub mod perf;
+pub mod pool;
+#[cfg(all(feature = "profiling", target_os = "linux"))]
+pub mod profiler;
+pub mod regression;
+pub mod report;
+pub mod scale;
+pub mod scenario;
+pub mod snapshot;
+pub mod stats;
+mod suite;
+pub mod summary;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod zipf;
+
+pub use suite::*;
+
+/// The set sizes each benchmark group is run against, see [`scale`].
+pub static N: scale::Sizes = scale::Sizes::new();
@@ -0,0 +1,190 @@
+//! Seeded streams of mixed reads and writes, replayed against each backend
+//! by the mixed workload groups.
+//!
+//! A [`Mix`] gives the relative weight of each kind of [`Op`]. The mixes
+//! benchmarked are [`DEFAULT_MIXES`], or those set by [`MIX_ENV`] as a
+//! comma-separated list of `insert/remove/contains[/rank]` weights, such as
+//! `80/10/10,25/25/25/25`.
+
+use std::{env, fmt, str::FromStr};
+
+use rand::Rng;
+
+/// The environment variable holding the mixes benchmarked.
+pub const MIX_ENV: &str = "ROARING_BENCH_MIX";
+
+/// The mixes benchmarked when [`MIX_ENV`] is not set: write heavy, read
+/// heavy, and an even split.
+pub const DEFAULT_MIXES: [Mix; 3] = [
+    Mix {
+        insert: 80,
+        remove: 10,
+        contains: 10,
+        rank: 0,
+    },
+    Mix {
+        insert: 5,
+        remove: 5,
+        contains: 80,
+        rank: 10,
+    },
+    Mix {
+        insert: 25,
+        remove: 25,
+        contains: 25,
+        rank: 25,
+    },
+];
+
+/// A single operation of a mixed workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
+    Insert(u32),
+    Remove(u32),
+    Contains(u32),
+    Rank(u32),
+}
+
+/// The relative weights of each kind of [`Op`] in a workload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Mix {
+    pub insert: u32,
+    pub remove: u32,
+    pub contains: u32,
+    pub rank: u32,
+}
+
+impl Mix {
+    /// The name used for this mix in benchmark group names, such as
+    /// `80_10_10_0`.
+    pub fn name(&self) -> String {
+        format!(
+            "{}_{}_{}_{}",
+            self.insert, self.remove, self.contains, self.rank
+        )
+    }
+
+    /// Generate `len` operations following this mix, each on a value drawn
+    /// uniformly from `0..universe`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if every weight is zero.
+    pub fn stream<R: Rng + ?Sized>(&self, len: usize, universe: u32, rng: &mut R) -> Vec<Op> {
+        let total = self.insert + self.remove + self.contains + self.rank;
+        assert!(total > 0, "mix {} has no operations", self);
+        (0..len)
+            .map(|_| {
+                let v = rng.gen_range(0..universe.max(1));
+                match rng.gen_range(0..total) {
+                    w if w < self.insert => Op::Insert(v),
+                    w if w < self.insert + self.remove => Op::Remove(v),
+                    w if w < self.insert + self.remove + self.contains => Op::Contains(v),
+                    _ => Op::Rank(v),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Mix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}/{}/{}/{}",
+            self.insert, self.remove, self.contains, self.rank
+        )
+    }
+}
+
+impl FromStr for Mix {
+    type Err = String;
+
+    /// Parse `insert/remove/contains[/rank]` weights, the rank weight
+    /// defaulting to zero.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let weights = s
+            .split('/')
+            .map(|w| w.trim().parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| format!("invalid mix {s:?}: {e}"))?;
+        let (insert, remove, contains, rank) = match weights[..] {
+            [i, r, c] => (i, r, c, 0),
+            [i, r, c, k] => (i, r, c, k),
+            _ => return Err(format!("invalid mix {s:?}: expected 3 or 4 weights")),
+        };
+        // The weights are summed when drawing each operation.
+        let total = [remove, contains, rank]
+            .iter()
+            .try_fold(insert, |total, &w| total.checked_add(w))
+            .ok_or_else(|| format!("invalid mix {s:?}: the weights sum past {}", u32::MAX))?;
+        if total == 0 {
+            return Err(format!("invalid mix {s:?}: every weight is zero"));
+        }
+        Ok(Self {
+            insert,
+            remove,
+            contains,
+            rank,
+        })
+    }
+}
+
+/// The mixes set by [`MIX_ENV`], or [`DEFAULT_MIXES`].
+///
+/// # Panics
+///
+/// Panics if the variable is set to something other than a list of mixes.
+pub fn mixes_from_env() -> Vec<Mix> {
+    match env::var(MIX_ENV) {
+        Ok(v) => v
+            .split(',')
+            .map(|m| {
+                m.parse()
+                    .unwrap_or_else(|e| panic!("invalid {} value: {}", MIX_ENV, e))
+            })
+            .collect(),
+        Err(_) => DEFAULT_MIXES.to_vec(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_three_and_four_weights() {
+        assert_eq!("80/10/10".parse::<Mix>().unwrap(), DEFAULT_MIXES[0]);
+        assert_eq!("25/25/25/25".parse::<Mix>().unwrap(), DEFAULT_MIXES[2]);
+        assert_eq!(
+            " 5 / 5 / 80 / 10 ".parse::<Mix>().unwrap(),
+            DEFAULT_MIXES[1]
+        );
+        assert_eq!("0/0/1".parse::<Mix>().unwrap().name(), "0_0_1_0");
+    }
+
+    #[test]
+    fn rejects_bad_mixes() {
+        for mix in [
+            "",
+            "80/20",
+            "1/2/3/4/5",
+            "80/10/ten",
+            "-1/1/1",
+            "0/0/0",
+            "0/0/0/0",
+            "4294967295/1/0",
+            "2147483648/2147483648/0/0",
+        ] {
+            assert!(mix.parse::<Mix>().is_err(), "{:?}", mix);
+        }
+        assert!("4294967295/0/0".parse::<Mix>().is_ok());
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for mix in DEFAULT_MIXES {
+            assert_eq!(mix.to_string().parse::<Mix>().unwrap(), mix);
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! Normalization of results against the machine they were measured on.
+//!
+//! Every run includes two reference groups: [`NULL_BASELINE_GROUP`], the cost
+//! of benchmarking an empty closure, and [`CALIBRATION_GROUP`], a fixed
+//! backend-independent CPU workload. Subtracting the former and dividing by
+//! the latter expresses each result in "calibration units", which remain
+//! roughly comparable between machines of different speeds.
+
+use crate::estimates::{Estimate, Measurement};
+
+/// The criterion group measuring harness overhead.
+pub const NULL_BASELINE_GROUP: &str = "null_baseline";
+
+/// The criterion group measuring the reference workload.
+pub const CALIBRATION_GROUP: &str = "calibration";
+
+/// Scales results by the reference groups of a single run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Normalizer {
+    /// The harness overhead, in ns.
+    null_ns: f64,
+
+    /// The cost of one calibration unit (net of the overhead), in ns.
+    unit_ns: f64,
+}
+
+impl Normalizer {
+    /// Derive a normalizer from the reference groups in `measurements`,
+    /// returning `None` if either is missing.
+    pub fn from_measurements(measurements: &[Measurement]) -> Option<Self> {
+        let find = |group| {
+            measurements
+                .iter()
+                .find(|m| m.group == group)
+                .map(|m| m.typical().point)
+        };
+        let null_ns = find(NULL_BASELINE_GROUP)?;
+        let unit_ns = find(CALIBRATION_GROUP)? - null_ns;
+        (unit_ns > 0.0).then_some(Self { null_ns, unit_ns })
+    }
+
+    /// Express `m` in calibration units.
+    pub fn apply(&self, m: &Measurement) -> Measurement {
+        let scale = |e: &Estimate| Estimate {
+            point: self.scale(e.point),
+            lower: self.scale(e.lower),
+            upper: self.scale(e.upper),
+        };
+        Measurement {
+            mean: scale(&m.mean),
+            median: scale(&m.median),
+            slope: m.slope.as_ref().map(scale),
+            samples: m.samples.iter().map(|&v| self.scale(v)).collect(),
+            ..m.clone()
+        }
+    }
+
+    fn scale(&self, ns: f64) -> f64 {
+        (ns - self.null_ns).max(0.0) / self.unit_ns
+    }
+}
+
+/// Normalize every measurement of a single run, dropping the reference
+/// groups themselves.
+///
+/// Returns `None` if the run did not include the reference groups.
+pub fn normalize(measurements: &[Measurement]) -> Option<Vec<Measurement>> {
+    let normalizer = Normalizer::from_measurements(measurements)?;
+    Some(
+        measurements
+            .iter()
+            .filter(|m| m.group != NULL_BASELINE_GROUP && m.group != CALIBRATION_GROUP)
+            .map(|m| normalizer.apply(m))
+            .collect(),
+    )
+}
+
+/// The fixed workload measured by the [`CALIBRATION_GROUP`]: a dependent
+/// chain of integer mixing steps, so its cost tracks scalar CPU speed.
+pub fn calibration_workload(seed: u64) -> u64 {
+    let mut x = seed;
+    for _ in 0..64 {
+        x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = x;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        x = z ^ (z >> 31);
+    }
+    x
+}
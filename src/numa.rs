@@ -0,0 +1,131 @@
+//! Opt-in binding of benchmark memory to a single NUMA node.
+//!
+//! On multi-socket hosts the largest bitmaps can end up spread across the
+//! memory of several nodes, and the remote-node access latency then distorts
+//! the comparison between backends. Set [`NUMA_NODE_ENV`] to bind all
+//! subsequent allocations (and the benchmark thread) to one node, so datasets
+//! built during setup are allocated and first touched on that node before
+//! measurement begins.
+
+use std::{env, fs, io};
+
+use crate::affinity;
+
+/// The environment variable naming the NUMA node to bind to.
+pub const NUMA_NODE_ENV: &str = "ROARING_BENCH_NUMA_NODE";
+
+/// Bind the calling thread's memory and CPU affinity to the node named by
+/// [`NUMA_NODE_ENV`], returning the node if binding was requested.
+///
+/// This must be called before any benchmark input is generated - memory
+/// already allocated is not migrated.
+///
+/// # Panics
+///
+/// Panics if the variable is set but is not a valid node, or binding fails.
+pub fn bind_from_env() -> Option<usize> {
+    let node = env::var(NUMA_NODE_ENV).ok()?;
+    let node = node
+        .trim()
+        .parse()
+        .unwrap_or_else(|_| panic!("{} must be a node index, got {:?}", NUMA_NODE_ENV, node));
+
+    bind_current_thread(node)
+        .unwrap_or_else(|e| panic!("failed to bind to NUMA node {}: {}", node, e));
+    Some(node)
+}
+
+/// Bind all future allocations by the calling thread to `node`, and restrict
+/// it to run on the CPUs local to that node.
+pub fn bind_current_thread(node: usize) -> io::Result<()> {
+    set_membind(node)?;
+    affinity::restrict_current_thread(&node_cpus(node)?)
+}
+
+/// Read the CPUs belonging to `node` from sysfs.
+fn node_cpus(node: usize) -> io::Result<Vec<usize>> {
+    let list = fs::read_to_string(format!("/sys/devices/system/node/node{node}/cpulist"))?;
+    parse_cpu_list(&list)
+}
+
+/// Parse a kernel CPU list such as `0-3,8,10-11`.
+fn parse_cpu_list(list: &str) -> io::Result<Vec<usize>> {
+    let invalid = || io::Error::new(io::ErrorKind::InvalidData, "malformed cpulist");
+
+    let mut cpus = Vec::new();
+    for part in list.trim().split(',').filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((lo, hi)) => {
+                let lo: usize = lo.parse().map_err(|_| invalid())?;
+                let hi: usize = hi.parse().map_err(|_| invalid())?;
+                if lo > hi {
+                    return Err(invalid());
+                }
+                cpus.extend(lo..=hi);
+            }
+            None => cpus.push(part.parse().map_err(|_| invalid())?),
+        }
+    }
+    Ok(cpus)
+}
+
+#[cfg(target_os = "linux")]
+fn set_membind(node: usize) -> io::Result<()> {
+    // From linux/mempolicy.h
+    const MPOL_BIND: libc::c_int = 2;
+
+    let bits = libc::c_ulong::BITS as usize;
+    let mut mask = vec![0 as libc::c_ulong; node / bits + 1];
+    mask[node / bits] |= 1 << (node % bits);
+
+    // SAFETY: the mask is valid for the number of bits passed as maxnode.
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_set_mempolicy,
+            MPOL_BIND,
+            mask.as_ptr(),
+            mask.len() * bits + 1,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+#[cfg(not(target_os = "linux"))]
+fn set_membind(_node: usize) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "NUMA binding is only supported on linux",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_cpu_list_ranges_and_singles() {
+        assert_eq!(
+            parse_cpu_list("0-3,8,10-11\n").unwrap(),
+            [0, 1, 2, 3, 8, 10, 11]
+        );
+        assert_eq!(parse_cpu_list("5").unwrap(), [5]);
+    }
+
+    #[test]
+    fn parse_cpu_list_empty() {
+        // A memory-only node has no CPUs.
+        assert!(parse_cpu_list("").unwrap().is_empty());
+        assert!(parse_cpu_list("\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_cpu_list_rejects_malformed() {
+        for list in ["0-", "-3", "a", "0-3,x", "1-2-3", "3-1", "0;1"] {
+            let err = parse_cpu_list(list).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{:?}", list);
+        }
+    }
+}
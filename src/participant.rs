@@ -0,0 +1,49 @@
+//! Guards for the code of each library under test, so the suite builds with
+//! any subset of the `croaring` and `roaring` features enabled, and the
+//! dispatch of generic code to each of them.
+//!
+//! The macros expand their body as a block statement that only exists when
+//! the library's feature is enabled. The `cfg` is evaluated where the macro
+//! is used, which for the benchmarks of this package is the same feature
+//! set.
+
+/// Expand to the given statements only when the `croaring` feature is
+/// enabled.
+#[macro_export]
+macro_rules! with_croaring {
+    ($($body:tt)*) => {
+        #[cfg(feature = "croaring")]
+        {
+            $($body)*
+        }
+    };
+}
+
+/// Expand to the given statements only when the `roaring` feature is
+/// enabled.
+#[macro_export]
+macro_rules! with_roaring {
+    ($($body:tt)*) => {
+        #[cfg(feature = "roaring")]
+        {
+            $($body)*
+        }
+    };
+}
+
+/// Call the generic function `f` once for each enabled library, with its
+/// [`BitmapImpl`](crate::bitmap_impl::BitmapImpl) adapter as the type
+/// parameter, such as `for_each_impl!(insert_case(&mut group, values))`.
+///
+/// The arguments are evaluated once per call, so are usually borrows.
+#[macro_export]
+macro_rules! for_each_impl {
+    ($f:ident($($arg:expr),* $(,)?)) => {
+        $crate::with_croaring! {
+            $f::<::croaring::Bitmap>($($arg),*);
+        }
+        $crate::with_roaring! {
+            $f::<::roaring::RoaringBitmap>($($arg),*);
+        }
+    };
+}
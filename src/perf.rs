@@ -0,0 +1,159 @@
+//! A criterion [`Measurement`] counting retired instructions with the Linux
+//! perf subsystem.
+//!
+//! Instruction counts are largely immune to frequency scaling and noisy
+//! neighbours, so they make a more repeatable (if less direct) metric than
+//! wall time. Enabled by the `perf` feature, which switches every benchmark
+//! group to this measurement; results written under `target/criterion` are
+//! then counts of instructions rather than nanoseconds.
+//!
+//! Opening the counter requires `kernel.perf_event_paranoid` of 2 or lower
+//! (only user-space instructions are counted).
+
+use std::{io, os::unix::io::RawFd};
+
+use criterion::{
+    measurement::{Measurement, ValueFormatter},
+    Throughput,
+};
+
+const PERF_TYPE_HARDWARE: u32 = 0;
+const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+
+const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+/// The `PERF_ATTR_SIZE_VER0` prefix of `struct perf_event_attr`, which libc
+/// does not define.
+#[repr(C)]
+#[derive(Default)]
+struct PerfEventAttr {
+    kind: u32,
+    size: u32,
+    config: u64,
+    sample_period: u64,
+    sample_type: u64,
+    read_format: u64,
+    flags: u64,
+    wakeup_events: u32,
+    bp_type: u32,
+    config1: u64,
+}
+
+/// Measures the user-space instructions retired by the calling thread.
+///
+/// The counter is opened for the thread that constructs the measurement, so
+/// it must be created on the thread running the benchmarks.
+#[derive(Debug)]
+pub struct Instructions {
+    fd: RawFd,
+}
+
+impl Instructions {
+    pub fn new() -> io::Result<Self> {
+        let attr = PerfEventAttr {
+            kind: PERF_TYPE_HARDWARE,
+            size: std::mem::size_of::<PerfEventAttr>() as u32,
+            config: PERF_COUNT_HW_INSTRUCTIONS,
+            flags: FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+            ..Default::default()
+        };
+
+        // SAFETY: attr is a valid, correctly sized perf_event_attr.
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &attr as *const PerfEventAttr,
+                0,  // this thread
+                -1, // any CPU
+                -1, // no group
+                0,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(Self { fd: fd as RawFd })
+    }
+
+    fn read(&self) -> u64 {
+        let mut count = 0_u64;
+        // SAFETY: reads exactly the 8 bytes of count.
+        let n = unsafe { libc::read(self.fd, (&mut count as *mut u64).cast(), 8) };
+        assert_eq!(n, 8, "failed to read perf counter");
+        count
+    }
+}
+
+impl Drop for Instructions {
+    fn drop(&mut self) {
+        // SAFETY: fd is owned by self.
+        unsafe { libc::close(self.fd) };
+    }
+}
+
+impl Measurement for Instructions {
+    type Intermediate = u64;
+    type Value = u64;
+
+    fn start(&self) -> Self::Intermediate {
+        self.read()
+    }
+
+    fn end(&self, start: Self::Intermediate) -> Self::Value {
+        self.read() - start
+    }
+
+    fn add(&self, v1: &Self::Value, v2: &Self::Value) -> Self::Value {
+        v1 + v2
+    }
+
+    fn zero(&self) -> Self::Value {
+        0
+    }
+
+    fn to_f64(&self, value: &Self::Value) -> f64 {
+        *value as f64
+    }
+
+    fn formatter(&self) -> &dyn ValueFormatter {
+        &InstructionFormatter
+    }
+}
+
+struct InstructionFormatter;
+
+impl ValueFormatter for InstructionFormatter {
+    fn scale_values(&self, typical: f64, values: &mut [f64]) -> &'static str {
+        let (factor, unit) = match typical {
+            t if t < 1e3 => (1.0, "insns"),
+            t if t < 1e6 => (1e-3, "Kinsns"),
+            t if t < 1e9 => (1e-6, "Minsns"),
+            _ => (1e-9, "Ginsns"),
+        };
+        for v in values {
+            *v *= factor;
+        }
+        unit
+    }
+
+    fn scale_throughputs(
+        &self,
+        _typical: f64,
+        throughput: &Throughput,
+        values: &mut [f64],
+    ) -> &'static str {
+        let (n, unit) = match *throughput {
+            Throughput::Bytes(n) | Throughput::BytesDecimal(n) => (n, "B/insn"),
+            Throughput::Elements(n) => (n, "elem/insn"),
+        };
+        for v in values {
+            *v = n as f64 / *v;
+        }
+        unit
+    }
+
+    fn scale_for_machines(&self, _values: &mut [f64]) -> &'static str {
+        "insns"
+    }
+}
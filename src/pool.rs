@@ -0,0 +1,153 @@
+//! A process-wide pool of pre-built bitmaps, shared by the benchmark groups
+//! that only read their input.
+//!
+//! Building the larger inputs dominates the setup of many groups, and the
+//! same input is often used by several of them. Each bitmap is built on
+//! first use and kept for the lifetime of the process.
+
+use std::{
+    collections::HashMap,
+    sync::{Mutex, OnceLock},
+};
+
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
+
+use crate::gen::{self, Distribution};
+
+/// The contents of a pooled bitmap of a given size.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Workload {
+    /// The contiguous range `0..size`, inserted as a range.
+    Sequential,
+
+    /// The `size` even values `0, 2, 4, ...`.
+    Even,
+
+    /// The even values in `0..size`.
+    AlternateEven,
+
+    /// The odd values in `0..size`, except that `percent`% of them (spread
+    /// evenly) are replaced by the even value below, also in
+    /// [`Workload::AlternateEven`].
+    AlternateOverlap(u32),
+
+    /// The range `0..size / 2`.
+    LowerHalf,
+
+    /// A range of `size / 2` values, starting far enough into
+    /// [`Workload::LowerHalf`] that `percent`% of them are also in it. At 0%
+    /// this is the range `size / 2..size / 2 * 2`.
+    ShiftedHalf(u32),
+
+    /// Every `k`th value of `0..size`, see [`gen::stride`].
+    Stride(u32),
+
+    /// Values following `distribution` at [`gen::DEFAULT_DENSITY`]. Each
+    /// `instance` is generated independently of the others.
+    Generated {
+        distribution: Distribution,
+        instance: u32,
+    },
+}
+
+impl Workload {
+    /// The values of this workload at `size`, in ascending order.
+    pub fn values(&self, size: u32) -> Vec<u32> {
+        match self {
+            Workload::Sequential => (0..size).collect(),
+            Workload::Even => (0..size).map(|v| v * 2).collect(),
+            Workload::AlternateEven => (0..size).step_by(2).collect(),
+            Workload::AlternateOverlap(percent) => (0..size / 2)
+                .map(|i| {
+                    let shared = (i as u64 * *percent as u64) % 100 < *percent as u64;
+                    2 * i + u32::from(!shared)
+                })
+                .collect(),
+            Workload::LowerHalf => (0..size / 2).collect(),
+            Workload::ShiftedHalf(percent) => {
+                let half = size / 2;
+                let start = half - (half as u64 * (*percent).min(100) as u64 / 100) as u32;
+                (start..start + half).collect()
+            }
+            Workload::Stride(k) => gen::stride(*k, size),
+            Workload::Generated {
+                distribution,
+                instance,
+            } => {
+                let mut rng = gen::rng(&format!("pool/{distribution}/{instance}/{size}"));
+                gen::generate(*distribution, size, gen::DEFAULT_DENSITY, &mut rng)
+            }
+        }
+    }
+}
+
+/// The bitmaps of one (workload, size) entry, each built on first access.
+#[derive(Debug)]
+pub struct Pooled {
+    workload: Workload,
+    size: u32,
+    values: OnceLock<Vec<u32>>,
+    #[cfg(feature = "croaring")]
+    croaring: OnceLock<croaring::Bitmap>,
+    #[cfg(feature = "croaring")]
+    croaring_rle: OnceLock<croaring::Bitmap>,
+    #[cfg(feature = "roaring")]
+    roaring: OnceLock<RoaringBitmap>,
+}
+
+impl Pooled {
+    pub fn values(&self) -> &[u32] {
+        self.values.get_or_init(|| self.workload.values(self.size))
+    }
+
+    #[cfg(feature = "croaring")]
+    pub fn croaring(&self) -> &croaring::Bitmap {
+        self.croaring.get_or_init(|| match self.workload {
+            Workload::Sequential => croaring::Bitmap::from_range(0..self.size),
+            _ => croaring::Bitmap::of(self.values()),
+        })
+    }
+
+    /// The croaring bitmap after `run_optimize()`.
+    #[cfg(feature = "croaring")]
+    pub fn croaring_rle(&self) -> &croaring::Bitmap {
+        self.croaring_rle.get_or_init(|| {
+            let mut bm = self.croaring().clone();
+            bm.run_optimize();
+            bm
+        })
+    }
+
+    #[cfg(feature = "roaring")]
+    pub fn roaring(&self) -> &RoaringBitmap {
+        self.roaring.get_or_init(|| match self.workload {
+            Workload::Sequential => {
+                let mut bm = RoaringBitmap::new();
+                bm.insert_range(0..self.size);
+                bm
+            }
+            _ => RoaringBitmap::from_sorted_iter(self.values().iter().copied()).unwrap(),
+        })
+    }
+}
+
+/// Return the pooled entry for `workload` at `size`.
+pub fn get(workload: Workload, size: u32) -> &'static Pooled {
+    static POOL: OnceLock<Mutex<HashMap<(Workload, u32), &'static Pooled>>> = OnceLock::new();
+
+    let mut pool = POOL.get_or_init(Default::default).lock().unwrap();
+    pool.entry((workload, size)).or_insert_with(|| {
+        Box::leak(Box::new(Pooled {
+            workload,
+            size,
+            values: OnceLock::new(),
+            #[cfg(feature = "croaring")]
+            croaring: OnceLock::new(),
+            #[cfg(feature = "croaring")]
+            croaring_rle: OnceLock::new(),
+            #[cfg(feature = "roaring")]
+            roaring: OnceLock::new(),
+        }))
+    })
+}
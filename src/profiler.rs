@@ -0,0 +1,103 @@
+//! A criterion [`Profiler`] recording a call-graph profile of each
+//! benchmark with `perf record`, for rendering as a flamegraph.
+//!
+//! Enabled by the `profiling` feature, and only active when criterion is
+//! run in profiling mode:
+//!
+//! ```text
+//! cargo bench --features profiling -- --profile-time 10
+//! ```
+//!
+//! Each benchmark then leaves a `perf.data` in its directory under
+//! `target/criterion/<group>/<id>/profile`, covering only the iterations
+//! criterion ran while profiling (not the setup before them). Render it with
+//! any `perf script` consumer, such as:
+//!
+//! ```text
+//! perf script -i perf.data | inferno-collapse-perf | inferno-flamegraph > flamegraph.svg
+//! ```
+//!
+//! Sampling from outside the process keeps the profiler out of the process
+//! being profiled, at the cost of a separate step to render the SVG that an
+//! in-process sampler (such as pprof's) would write directly. `perf` must be
+//! on the `PATH` and allowed to attach to the benchmark process
+//! (`kernel.perf_event_paranoid` of 2 or lower for user-space samples).
+
+use std::{
+    path::Path,
+    process::{Child, Command, Stdio},
+    thread,
+    time::Duration,
+};
+
+use criterion::profiler::Profiler;
+
+/// The sampling frequency passed to `perf record`, in Hz.
+pub const DEFAULT_FREQUENCY: u32 = 999;
+
+/// How long `perf record` is given to attach before the profiled
+/// iterations begin.
+const ATTACH_DELAY: Duration = Duration::from_millis(250);
+
+/// Records each profiled benchmark with `perf record -g` attached to this
+/// process.
+#[derive(Debug)]
+pub struct PerfRecord {
+    frequency: u32,
+    child: Option<Child>,
+}
+
+impl PerfRecord {
+    pub fn new(frequency: u32) -> Self {
+        Self {
+            frequency,
+            child: None,
+        }
+    }
+}
+
+impl Default for PerfRecord {
+    fn default() -> Self {
+        Self::new(DEFAULT_FREQUENCY)
+    }
+}
+
+impl Profiler for PerfRecord {
+    fn start_profiling(&mut self, benchmark_id: &str, benchmark_dir: &Path) {
+        std::fs::create_dir_all(benchmark_dir)
+            .unwrap_or_else(|e| panic!("failed to create {}: {}", benchmark_dir.display(), e));
+        let child = Command::new("perf")
+            .arg("record")
+            .arg("--call-graph=dwarf")
+            .arg("-F")
+            .arg(self.frequency.to_string())
+            .arg("-p")
+            .arg(std::process::id().to_string())
+            .arg("-o")
+            .arg(benchmark_dir.join("perf.data"))
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|e| panic!("failed to start perf record for {}: {}", benchmark_id, e));
+        self.child = Some(child);
+        thread::sleep(ATTACH_DELAY);
+    }
+
+    fn stop_profiling(&mut self, benchmark_id: &str, benchmark_dir: &Path) {
+        let mut child = match self.child.take() {
+            Some(c) => c,
+            None => return,
+        };
+        // perf record only writes out a complete profile when interrupted.
+        // The pid of a child we spawned always fits a pid_t.
+        unsafe { libc::kill(child.id() as libc::pid_t, libc::SIGINT) };
+        match child.wait() {
+            Ok(_) => println!(
+                "profile of {} written to {}",
+                benchmark_id,
+                benchmark_dir.join("perf.data").display()
+            ),
+            Err(e) => eprintln!("perf record for {} failed: {}", benchmark_id, e),
+        }
+    }
+}
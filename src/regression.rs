@@ -0,0 +1,227 @@
+//! Detection of performance regressions against a saved snapshot of
+//! earlier results.
+//!
+//! A baseline is the [`crate::export::rows`] of a run, saved as JSON under
+//! [`DEFAULT_DIR`]. Comparing a later run against it matches each case by
+//! (operation, distribution, backend, n) and flags those whose median moved
+//! by more than a threshold.
+
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::export::Row;
+
+/// The default directory baselines are saved in, relative to the crate
+/// root.
+pub const DEFAULT_DIR: &str = "target/baselines";
+
+/// The change in median flagged when no threshold is given, as a fraction
+/// of the baseline.
+pub const DEFAULT_THRESHOLD: f64 = 0.05;
+
+/// The path of the baseline `name` under `dir`.
+pub fn path(dir: impl AsRef<Path>, name: &str) -> PathBuf {
+    dir.as_ref().join(format!("{name}.json"))
+}
+
+/// Save `rows` as the baseline `name` under `dir`, replacing any previous
+/// baseline of that name.
+pub fn save(dir: impl AsRef<Path>, name: &str, rows: &[Row]) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir.as_ref())?;
+    let path = path(dir, name);
+    let json = serde_json::to_string_pretty(rows).map_err(io::Error::other)?;
+    fs::write(&path, json + "\n")?;
+    Ok(path)
+}
+
+/// Load the baseline `name` from `dir`.
+pub fn load(dir: impl AsRef<Path>, name: &str) -> io::Result<Vec<Row>> {
+    let path = path(dir, name);
+    let json = fs::read_to_string(&path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    serde_json::from_str(&json).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// How the median of a case moved relative to the baseline.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Change {
+    Regressed,
+    Improved,
+    Unchanged,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Change::Regressed => "regressed",
+            Change::Improved => "improved",
+            Change::Unchanged => "unchanged",
+        })
+    }
+}
+
+/// A case present in both the baseline and the current run.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Delta {
+    /// The case as measured by the current run.
+    pub current: Row,
+    pub baseline_ns: f64,
+    pub change: Change,
+}
+
+impl Delta {
+    /// The change in median as a fraction of the baseline, positive when
+    /// slower.
+    pub fn relative(&self) -> f64 {
+        self.current.median_ns / self.baseline_ns.max(f64::MIN_POSITIVE) - 1.0
+    }
+}
+
+/// The outcome of [`compare`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    /// Every case in both runs, in the order of the current run.
+    pub deltas: Vec<Delta>,
+
+    /// The number of cases present in only one of the runs.
+    pub unmatched: usize,
+}
+
+impl Report {
+    pub fn regressions(&self) -> impl Iterator<Item = &Delta> {
+        self.deltas.iter().filter(|d| d.change == Change::Regressed)
+    }
+
+    pub fn has_regressions(&self) -> bool {
+        self.regressions().next().is_some()
+    }
+}
+
+/// Compare `current` against `baseline`, flagging the cases whose median
+/// changed by more than `threshold` (a fraction of the baseline).
+///
+/// A case only counts as changed when its median moved past the
+/// confidence interval of the baseline median too, so that noisy cases
+/// with wide intervals are not flagged by chance.
+pub fn compare(baseline: &[Row], current: &[Row], threshold: f64) -> Report {
+    let mut baseline = baseline
+        .iter()
+        .map(|r| (key(r), r))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut deltas = Vec::new();
+    let mut unmatched = 0;
+    for r in current {
+        let base = match baseline.remove(&key(r)) {
+            Some(b) => b,
+            None => {
+                unmatched += 1;
+                continue;
+            }
+        };
+        let mut delta = Delta {
+            current: r.clone(),
+            baseline_ns: base.median_ns,
+            change: Change::Unchanged,
+        };
+        let relative = delta.relative();
+        if relative > threshold && r.median_ns > base.median_upper_ns {
+            delta.change = Change::Regressed;
+        } else if relative < -threshold && r.median_ns < base.median_lower_ns {
+            delta.change = Change::Improved;
+        }
+        deltas.push(delta);
+    }
+    unmatched += baseline.len();
+    Report { deltas, unmatched }
+}
+
+fn key(r: &Row) -> (&str, Option<&str>, &str, Option<&str>) {
+    (
+        &r.operation,
+        r.distribution.map(|d| d.name()),
+        &r.backend,
+        r.n.as_deref(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(operation: &str, median_ns: f64, interval_ns: (f64, f64)) -> Row {
+        Row {
+            operation: operation.to_string(),
+            distribution: None,
+            backend: "roaring".to_string(),
+            n: Some("1000".to_string()),
+            median_ns,
+            median_lower_ns: interval_ns.0,
+            median_upper_ns: interval_ns.1,
+            mean_ns: median_ns,
+        }
+    }
+
+    #[test]
+    fn compare_flags_changes_past_threshold_and_interval() {
+        let baseline = [
+            row("slower", 100.0, (90.0, 110.0)),
+            row("slightly_slower", 100.0, (99.0, 101.0)),
+            row("slower_but_noisy", 100.0, (80.0, 130.0)),
+            row("faster", 100.0, (90.0, 110.0)),
+            row("faster_but_noisy", 100.0, (70.0, 110.0)),
+            row("only_in_baseline", 100.0, (90.0, 110.0)),
+        ];
+        let current = [
+            row("only_in_current", 100.0, (90.0, 110.0)),
+            row("slower", 120.0, (115.0, 125.0)),
+            row("slightly_slower", 104.0, (103.0, 105.0)),
+            row("slower_but_noisy", 120.0, (115.0, 125.0)),
+            row("faster", 80.0, (75.0, 85.0)),
+            row("faster_but_noisy", 80.0, (75.0, 85.0)),
+        ];
+        let report = compare(&baseline, &current, DEFAULT_THRESHOLD);
+
+        let changes = report
+            .deltas
+            .iter()
+            .map(|d| (d.current.operation.as_str(), d.change))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                ("slower", Change::Regressed),
+                ("slightly_slower", Change::Unchanged),
+                ("slower_but_noisy", Change::Unchanged),
+                ("faster", Change::Improved),
+                ("faster_but_noisy", Change::Unchanged),
+            ]
+        );
+        assert_eq!(report.unmatched, 2);
+        assert!(report.has_regressions());
+        assert_eq!(report.deltas[0].baseline_ns, 100.0);
+        assert!((report.deltas[0].relative() - 0.2).abs() < 1e-12);
+    }
+
+    #[test]
+    fn compare_matches_by_backend_and_size() {
+        let baseline = [row("union", 100.0, (90.0, 110.0))];
+        let mut other_backend = row("union", 200.0, (190.0, 210.0));
+        other_backend.backend = "croaring".to_string();
+        let mut other_size = row("union", 200.0, (190.0, 210.0));
+        other_size.n = Some("10".to_string());
+
+        let report = compare(&baseline, &[other_backend, other_size], DEFAULT_THRESHOLD);
+        assert!(report.deltas.is_empty());
+        assert_eq!(report.unmatched, 3);
+        assert!(!report.has_regressions());
+    }
+}
@@ -0,0 +1,356 @@
+//! Rendering of a self-contained croaring against roaring comparison
+//! report, as markdown or HTML, from the [`Speedup`]s of a criterion run.
+//!
+//! Each (operation, distribution) gets a chart of the two medians at each
+//! size, annotated with the speedup of roaring over croaring, and the
+//! report opens with the [`Environment`] it was measured in. The HTML
+//! report draws its charts as inline SVG and needs no other files.
+
+use std::{collections::BTreeMap, fmt::Write, fs, path::Path, process::Command};
+
+use serde::{Deserialize, Serialize};
+
+use crate::export::{format_nanos, Speedup};
+
+/// The dependencies whose versions are listed in the [`Environment`].
+const CRATES: [&str; 4] = ["croaring", "croaring-sys", "roaring", "criterion"];
+
+/// The width of the longest bar of the markdown charts, in characters.
+const MARKDOWN_BAR: f64 = 30.0;
+
+/// The width of the longest bar of the HTML charts, in pixels.
+const SVG_BAR: f64 = 480.0;
+
+/// Where and with what the report's results were measured.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Environment {
+    /// The CPU model, as reported by `/proc/cpuinfo`.
+    pub cpu: Option<String>,
+    pub cores: Option<usize>,
+    pub os: String,
+
+    /// The output of `rustc --version`.
+    pub rustc: Option<String>,
+
+    /// The resolved version of each of the benchmarked dependencies, from
+    /// `Cargo.lock`, with the commit of any taken from git.
+    pub crates: Vec<(String, String)>,
+}
+
+impl Environment {
+    /// Describe the machine this is running on, which should be the one the
+    /// results were measured on.
+    ///
+    /// Anything that cannot be determined is left out.
+    pub fn collect() -> Self {
+        let cpu = fs::read_to_string("/proc/cpuinfo").ok().and_then(|info| {
+            info.lines()
+                .find_map(|l| l.strip_prefix("model name")?.split_once(':'))
+                .map(|(_, model)| model.trim().to_string())
+        });
+        let rustc = Command::new(std::env::var("RUSTC").unwrap_or_else(|_| "rustc".into()))
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())
+            .map(|o| String::from_utf8_lossy(&o.stdout).trim().to_string());
+        let lock = Path::new(env!("CARGO_MANIFEST_DIR")).join("Cargo.lock");
+        let crates = fs::read_to_string(lock)
+            .map(|lock| locked_versions(&lock))
+            .unwrap_or_default();
+
+        Self {
+            cpu,
+            cores: std::thread::available_parallelism().ok().map(|n| n.get()),
+            os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            rustc,
+            crates,
+        }
+    }
+
+    /// The environment as `(name, value)` pairs, in display order.
+    fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![
+            ("CPU", self.cpu.clone().unwrap_or_else(|| "unknown".into())),
+            (
+                "cores",
+                self.cores
+                    .map_or_else(|| "unknown".into(), |n| n.to_string()),
+            ),
+            ("OS", self.os.clone()),
+            (
+                "rustc",
+                self.rustc.clone().unwrap_or_else(|| "unknown".into()),
+            ),
+        ];
+        for (name, version) in &self.crates {
+            fields.push(("crate", format!("{name} {version}")));
+        }
+        fields
+    }
+}
+
+/// The versions of [`CRATES`] pulled from a registry or git in the
+/// `Cargo.lock` `lock`, skipping this crate (which is also named `roaring`).
+///
+/// A git dependency's version is followed by its locked commit, as in
+/// `0.10.2 (git 1a2b3c4d)`, since unreleased commits share a version.
+fn locked_versions(lock: &str) -> Vec<(String, String)> {
+    let mut versions = Vec::new();
+    for package in lock.split("[[package]]").skip(1) {
+        let field = |key: &str| {
+            package.lines().find_map(|l| {
+                let v = l.strip_prefix(key)?.trim_start().strip_prefix('=')?;
+                Some(v.trim().trim_matches('"').to_string())
+            })
+        };
+        if let (Some(name), Some(version), Some(source)) =
+            (field("name"), field("version"), field("source"))
+        {
+            if CRATES.contains(&name.as_str()) {
+                let version = match source.strip_prefix("git+").and_then(|s| s.rsplit_once('#')) {
+                    Some((_, commit)) => {
+                        format!("{version} (git {})", &commit[..commit.len().min(8)])
+                    }
+                    None => version,
+                };
+                versions.push((name, version));
+            }
+        }
+    }
+    versions.sort();
+    versions
+}
+
+/// The speedups of each (operation, distribution), in the order of
+/// `speedups`, with the title of its chart.
+fn charts(speedups: &[Speedup]) -> Vec<(String, Vec<&Speedup>)> {
+    let mut charts: BTreeMap<_, Vec<&Speedup>> = BTreeMap::new();
+    let mut order = Vec::new();
+    for s in speedups {
+        let key = (&s.operation, s.distribution);
+        charts
+            .entry(key)
+            .or_insert_with(|| {
+                order.push(key);
+                Vec::new()
+            })
+            .push(s);
+    }
+    order
+        .into_iter()
+        .map(|key| {
+            let title = match key.1 {
+                Some(d) => format!("{} ({d})", key.0),
+                None => key.0.clone(),
+            };
+            (title, charts.remove(&key).expect("every key has a chart"))
+        })
+        .collect()
+}
+
+/// Render a markdown report of `speedups`, drawing each chart as a table
+/// of text bars.
+pub fn markdown(speedups: &[Speedup], env: &Environment) -> String {
+    let mut out = String::from("# croaring vs roaring\n\n");
+    for (name, value) in env.fields() {
+        writeln!(out, "- **{name}**: {value}").expect("writing to a string");
+    }
+    let faster = speedups.iter().filter(|s| s.ratio() > 1.0).count();
+    writeln!(
+        out,
+        "\nroaring is faster in {faster} of {} cases. Speedups above 1x favour roaring.",
+        speedups.len()
+    )
+    .expect("writing to a string");
+
+    for (title, cases) in charts(speedups) {
+        writeln!(
+            out,
+            "\n## {title}\n\n| n | backend | median | | speedup |\n|---:|---|---:|---|---:|",
+        )
+        .expect("writing to a string");
+        for s in cases {
+            let n = s.n.as_deref().unwrap_or("-");
+            for (backend, ns) in [("croaring", s.croaring_ns), ("roaring", s.roaring_ns)] {
+                let bar = "█".repeat(((ns / longest(s)) * MARKDOWN_BAR).ceil() as usize);
+                let speedup = if backend == "roaring" {
+                    format!("{:.2}x", s.ratio())
+                } else {
+                    String::new()
+                };
+                writeln!(
+                    out,
+                    "| {n} | {backend} | {} | `{bar}` | {speedup} |",
+                    format_nanos(ns)
+                )
+                .expect("writing to a string");
+            }
+        }
+    }
+    out
+}
+
+/// Render a standalone HTML report of `speedups`, drawing each chart as an
+/// inline SVG.
+pub fn html(speedups: &[Speedup], env: &Environment) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>croaring vs roaring</title>\n<style>\n\
+         body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }\n\
+         svg text { font-size: 12px; dominant-baseline: middle; }\n\
+         .croaring { fill: #4e79a7; } .roaring { fill: #f28e2b; }\n\
+         .faster { font-weight: bold; }\n\
+         </style>\n</head>\n<body>\n<h1>croaring vs roaring</h1>\n<table>\n",
+    );
+    for (name, value) in env.fields() {
+        writeln!(
+            out,
+            "<tr><th align=\"left\">{name}</th><td>{}</td></tr>",
+            escape(&value)
+        )
+        .expect("writing to a string");
+    }
+    out.push_str(
+        "</table>\n<p><svg width=\"12\" height=\"12\"><rect class=\"croaring\" width=\"12\" \
+         height=\"12\"/></svg> croaring <svg width=\"12\" height=\"12\"><rect \
+         class=\"roaring\" width=\"12\" height=\"12\"/></svg> roaring. Speedups above 1x \
+         favour roaring.</p>\n",
+    );
+
+    const ROW: f64 = 18.0;
+    const LABEL: f64 = 80.0;
+    for (title, cases) in charts(speedups) {
+        let height = cases.len() as f64 * ROW * 2.5;
+        writeln!(
+            out,
+            "<h2>{}</h2>\n<svg width=\"{}\" height=\"{height}\">",
+            escape(&title),
+            LABEL + SVG_BAR + 160.0,
+        )
+        .expect("writing to a string");
+        for (i, s) in cases.iter().enumerate() {
+            let y = i as f64 * ROW * 2.5;
+            writeln!(
+                out,
+                "<text x=\"0\" y=\"{}\">{}</text>",
+                y + ROW,
+                escape(s.n.as_deref().unwrap_or("-"))
+            )
+            .expect("writing to a string");
+            for (j, &(backend, ns)) in [("croaring", s.croaring_ns), ("roaring", s.roaring_ns)]
+                .iter()
+                .enumerate()
+            {
+                let y = y + j as f64 * ROW;
+                let width = (ns / longest(s) * SVG_BAR).max(1.0);
+                writeln!(
+                    out,
+                    "<rect class=\"{backend}\" x=\"{LABEL}\" y=\"{y}\" width=\"{width:.1}\" \
+                     height=\"{}\"><title>{backend}: {}</title></rect>\
+                     <text x=\"{:.1}\" y=\"{}\">{}</text>",
+                    ROW - 2.0,
+                    format_nanos(ns),
+                    LABEL + width + 4.0,
+                    y + ROW / 2.0,
+                    format_nanos(ns),
+                )
+                .expect("writing to a string");
+            }
+            writeln!(
+                out,
+                "<text x=\"{}\" y=\"{}\"{}>{:.2}x</text>",
+                LABEL + SVG_BAR + 100.0,
+                y + ROW,
+                if s.ratio() > 1.0 {
+                    " class=\"faster\""
+                } else {
+                    ""
+                },
+                s.ratio(),
+            )
+            .expect("writing to a string");
+        }
+        out.push_str("</svg>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// The larger median of `s`, which the bars of its case are scaled to.
+///
+/// The medians of a chart can span several orders of magnitude across its
+/// sizes, so each pair of bars is scaled on its own.
+fn longest(s: &Speedup) -> f64 {
+    s.croaring_ns.max(s.roaring_ns).max(f64::MIN_POSITIVE)
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCK: &str = r#"# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 3
+
+[[package]]
+name = "criterion"
+version = "0.3.6"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "b01d6de93b2b6c65e17c634a26653a29d107b3c98c607c765bf38d041531cd8f"
+dependencies = [
+ "atty",
+]
+
+[[package]]
+name = "croaring"
+version = "1.0.1"
+source = "git+https://github.com/RoaringBitmap/croaring-rs?branch=main#0123456789abcdef0123456789abcdef01234567"
+dependencies = [
+ "croaring-sys",
+]
+
+[[package]]
+name = "rand"
+version = "0.8.5"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+
+[[package]]
+name = "roaring"
+version = "0.1.0"
+dependencies = [
+ "croaring",
+ "roaring 0.10.2",
+]
+
+[[package]]
+name = "roaring"
+version = "0.10.2"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+"#;
+
+    #[test]
+    fn locked_versions_of_fixture() {
+        assert_eq!(
+            locked_versions(LOCK),
+            [
+                ("criterion".to_string(), "0.3.6".to_string()),
+                ("croaring".to_string(), "1.0.1 (git 01234567)".to_string()),
+                ("roaring".to_string(), "0.10.2".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn locked_versions_of_empty_lock() {
+        assert!(locked_versions("").is_empty());
+        assert!(locked_versions("version = 3\n").is_empty());
+    }
+}
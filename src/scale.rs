@@ -0,0 +1,152 @@
+//! The set sizes the benchmark groups are run against.
+//!
+//! [`DEFAULT_SIZES`] top out at a million values, within a few dozen
+//! containers. Set [`SIZES_ENV`] to a comma-separated list of sizes to run
+//! instead (such as `1000,1e7`), and [`LARGE_ENV`] (or pass [`LARGE_FLAG`]
+//! to the benchmark binary) to add the [`LARGE_SIZES`] tier, whose bitmaps
+//! span many thousands of containers up to the whole u32 range.
+//!
+//! The large tier needs several gigabytes of memory, and each of its cases
+//! takes seconds to run, so enabling it also drops the number of samples
+//! taken per case to [`LARGE_SAMPLE_SIZE`].
+
+use std::{env, ops::Deref, slice, sync::OnceLock};
+
+use crate::gen;
+
+/// The environment variable holding the sizes to run instead of
+/// [`DEFAULT_SIZES`].
+pub const SIZES_ENV: &str = "ROARING_BENCH_SIZES";
+
+/// The environment variable adding the [`LARGE_SIZES`] tier when set to
+/// anything other than `0`.
+pub const LARGE_ENV: &str = "ROARING_BENCH_LARGE";
+
+/// The command line flag equivalent of [`LARGE_ENV`], see
+/// [`split_large_flag`].
+pub const LARGE_FLAG: &str = "--large";
+
+/// The sizes run when [`SIZES_ENV`] is not set.
+pub const DEFAULT_SIZES: [u32; 5] = [10, 100, 1_000, 100_000, 1_000_000];
+
+/// The smallest size whose generated values, at [`gen::DEFAULT_DENSITY`],
+/// are sampled from the whole u32 range.
+pub const FULL_RANGE: u32 = (u32::MAX as f64 * gen::DEFAULT_DENSITY) as u32 + 1;
+
+/// The sizes added by [`LARGE_ENV`].
+pub const LARGE_SIZES: [u32; 3] = [10_000_000, 100_000_000, FULL_RANGE];
+
+/// The number of samples taken per case when the large tier is enabled,
+/// the least criterion accepts.
+pub const LARGE_SAMPLE_SIZE: usize = 10;
+
+/// A list of sizes read from the environment on first use.
+#[derive(Debug)]
+pub struct Sizes(OnceLock<Vec<u32>>);
+
+impl Sizes {
+    pub const fn new() -> Self {
+        Self(OnceLock::new())
+    }
+}
+
+impl Default for Sizes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Deref for Sizes {
+    type Target = [u32];
+
+    fn deref(&self) -> &[u32] {
+        self.0.get_or_init(sizes_from_env)
+    }
+}
+
+impl<'a> IntoIterator for &'a Sizes {
+    type Item = &'a u32;
+    type IntoIter = slice::Iter<'a, u32>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// The sizes set by [`SIZES_ENV`] (or [`DEFAULT_SIZES`]), followed by
+/// [`LARGE_SIZES`] if [`large_enabled`].
+///
+/// # Panics
+///
+/// Panics if [`SIZES_ENV`] is set to something other than a list of sizes.
+pub fn sizes_from_env() -> Vec<u32> {
+    let mut sizes = match env::var(SIZES_ENV) {
+        Ok(v) => v
+            .split(',')
+            .map(|s| parse_size(s).unwrap_or_else(|e| panic!("invalid {} value: {}", SIZES_ENV, e)))
+            .collect(),
+        Err(_) => DEFAULT_SIZES.to_vec(),
+    };
+    if large_enabled() {
+        sizes.extend(LARGE_SIZES);
+    }
+    sizes.sort_unstable();
+    sizes.dedup();
+    sizes
+}
+
+/// Returns true if [`LARGE_ENV`] is set to anything other than `0`.
+pub fn large_enabled() -> bool {
+    env::var(LARGE_ENV).is_ok_and(|v| v.trim() != "0")
+}
+
+/// Remove [`LARGE_FLAG`] from `args`, returning the remaining arguments, or
+/// `None` if it is not present.
+///
+/// Criterion rejects unknown flags, so the benchmark binary uses this to
+/// move the flag into [`LARGE_ENV`] before handing the rest to criterion.
+pub fn split_large_flag(args: &[String]) -> Option<Vec<String>> {
+    if !args.iter().any(|a| a == LARGE_FLAG) {
+        return None;
+    }
+    Some(args.iter().filter(|a| *a != LARGE_FLAG).cloned().collect())
+}
+
+/// Parse a size, accepting exponent notation such as `1e6`.
+pub fn parse_size(v: &str) -> Result<u32, String> {
+    match v.trim().parse::<f64>() {
+        Ok(n) if n.fract() == 0.0 && (0.0..=u32::MAX as f64).contains(&n) => Ok(n as u32),
+        _ => Err(format!("invalid size {v:?}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_size_known_values() {
+        assert_eq!(parse_size("0"), Ok(0));
+        assert_eq!(parse_size("1000"), Ok(1_000));
+        assert_eq!(parse_size(" 1e7 "), Ok(10_000_000));
+        assert_eq!(parse_size("2.5e3"), Ok(2_500));
+        assert_eq!(parse_size("4294967295"), Ok(u32::MAX));
+    }
+
+    #[test]
+    fn parse_size_rejects_out_of_range() {
+        for v in [
+            "1.5",
+            "-1",
+            "5e9",
+            "4294967296",
+            "1e-3",
+            "NaN",
+            "inf",
+            "",
+            "large",
+        ] {
+            assert!(parse_size(v).is_err(), "{:?}", v);
+        }
+    }
+}
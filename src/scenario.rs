@@ -0,0 +1,436 @@
+//! Scenario files describing a benchmark matrix for [`run_suite`], so a
+//! comparison can be reproduced from a small file instead of a list of
+//! command line flags.
+//!
+//! A scenario is a TOML document of top-level keys, each optional and
+//! defaulting to the [`SuiteConfig`] default:
+//!
+//! ```toml
+//! # The union of zipfian sets, at two sizes and densities.
+//! operations = ["union_no_rle", "union_with_rle"]
+//! libraries = ["croaring", "roaring"]
+//! sizes = [1_000, 1e6]
+//! distributions = ["zipfian"]
+//! densities = [0.01, 1.0]
+//! iterations = 10
+//! samples = 5
+//! measurement_time = 2.5  # seconds per case, overriding iterations
+//! large = false           # add scale::LARGE_SIZES
+//! ```
+//!
+//! Names are matched exactly against those printed by `roaring-bench list`,
+//! a single value may stand in for a list of one, and an empty (or absent)
+//! `distributions` runs evenly spaced input. Only the subset of TOML above
+//! is understood (strings, numbers, booleans and arrays of them) - tables
+//! are rejected - which is all a scenario needs, and is parsed here rather
+//! than through a full TOML parser.
+//!
+//! [`run_suite`]: crate::run_suite
+
+use std::{fs, io, path::Path, slice, str::FromStr, time::Duration};
+
+use crate::{scale, SuiteConfig};
+
+/// The keys a scenario may set.
+pub const KEYS: [&str; 9] = [
+    "operations",
+    "libraries",
+    "sizes",
+    "distributions",
+    "densities",
+    "iterations",
+    "samples",
+    "measurement_time",
+    "large",
+];
+
+/// Read the scenario file at `path`.
+pub fn load(path: impl AsRef<Path>) -> io::Result<SuiteConfig> {
+    let path = path.as_ref();
+    let src = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))?;
+    parse(&src).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })
+}
+
+/// Parse a scenario, returning the [`SuiteConfig`] it describes.
+pub fn parse(src: &str) -> Result<SuiteConfig, String> {
+    let mut config = SuiteConfig::default();
+    let mut large = false;
+    for (key, value) in Parser::new(src).entries()? {
+        let with_key = |e: String| format!("{key}: {e}");
+        match key.as_str() {
+            "operations" => config.operations = non_empty(names(&value)).map_err(with_key)?,
+            "libraries" => config.backends = non_empty(names(&value)).map_err(with_key)?,
+            "distributions" => config.distributions = names(&value).map_err(with_key)?,
+            "sizes" => {
+                config.sizes = non_empty(
+                    value
+                        .list()
+                        .iter()
+                        .map(|v| scale::parse_size(v.number()?))
+                        .collect(),
+                )
+                .map_err(with_key)?;
+            }
+            "densities" => {
+                config.densities = non_empty(
+                    value
+                        .list()
+                        .iter()
+                        .map(|v| match v.number()?.parse::<f64>() {
+                            Ok(d) if d > 0.0 && d <= 1.0 => Ok(d),
+                            _ => Err(format!("invalid density {v}")),
+                        })
+                        .collect(),
+                )
+                .map_err(with_key)?;
+            }
+            "iterations" => config.iterations = count(&value).map_err(with_key)?,
+            "samples" => config.samples = count(&value).map_err(with_key)?,
+            "measurement_time" => {
+                let secs = match value.number().map(str::parse::<f64>) {
+                    Ok(Ok(secs)) if secs > 0.0 => secs,
+                    _ => return Err(with_key(format!("invalid number of seconds {value}"))),
+                };
+                config.measurement_time = Some(Duration::from_secs_f64(secs));
+            }
+            "large" => large = value.bool().map_err(with_key)?,
+            _ => {
+                return Err(format!(
+                    "unknown key {key:?}, expected one of: {}",
+                    KEYS.join(", ")
+                ))
+            }
+        }
+    }
+    if large {
+        config.sizes.extend(scale::LARGE_SIZES);
+        config.sizes.sort_unstable();
+        config.sizes.dedup();
+    }
+    Ok(config)
+}
+
+/// The items named by the strings of `value`.
+fn names<T: FromStr<Err = String>>(value: &Value) -> Result<Vec<T>, String> {
+    value.list().iter().map(|v| v.string()?.parse()).collect()
+}
+
+fn non_empty<T>(list: Result<Vec<T>, String>) -> Result<Vec<T>, String> {
+    list.and_then(|l| {
+        if l.is_empty() {
+            return Err("must not be empty".into());
+        }
+        Ok(l)
+    })
+}
+
+fn count(value: &Value) -> Result<u32, String> {
+    match value.number()?.parse() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("invalid count {value}")),
+    }
+}
+
+/// A value of the TOML subset understood by [`Parser`].
+#[derive(Debug, Clone, PartialEq)]
+enum Value {
+    String(String),
+
+    /// The text of a number, without its digit separators, parsed once the
+    /// type its key expects is known.
+    Number(String),
+    Bool(bool),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    /// The items of an array, or a list of only this value otherwise.
+    fn list(&self) -> &[Value] {
+        match self {
+            Value::Array(values) => values,
+            v => slice::from_ref(v),
+        }
+    }
+
+    fn string(&self) -> Result<&str, String> {
+        match self {
+            Value::String(s) => Ok(s),
+            v => Err(format!("expected a string, found {v}")),
+        }
+    }
+
+    fn number(&self) -> Result<&str, String> {
+        match self {
+            Value::Number(n) => Ok(n),
+            v => Err(format!("expected a number, found {v}")),
+        }
+    }
+
+    fn bool(&self) -> Result<bool, String> {
+        match self {
+            Value::Bool(b) => Ok(*b),
+            v => Err(format!("expected true or false, found {v}")),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(s) => write!(f, "{s:?}"),
+            Value::Number(n) => f.write_str(n),
+            Value::Bool(b) => write!(f, "{b}"),
+            Value::Array(values) => {
+                f.write_str("[")?;
+                for (i, v) in values.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{v}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// A parser of `key = value` lines, with comments and multi-line arrays.
+struct Parser<'a> {
+    src: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(src: &'a str) -> Self {
+        Self { src, pos: 0 }
+    }
+
+    /// Every `(key, value)` of the document, in order.
+    fn entries(mut self) -> Result<Vec<(String, Value)>, String> {
+        let mut entries: Vec<(String, Value)> = Vec::new();
+        loop {
+            self.skip(true);
+            match self.peek() {
+                None => return Ok(entries),
+                Some('[') => return Err(self.error("tables are not supported")),
+                Some(_) => {}
+            }
+            let key = self.word();
+            if key.is_empty() {
+                return Err(self.error("expected a key"));
+            }
+            if entries.iter().any(|(k, _)| *k == key) {
+                return Err(self.error(&format!("duplicate key {key:?}")));
+            }
+            self.skip(false);
+            if self.bump() != Some('=') {
+                return Err(self.error(&format!("expected = after {key}")));
+            }
+            self.skip(false);
+            let value = self.value()?;
+            self.skip(false);
+            if !matches!(self.peek(), None | Some('\n' | '\r')) {
+                return Err(self.error(&format!("expected a new line after the value of {key}")));
+            }
+            entries.push((key, value));
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        match self.peek() {
+            Some('"') => self.string(),
+            Some('[') => self.array(),
+            Some(c) if c.is_ascii_digit() || "+-.".contains(c) => Ok(Value::Number(
+                self.word().chars().filter(|&c| c != '_').collect(),
+            )),
+            Some(_) => match self.word().as_str() {
+                "true" => Ok(Value::Bool(true)),
+                "false" => Ok(Value::Bool(false)),
+                _ => Err(self.error("expected a string, number, boolean or array")),
+            },
+            None => Err(self.error("expected a value")),
+        }
+    }
+
+    fn string(&mut self) -> Result<Value, String> {
+        self.bump();
+        let mut s = String::new();
+        loop {
+            match self.bump() {
+                Some('"') => return Ok(Value::String(s)),
+                Some('\\') => match self.bump() {
+                    Some('"') => s.push('"'),
+                    Some('\\') => s.push('\\'),
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    _ => return Err(self.error("unsupported escape in string")),
+                },
+                Some('\n') | None => return Err(self.error("unterminated string")),
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.bump();
+        let mut values = Vec::new();
+        loop {
+            self.skip(true);
+            if self.peek() == Some(']') {
+                self.bump();
+                return Ok(Value::Array(values));
+            }
+            values.push(self.value()?);
+            self.skip(true);
+            match self.bump() {
+                Some(',') => {}
+                Some(']') => return Ok(Value::Array(values)),
+                _ => return Err(self.error("expected , or ] in array")),
+            }
+        }
+    }
+
+    /// The bare key, number or keyword at the cursor.
+    fn word(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || "_-+.".contains(c))
+        {
+            self.bump();
+        }
+        self.src[start..self.pos].to_string()
+    }
+
+    /// Skip spaces and comments, and new lines too if `newlines`.
+    fn skip(&mut self, newlines: bool) {
+        loop {
+            match self.peek() {
+                Some(' ' | '\t') => {}
+                Some('\n' | '\r') if newlines => {}
+                Some('#') => {
+                    while !matches!(self.peek(), None | Some('\n')) {
+                        self.bump();
+                    }
+                    continue;
+                }
+                _ => return,
+            }
+            self.bump();
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
+    }
+
+    /// An error at the line of the cursor.
+    fn error(&self, msg: &str) -> String {
+        let line = self.src[..self.pos].matches('\n').count() + 1;
+        format!("line {line}: {msg}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gen::Distribution, Backend, Operation};
+
+    #[test]
+    fn parses_every_key() {
+        let backend = Backend::ALL[0];
+        let config = parse(&format!(
+            r#"
+# A comment line.
+operations = ["union_no_rle", "add_range"]  # a trailing comment
+libraries = "{}"
+sizes = [
+    1_000,
+    1e6,   # exponent notation
+]
+distributions = ["zipfian"]
+densities = [0.01, 1.0]
+iterations = 3
+samples = 7
+measurement_time = 2.5
+large = true
+"#,
+            backend.name()
+        ))
+        .unwrap();
+
+        assert_eq!(
+            config.operations,
+            [Operation::UnionNoRle, Operation::AddRange]
+        );
+        assert_eq!(config.backends, [backend]);
+        let mut sizes = vec![1_000, 1_000_000];
+        sizes.extend(scale::LARGE_SIZES);
+        assert_eq!(config.sizes, sizes);
+        assert_eq!(config.distributions, [Distribution::Zipfian]);
+        assert_eq!(config.densities, [0.01, 1.0]);
+        assert_eq!(config.iterations, 3);
+        assert_eq!(config.samples, 7);
+        assert_eq!(config.measurement_time, Some(Duration::from_secs_f64(2.5)));
+    }
+
+    #[test]
+    fn absent_keys_keep_defaults() {
+        let config = parse("# nothing but a comment\n").unwrap();
+        let default = SuiteConfig::default();
+        assert_eq!(config.operations, default.operations);
+        assert_eq!(config.sizes, default.sizes);
+        assert!(config.distributions.is_empty());
+        assert_eq!(config.measurement_time, None);
+    }
+
+    #[test]
+    fn rejects_unknown_key() {
+        let err = parse("threads = 4\n").unwrap_err();
+        assert!(err.starts_with("unknown key \"threads\""), "{}", err);
+    }
+
+    #[test]
+    fn rejects_bad_values() {
+        for (src, expected) in [
+            ("iterations = 0", "iterations: invalid count 0"),
+            ("samples = \"5\"", "samples: expected a number"),
+            ("densities = [2.0]", "densities: invalid density 2.0"),
+            ("measurement_time = -1", "measurement_time: invalid number"),
+            ("large = yes", "line 1: expected a string, number"),
+            ("operations = []", "operations: must not be empty"),
+            ("operations = [\"nope\"]", "operations: "),
+            ("sizes = [1.5]", "sizes: invalid size"),
+        ] {
+            let err = parse(src).unwrap_err();
+            assert!(err.starts_with(expected), "{}: {}", src, err);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        for (src, expected) in [
+            ("iterations 3", "line 1: expected = after iterations"),
+            ("iterations = 3 4", "line 1: expected a new line"),
+            ("\n[table]", "line 2: tables are not supported"),
+            ("samples = 1\nsamples = 2", "line 2: duplicate key"),
+            ("libraries = [\"roaring\"", "line 1: expected , or ]"),
+            ("libraries = \"roaring", "line 1: unterminated string"),
+        ] {
+            let err = parse(src).unwrap_err();
+            assert!(err.starts_with(expected), "{}: {}", src, err);
+        }
+    }
+}
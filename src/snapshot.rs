@@ -0,0 +1,32 @@
+//! Publication of immutable snapshots to concurrent readers.
+
+use std::sync::{Arc, RwLock};
+
+/// A swappable shared pointer, standing in for `arc_swap::ArcSwap`.
+///
+/// Readers take a cheap reference-counted handle to the current value, and
+/// the writer publishes a new value by replacing the pointer. The lock is
+/// only held for the duration of the pointer copy, never while the value is
+/// in use.
+#[derive(Debug, Default)]
+pub struct SnapshotCell<T> {
+    current: RwLock<Arc<T>>,
+}
+
+impl<T> SnapshotCell<T> {
+    pub fn new(value: T) -> Self {
+        Self {
+            current: RwLock::new(Arc::new(value)),
+        }
+    }
+
+    /// Return a handle to the most recently published value.
+    pub fn load(&self) -> Arc<T> {
+        Arc::clone(&self.current.read().unwrap())
+    }
+
+    /// Publish `value`, replacing the current value for subsequent loads.
+    pub fn store(&self, value: Arc<T>) {
+        *self.current.write().unwrap() = value;
+    }
+}
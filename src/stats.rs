@@ -0,0 +1,212 @@
+//! Statistical tests over criterion's raw samples.
+
+/// The outcome of a two one-sided tests (TOST) equivalence test.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tost {
+    /// The difference in means, `a - b`.
+    pub difference: f64,
+
+    /// The equivalence margin, in the same units as the samples.
+    pub margin: f64,
+
+    /// The larger of the two one-sided p-values. The samples are
+    /// equivalent within the margin at significance level alpha if this is
+    /// below alpha.
+    pub p_value: f64,
+}
+
+/// Test whether the means of `a` and `b` differ by less than `margin`,
+/// using Welch's t-test for each of the two one-sided hypotheses.
+///
+/// Returns `None` if either has fewer than two samples.
+pub fn tost(a: &[f64], b: &[f64], margin: f64) -> Option<Tost> {
+    if a.len() < 2 || b.len() < 2 {
+        return None;
+    }
+    let (mean_a, var_a) = mean_var(a);
+    let (mean_b, var_b) = mean_var(b);
+    let (se_a, se_b) = (var_a / a.len() as f64, var_b / b.len() as f64);
+    let difference = mean_a - mean_b;
+
+    let se = (se_a + se_b).sqrt();
+    let p_value = if se == 0.0 {
+        if difference.abs() < margin {
+            0.0
+        } else {
+            1.0
+        }
+    } else {
+        // Welch-Satterthwaite degrees of freedom.
+        let df = (se_a + se_b).powi(2)
+            / (se_a.powi(2) / (a.len() - 1) as f64 + se_b.powi(2) / (b.len() - 1) as f64);
+        let p_lower = 1.0 - student_t_cdf((difference + margin) / se, df);
+        let p_upper = student_t_cdf((difference - margin) / se, df);
+        p_lower.max(p_upper)
+    };
+
+    Some(Tost {
+        difference,
+        margin,
+        p_value,
+    })
+}
+
+/// The sample mean and (unbiased) variance of `v`.
+fn mean_var(v: &[f64]) -> (f64, f64) {
+    let n = v.len() as f64;
+    let mean = v.iter().sum::<f64>() / n;
+    let var = v.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, var)
+}
+
+/// The cumulative distribution function of Student's t-distribution with
+/// `df` degrees of freedom.
+pub fn student_t_cdf(t: f64, df: f64) -> f64 {
+    let tail = 0.5 * incomplete_beta(df / 2.0, 0.5, df / (df + t * t));
+    if t > 0.0 {
+        1.0 - tail
+    } else {
+        tail
+    }
+}
+
+/// The regularized incomplete beta function `I_x(a, b)`.
+fn incomplete_beta(a: f64, b: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 0.0;
+    }
+    if x >= 1.0 {
+        return 1.0;
+    }
+    let front =
+        (ln_gamma(a + b) - ln_gamma(a) - ln_gamma(b) + a * x.ln() + b * (1.0 - x).ln()).exp();
+    // The continued fraction converges quickly for x < (a + 1) / (a + b + 2),
+    // so use the symmetry I_x(a, b) = 1 - I_{1-x}(b, a) otherwise.
+    if x < (a + 1.0) / (a + b + 2.0) {
+        front * beta_continued_fraction(a, b, x) / a
+    } else {
+        1.0 - front * beta_continued_fraction(b, a, 1.0 - x) / b
+    }
+}
+
+/// Evaluate the continued fraction of the incomplete beta function with
+/// the modified Lentz method.
+fn beta_continued_fraction(a: f64, b: f64, x: f64) -> f64 {
+    const TINY: f64 = 1e-300;
+    const EPSILON: f64 = 1e-14;
+
+    let clamp = |v: f64| if v.abs() < TINY { TINY } else { v };
+    let mut c = 1.0;
+    let mut d = 1.0 / clamp(1.0 - (a + b) * x / (a + 1.0));
+    let mut h = d;
+    for m in 1..300 {
+        let m = m as f64;
+        let m2 = 2.0 * m;
+
+        let aa = m * (b - m) * x / ((a + m2 - 1.0) * (a + m2));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        h *= d * c;
+
+        let aa = -(a + m) * (a + b + m) * x / ((a + m2) * (a + m2 + 1.0));
+        d = 1.0 / clamp(1.0 + aa * d);
+        c = clamp(1.0 + aa / c);
+        let delta = d * c;
+        h *= delta;
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+    h
+}
+
+/// The natural log of the gamma function, by the Lanczos approximation
+/// (g = 7, n = 9).
+fn ln_gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+    if x < 0.5 {
+        // Reflection formula.
+        let pi = std::f64::consts::PI;
+        return (pi / (pi * x).sin()).ln() - ln_gamma(1.0 - x);
+    }
+    let x = x - 1.0;
+    let t = x + 7.5;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| {
+            acc + c / (x + i as f64 + 1.0)
+        });
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    #[test]
+    fn student_t_cdf_quantiles() {
+        // (df, t, CDF) from published t tables.
+        for &(df, t, p) in &[
+            (1.0, 1.0, 0.75),
+            (1.0, 6.313_752, 0.95),
+            (1.0, 12.706_205, 0.975),
+            (10.0, 1.812_461, 0.95),
+            (10.0, 2.228_139, 0.975),
+            (10.0, 3.169_273, 0.995),
+            (30.0, 1.697_261, 0.95),
+            (30.0, 2.042_272, 0.975),
+            (30.0, 2.749_996, 0.995),
+        ] {
+            assert_close(student_t_cdf(t, df), p, 1e-6);
+            assert_close(student_t_cdf(-t, df), 1.0 - p, 1e-6);
+        }
+        assert_close(student_t_cdf(0.0, 10.0), 0.5, 1e-12);
+    }
+
+    #[test]
+    fn special_functions() {
+        assert_close(ln_gamma(5.0), 24f64.ln(), 1e-10);
+        assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-10);
+        assert_close(incomplete_beta(1.0, 1.0, 0.3), 0.3, 1e-10);
+        assert_close(incomplete_beta(2.5, 2.5, 0.5), 0.5, 1e-10);
+        assert_eq!(incomplete_beta(2.0, 3.0, 0.0), 0.0);
+        assert_eq!(incomplete_beta(2.0, 3.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn tost_verdicts() {
+        let a = [100.0, 101.0, 99.0, 100.5, 99.5, 100.0, 100.2, 99.8];
+        let near = a.iter().map(|v| v + 0.2).collect::<Vec<_>>();
+        let far = a.iter().map(|v| v + 5.0).collect::<Vec<_>>();
+
+        let equivalent = tost(&a, &near, 2.0).unwrap();
+        assert_close(equivalent.difference, -0.2, 1e-9);
+        assert!(equivalent.p_value < 0.001, "{:?}", equivalent);
+
+        let different = tost(&a, &far, 2.0).unwrap();
+        assert!(different.p_value > 0.999, "{:?}", different);
+
+        assert_eq!(tost(&a[..1], &near, 2.0), None);
+    }
+}
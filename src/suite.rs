@@ -0,0 +1,437 @@
+#[cfg(feature = "roaring")]
+use std::ops::BitOr;
+use std::{
+    cell::Cell,
+    fmt,
+    hint::black_box,
+    str::FromStr,
+    time::{Duration, Instant},
+};
+
+use rand::prelude::SliceRandom;
+#[cfg(feature = "roaring")]
+use roaring::RoaringBitmap;
+
+use crate::{
+    allocator,
+    gen::{self, Distribution},
+    N,
+};
+
+/// A bitmap implementation under test, present when its cargo feature is
+/// enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Backend {
+    #[cfg(feature = "croaring")]
+    CRoaring,
+    #[cfg(feature = "roaring")]
+    Roaring,
+}
+
+impl Backend {
+    pub const ALL: &'static [Backend] = &[
+        #[cfg(feature = "croaring")]
+        Backend::CRoaring,
+        #[cfg(feature = "roaring")]
+        Backend::Roaring,
+    ];
+
+    /// The name used for this backend in the criterion `BenchmarkId`.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "croaring")]
+            Backend::CRoaring => "croaring",
+            #[cfg(feature = "roaring")]
+            Backend::Roaring => "roaring",
+        }
+    }
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|b| b.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown backend {s:?}"))
+    }
+}
+
+/// An operation measured by [`run_suite`].
+///
+/// Each variant mirrors the criterion benchmark group of the same name, the
+/// unions that of their disjoint operands (`union_no_rle_overlap_0`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Operation {
+    AddSequential,
+    AddShuffled,
+    AddRange,
+    CollectUint,
+    UnionNoRle,
+    UnionWithRle,
+}
+
+impl Operation {
+    pub const ALL: [Operation; 6] = [
+        Operation::AddSequential,
+        Operation::AddShuffled,
+        Operation::AddRange,
+        Operation::CollectUint,
+        Operation::UnionNoRle,
+        Operation::UnionWithRle,
+    ];
+
+    /// The criterion benchmark group name for this operation.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operation::AddSequential => "add_elements_sequential",
+            Operation::AddShuffled => "add_elements_shuffled",
+            Operation::AddRange => "add_range",
+            Operation::CollectUint => "collect_uint",
+            Operation::UnionNoRle => "union_no_rle",
+            Operation::UnionWithRle => "union_with_rle",
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Operation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .iter()
+            .find(|o| o.name() == s)
+            .copied()
+            .ok_or_else(|| format!("unknown operation {s:?}"))
+    }
+}
+
+/// Selects what [`run_suite`] measures.
+#[derive(Debug, Clone)]
+pub struct SuiteConfig {
+    pub operations: Vec<Operation>,
+    pub backends: Vec<Backend>,
+    pub sizes: Vec<u32>,
+
+    /// The fraction of the value universe populated, see [`spaced_values`].
+    pub densities: Vec<f64>,
+
+    /// The distributions the input is drawn from at each density. When
+    /// empty, the input is evenly spaced instead, see [`spaced_values`].
+    pub distributions: Vec<Distribution>,
+
+    /// The number of times the operation is executed per measurement.
+    pub iterations: u32,
+
+    /// The number of measurements taken per case, of which the fastest is
+    /// reported.
+    pub samples: u32,
+
+    /// When set, the total time spent measuring each case, overriding
+    /// `iterations` with however many fill an equal share of it per sample.
+    pub measurement_time: Option<Duration>,
+}
+
+impl Default for SuiteConfig {
+    fn default() -> Self {
+        Self {
+            operations: Operation::ALL.to_vec(),
+            backends: Backend::ALL.to_vec(),
+            sizes: N.to_vec(),
+            densities: vec![1.0],
+            distributions: Vec::new(),
+            iterations: 10,
+            samples: 5,
+            measurement_time: None,
+        }
+    }
+}
+
+/// The measured cost of a single (operation, backend, size, density) case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchResult {
+    pub operation: Operation,
+    pub backend: Backend,
+    pub size: u32,
+    pub density: f64,
+
+    /// The distribution the input was drawn from, or `None` if it was evenly
+    /// spaced.
+    pub distribution: Option<Distribution>,
+    pub iterations: u32,
+
+    /// The name of the global allocator the case was measured under.
+    pub allocator: &'static str,
+
+    /// The mean wall time of one execution of the operation in the fastest
+    /// of the samples taken.
+    pub per_iteration: Duration,
+}
+
+impl BenchResult {
+    /// The throughput of this case, in input elements per second.
+    pub fn elements_per_second(&self) -> f64 {
+        self.size as f64 / self.per_iteration.as_secs_f64()
+    }
+}
+
+/// Run every case selected by `config` and return the results in
+/// (operation, distribution, density, size, backend) order.
+///
+/// Unlike the criterion benchmarks this performs no warm-up or statistical
+/// analysis - it is intended for embedding and smoke testing, not for
+/// publishing numbers.
+pub fn run_suite(config: &SuiteConfig) -> Vec<BenchResult> {
+    let distributions = match config.distributions.as_slice() {
+        [] => vec![None],
+        d => d.iter().copied().map(Some).collect(),
+    };
+
+    let mut results = Vec::new();
+    for &operation in &config.operations {
+        for &distribution in &distributions {
+            for &density in &config.densities {
+                for &size in &config.sizes {
+                    for &backend in &config.backends {
+                        results.push(run_case(
+                            config,
+                            operation,
+                            backend,
+                            size,
+                            density,
+                            distribution,
+                        ));
+                    }
+                }
+            }
+        }
+    }
+    results
+}
+
+/// Measure a single case using the iteration and sample counts of `config`,
+/// ignoring its operation, backend, size, density and distribution
+/// selection.
+///
+/// The input is drawn from `distribution` when given, and is otherwise
+/// evenly spaced.
+pub fn run_case(
+    config: &SuiteConfig,
+    operation: Operation,
+    backend: Backend,
+    size: u32,
+    density: f64,
+    distribution: Option<Distribution>,
+) -> BenchResult {
+    let timer = Timer {
+        iterations: Cell::new(config.iterations.max(1)),
+        samples: config.samples.max(1),
+        measurement_time: config.measurement_time,
+    };
+    let values = match distribution {
+        Some(d) => {
+            let mut rng = gen::rng(&format!("suite/{operation}/{d}/{size}/{density}"));
+            gen::generate(d, size, density, &mut rng)
+        }
+        None => spaced_values(size, density),
+    };
+    let per_iteration = measure(operation, backend, size, values, &timer);
+    BenchResult {
+        operation,
+        backend,
+        size,
+        density,
+        distribution,
+        iterations: timer.iterations.get(),
+        allocator: allocator::NAME,
+        per_iteration,
+    }
+}
+
+/// Generate `size` values evenly spaced over a universe of `size / density`.
+///
+/// A density of 1 yields the contiguous range `0..size`. The universe is
+/// capped at `u32::MAX`, so very low densities at large sizes saturate.
+pub fn spaced_values(size: u32, density: f64) -> Vec<u32> {
+    if density >= 1.0 {
+        return (0..size).collect();
+    }
+    let stride = 1.0 / density.max(f64::MIN_POSITIVE);
+    (0..size).map(|i| (i as f64 * stride) as u32).collect()
+}
+
+/// Perform any setup for `operation` over `values` and return the
+/// per-iteration duration measured by `timer`.
+fn measure(
+    operation: Operation,
+    backend: Backend,
+    size: u32,
+    values: Vec<u32>,
+    timer: &Timer,
+) -> Duration {
+    // Splits of the input into two operand sets: alternating values, and the
+    // low and high halves.
+    let alternating =
+        || -> (Vec<_>, Vec<_>) { values.iter().enumerate().partition(|(i, _)| i % 2 == 0) };
+    let halves = || values.split_at(values.len() / 2);
+
+    match (operation, backend) {
+        #[cfg(feature = "croaring")]
+        (Operation::AddSequential, Backend::CRoaring) => {
+            let mut bm = croaring::Bitmap::create();
+            timer.time(|| {
+                for &i in &values {
+                    bm.add(i);
+                }
+            })
+        }
+        #[cfg(feature = "roaring")]
+        (Operation::AddSequential, Backend::Roaring) => {
+            let mut bm = RoaringBitmap::new();
+            timer.time(|| {
+                for &i in &values {
+                    bm.insert(i);
+                }
+            })
+        }
+        (Operation::AddShuffled, backend) => {
+            let mut shuffled = values.clone();
+            shuffled.shuffle(&mut gen::rng(operation.name()));
+            match backend {
+                #[cfg(feature = "croaring")]
+                Backend::CRoaring => {
+                    let mut bm = croaring::Bitmap::create();
+                    timer.time(|| {
+                        for i in &shuffled {
+                            bm.add(*i);
+                        }
+                    })
+                }
+                #[cfg(feature = "roaring")]
+                Backend::Roaring => {
+                    let mut bm = RoaringBitmap::new();
+                    timer.time(|| {
+                        for i in &shuffled {
+                            bm.insert(*i);
+                        }
+                    })
+                }
+            }
+        }
+        // Range inserts are inherently contiguous, so ignore the density.
+        #[cfg(feature = "croaring")]
+        (Operation::AddRange, Backend::CRoaring) => {
+            let mut bm = croaring::Bitmap::create();
+            timer.time(|| bm.add_range(0..size))
+        }
+        #[cfg(feature = "roaring")]
+        (Operation::AddRange, Backend::Roaring) => {
+            let mut bm = RoaringBitmap::new();
+            timer.time(|| {
+                bm.insert_range(0..size);
+            })
+        }
+        #[cfg(feature = "croaring")]
+        (Operation::CollectUint, Backend::CRoaring) => {
+            let bm = croaring::Bitmap::of(&values);
+            timer.time(|| {
+                let _: Vec<u32> = bm.iter().collect();
+            })
+        }
+        #[cfg(feature = "roaring")]
+        (Operation::CollectUint, Backend::Roaring) => {
+            let bm = values.iter().collect::<RoaringBitmap>();
+            timer.time(|| {
+                let _: Vec<u32> = bm.iter().collect();
+            })
+        }
+        #[cfg(feature = "croaring")]
+        (Operation::UnionNoRle, Backend::CRoaring) => {
+            let (a, b): (Vec<_>, Vec<_>) = alternating();
+            let set_a = a.into_iter().map(|(_, v)| *v).collect::<croaring::Bitmap>();
+            let set_b = b.into_iter().map(|(_, v)| *v).collect::<croaring::Bitmap>();
+            timer.time(|| {
+                black_box(set_a.or(&set_b));
+            })
+        }
+        #[cfg(feature = "roaring")]
+        (Operation::UnionNoRle, Backend::Roaring) => {
+            let (a, b): (Vec<_>, Vec<_>) = alternating();
+            let set_a = a.into_iter().map(|(_, v)| *v).collect::<RoaringBitmap>();
+            let set_b = b.into_iter().map(|(_, v)| *v).collect::<RoaringBitmap>();
+            timer.time(|| {
+                black_box((&set_a).bitor(&set_b));
+            })
+        }
+        #[cfg(feature = "croaring")]
+        (Operation::UnionWithRle, Backend::CRoaring) => {
+            let (a, b) = halves();
+            let (mut set_a, mut set_b) = (croaring::Bitmap::of(a), croaring::Bitmap::of(b));
+            set_a.run_optimize();
+            set_b.run_optimize();
+            timer.time(|| {
+                black_box(set_a.or(&set_b));
+            })
+        }
+        #[cfg(feature = "roaring")]
+        (Operation::UnionWithRle, Backend::Roaring) => {
+            let (a, b) = halves();
+            let set_a = a.iter().collect::<RoaringBitmap>();
+            let set_b = b.iter().collect::<RoaringBitmap>();
+            timer.time(|| {
+                black_box((&set_a).bitor(&set_b));
+            })
+        }
+    }
+}
+
+/// A fixed-iteration, min-of-k timer.
+///
+/// Taking the minimum of several samples discards most of the scheduling
+/// and cache noise that a single sample picks up, without the cost of
+/// criterion's statistical analysis.
+struct Timer {
+    /// Recalibrated by [`Timer::time`] when a measurement time is set.
+    iterations: Cell<u32>,
+    samples: u32,
+    measurement_time: Option<Duration>,
+}
+
+impl Timer {
+    fn time<F: FnMut()>(&self, mut f: F) -> Duration {
+        if let Some(total) = self.measurement_time {
+            let start = Instant::now();
+            f();
+            let once = start.elapsed().as_secs_f64().max(1e-9);
+            let share = total.as_secs_f64() / self.samples as f64;
+            self.iterations
+                .set((share / once).clamp(1.0, u32::MAX as f64) as u32);
+        }
+
+        let iterations = self.iterations.get();
+        (0..self.samples)
+            .map(|_| {
+                let start = Instant::now();
+                for _ in 0..iterations {
+                    f();
+                }
+                start.elapsed() / iterations
+            })
+            .min()
+            .expect("at least one sample")
+    }
+}
@@ -0,0 +1,115 @@
+//! Backend comparisons derived from criterion results.
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{estimates::Measurement, stats};
+
+/// The significance level of the equivalence test.
+pub const EQUIVALENCE_ALPHA: f64 = 0.05;
+
+/// The outcome of comparing the two fastest backends for one case.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    /// The fastest backend is faster than the runner-up by `ratio`, and their
+    /// confidence intervals do not overlap.
+    Faster { ratio: f64 },
+
+    /// The confidence intervals overlap, so any difference in the point
+    /// estimates is within the measurement noise.
+    NoSignificantDifference,
+
+    /// A TOST equivalence test showed the backends' means differ by less
+    /// than `margin` (a fraction of the fastest backend's time), with
+    /// p-value `p_value`.
+    ///
+    /// Unlike [`Verdict::NoSignificantDifference`], which only says that no
+    /// difference was detected, this is positive evidence that the backends
+    /// perform the same for practical purposes.
+    Equivalent { margin: f64, p_value: f64 },
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Faster { ratio } => write!(f, "{ratio:.2}x faster"),
+            Verdict::NoSignificantDifference => f.write_str("no significant difference"),
+            Verdict::Equivalent { margin, p_value } => {
+                write!(
+                    f,
+                    "equivalent within ±{:.1}% (p = {p_value:.3})",
+                    margin * 100.0
+                )
+            }
+        }
+    }
+}
+
+/// A comparison of the two fastest backends for a single (group, value)
+/// case.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    pub group: String,
+    pub value: Option<String>,
+
+    /// The backend with the lowest typical time, and that time in ns.
+    pub fastest: (String, f64),
+
+    /// The next fastest backend, and its typical time in ns.
+    pub runner_up: (String, f64),
+
+    pub verdict: Verdict,
+}
+
+/// Compare the fastest two backends of every case in `measurements` that
+/// was run against more than one backend.
+///
+/// Measurements recording different input datasets are never compared with
+/// each other.
+///
+/// If `equivalence` is given, the two backends are first tested for
+/// equivalence within that fraction of the fastest backend's time (using
+/// criterion's raw samples), and reported as [`Verdict::Equivalent`] if the
+/// test passes at [`EQUIVALENCE_ALPHA`].
+pub fn compare(measurements: &[Measurement], equivalence: Option<f64>) -> Vec<Comparison> {
+    let mut cases: BTreeMap<_, Vec<&Measurement>> = BTreeMap::new();
+    for m in measurements {
+        cases
+            .entry((&m.group, &m.value, &m.dataset))
+            .or_default()
+            .push(m);
+    }
+
+    cases
+        .into_iter()
+        .filter(|(_, ms)| ms.len() > 1)
+        .map(|((group, value, _), mut ms)| {
+            ms.sort_by(|a, b| a.typical().point.total_cmp(&b.typical().point));
+            let (fastest, runner_up) = (ms[0], ms[1]);
+
+            let tost = equivalence.and_then(|margin| {
+                let margin_ns = margin * fastest.typical().point;
+                let t = stats::tost(&fastest.samples, &runner_up.samples, margin_ns)?;
+                Some((margin, t.p_value))
+            });
+
+            let verdict =
+                if let Some((margin, p_value)) = tost.filter(|&(_, p)| p < EQUIVALENCE_ALPHA) {
+                    Verdict::Equivalent { margin, p_value }
+                } else if fastest.typical().overlaps(runner_up.typical()) {
+                    Verdict::NoSignificantDifference
+                } else {
+                    Verdict::Faster {
+                        ratio: runner_up.typical().point / fastest.typical().point,
+                    }
+                };
+
+            Comparison {
+                group: group.clone(),
+                value: value.clone(),
+                fastest: (fastest.function.clone(), fastest.typical().point),
+                runner_up: (runner_up.function.clone(), runner_up.typical().point),
+                verdict,
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,180 @@
+//! Micro-benchmarks of roaring that run on WebAssembly, where croaring (a C
+//! library) and criterion are not available, reporting in the CSV schema of
+//! `roaring-bench export` for comparison with a native run.
+//!
+//! Enabled by the `wasm` feature. In a browser or node, load the library
+//! with wasm-bindgen and call the exported [`run_csv`] (wasm-bindgen is a
+//! dependency on wasm32 targets only):
+//!
+//! ```text
+//! cargo build --release --lib --target wasm32-unknown-unknown --no-default-features --features wasm
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/roaring_bench.wasm
+//! ```
+//!
+//! Under wasmtime, run the `wasm-bench` binary built for WASI, which prints
+//! the same CSV - as it does when run natively, giving the native results
+//! to compare against:
+//!
+//! ```text
+//! cargo build --release --bin wasm-bench --target wasm32-wasip1 --no-default-features --features wasm
+//! wasmtime target/wasm32-wasip1/release/wasm-bench.wasm > wasm.csv
+//! cargo run --release --bin wasm-bench --no-default-features --features wasm > native.csv
+//! ```
+//!
+//! The cases share the names and pooled inputs of the criterion groups they
+//! mirror. Each is timed by [`SAMPLES`] samples of a number of iterations
+//! doubled until a sample takes [`SAMPLE_TIME_NS`] (browsers coarsen their
+//! clock), and reports the median, fastest and slowest sample as the median
+//! and its bounds. The cases run as a benchmark only, with no `wasm-pack
+//! test` harness: running `wasm-bench` natively, as above, exercises the
+//! same code without a WebAssembly runtime.
+
+use std::hint::black_box;
+
+use rand::Rng;
+use roaring::RoaringBitmap;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::{
+    export::{self, Row},
+    gen::{self, Distribution},
+    pool::{self, Workload},
+    N,
+};
+
+/// The number of timed samples of each case.
+pub const SAMPLES: usize = 10;
+
+/// The shortest time a sample may take, in nanoseconds.
+pub const SAMPLE_TIME_NS: f64 = 10e6;
+
+/// The number of lookups per iteration of the contains case, as in the
+/// criterion group.
+const QUERIES: usize = 1_024;
+
+/// Run every case, returning one row per (case, size).
+pub fn run() -> Vec<Row> {
+    let mut rows = Vec::new();
+    for distribution in Distribution::ALL {
+        for &n in N.iter() {
+            let values = pool::get(generated(distribution), n).values();
+            let mut bm = RoaringBitmap::new();
+            rows.push(measure("add_elements", Some(distribution), n, || {
+                for &v in values {
+                    bm.insert(v);
+                }
+            }));
+        }
+    }
+
+    let mut rng = gen::rng("wasm/contains_mixed");
+    for &n in N.iter() {
+        let bm = pool::get(Workload::Even, n).roaring();
+        let queries = (0..QUERIES)
+            .map(|_| rng.gen_range(0..n) * 2 + rng.gen_range(0..=1))
+            .collect::<Vec<u32>>();
+        rows.push(measure("contains_mixed", None, n, || {
+            black_box(queries.iter().filter(|v| bm.contains(**v)).count());
+        }));
+    }
+
+    for &n in N.iter() {
+        let a = pool::get(Workload::AlternateEven, n).roaring();
+        let b = pool::get(Workload::AlternateOverlap(50), n).roaring();
+        rows.push(measure("union_no_rle_overlap_50", None, n, || {
+            black_box(a | b);
+        }));
+    }
+
+    for (input, workload) in [
+        ("sparse", generated(Distribution::UniformSparse)),
+        ("dense", Workload::AlternateEven),
+        ("run_heavy", generated(Distribution::RunHeavy)),
+    ] {
+        for &n in N.iter() {
+            let mut bytes = Vec::new();
+            pool::get(workload, n)
+                .roaring()
+                .serialize_into(&mut bytes)
+                .unwrap();
+            rows.push(measure(&format!("deserialize_{input}"), None, n, || {
+                black_box(RoaringBitmap::deserialize_from(bytes.as_slice()).unwrap());
+            }));
+        }
+    }
+    rows
+}
+
+/// [`run`], rendered as the CSV written by `roaring-bench export`.
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen)]
+pub fn run_csv() -> String {
+    export::csv(&run())
+}
+
+/// The pooled input the criterion groups draw from `distribution`.
+fn generated(distribution: Distribution) -> Workload {
+    Workload::Generated {
+        distribution,
+        instance: 0,
+    }
+}
+
+/// Time `f`, returning its row.
+fn measure(
+    operation: &str,
+    distribution: Option<Distribution>,
+    n: u32,
+    mut f: impl FnMut(),
+) -> Row {
+    let mut sample = |iterations: u64| {
+        let start = now_ns();
+        for _ in 0..iterations {
+            f();
+        }
+        (now_ns() - start) / iterations as f64
+    };
+
+    let mut iterations = 1;
+    while sample(iterations) * (iterations as f64) < SAMPLE_TIME_NS && iterations < 1 << 30 {
+        iterations *= 2;
+    }
+    let mut samples = (0..SAMPLES).map(|_| sample(iterations)).collect::<Vec<_>>();
+    samples.sort_by(f64::total_cmp);
+
+    Row {
+        operation: operation.to_string(),
+        distribution,
+        backend: "roaring".to_string(),
+        n: Some(n.to_string()),
+        median_ns: samples[SAMPLES / 2],
+        median_lower_ns: samples[0],
+        median_upper_ns: samples[SAMPLES - 1],
+        mean_ns: samples.iter().sum::<f64>() / SAMPLES as f64,
+    }
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[wasm_bindgen]
+extern "C" {
+    /// `performance.now()`, in milliseconds, present in browsers and node.
+    #[wasm_bindgen(js_namespace = performance, js_name = now)]
+    fn performance_now() -> f64;
+}
+
+/// The time since an arbitrary fixed point, in nanoseconds.
+///
+/// `std::time::Instant` panics on wasm32-unknown-unknown, which has no
+/// clock of its own, so the host's is used there.
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_ns() -> f64 {
+    performance_now() * 1e6
+}
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn now_ns() -> f64 {
+    use std::{sync::OnceLock, time::Instant};
+
+    static START: OnceLock<Instant> = OnceLock::new();
+    START.get_or_init(Instant::now).elapsed().as_nanos() as f64
+}
@@ -0,0 +1,277 @@
+//! Checks that croaring and roaring agree on the result of every operation
+//! the benchmarks compare, so that no group times one library doing
+//! different work from the other.
+//!
+//! Each property runs against [`CASES`] generated inputs: a seeded random
+//! size, density and layout drawn from the benchmark generators, so the
+//! properties see the shapes of input the benchmarks time, preceded by a
+//! few fixed edge cases. A failure names the seed stream of its input, so
+//! it reproduces with the same seed.
+
+#![cfg(all(feature = "croaring", feature = "roaring"))]
+
+use croaring::Bitmap;
+use rand::{rngs::StdRng, Rng};
+use roaring::RoaringBitmap;
+use roaring_bench::{
+    bitmap_impl::BitmapImpl,
+    gen::{self, Distribution},
+};
+
+/// The number of generated inputs each property is checked against.
+const CASES: usize = 64;
+
+/// The largest generated set.
+const MAX_SIZE: u32 = 20_000;
+
+/// One input set, held by both libraries.
+struct Input {
+    values: Vec<u32>,
+    croaring: Bitmap,
+    roaring: RoaringBitmap,
+}
+
+impl Input {
+    fn new(values: Vec<u32>) -> Self {
+        Self {
+            croaring: Bitmap::of(&values),
+            roaring: values.iter().copied().collect(),
+            values,
+        }
+    }
+
+    /// Run-optimize the croaring bitmap, so the run containers are checked
+    /// too.
+    fn run_optimized(mut self) -> Self {
+        self.croaring.run_optimize();
+        self
+    }
+}
+
+/// Generate a set of random size, density and layout.
+fn generate(rng: &mut StdRng) -> Vec<u32> {
+    let size = rng.gen_range(0..=MAX_SIZE);
+    match rng.gen_range(0..Distribution::ALL.len() + 2) {
+        i if i < Distribution::ALL.len() => {
+            let density = [0.001, 0.01, 0.1, 0.5, 1.0][rng.gen_range(0..5)];
+            gen::generate(Distribution::ALL[i], size, density, rng)
+        }
+        i if i == Distribution::ALL.len() => {
+            let k = gen::STRIDES[rng.gen_range(0..gen::STRIDES.len())];
+            gen::stride(k, size.saturating_mul(k))
+        }
+        // The top of the u32 range, where off-by-one errors overflow.
+        _ => (u32::MAX - size..=u32::MAX).collect(),
+    }
+}
+
+/// The fixed edge cases each property starts with, followed by [`CASES`]
+/// pairs of generated inputs, named by their seed stream.
+fn pairs(property: &str) -> Vec<(String, Input, Input)> {
+    let edges = vec![
+        (vec![], vec![]),
+        (vec![0], vec![]),
+        (vec![u32::MAX], vec![0, u32::MAX]),
+        ((0..1 << 16).collect(), (1 << 15..1 << 17).collect()),
+    ];
+    let mut pairs = edges
+        .into_iter()
+        .enumerate()
+        .map(|(i, (a, b))| (format!("{property}/edge/{i}"), Input::new(a), Input::new(b)))
+        .collect::<Vec<_>>();
+    for i in 0..CASES {
+        let stream = format!("equivalence/{property}/{i}");
+        let mut rng = gen::rng(&stream);
+        let a = Input::new(generate(&mut rng));
+        let b = Input::new(generate(&mut rng));
+        let (a, b) = if rng.gen() {
+            (a.run_optimized(), b.run_optimized())
+        } else {
+            (a, b)
+        };
+        pairs.push((stream, a, b));
+    }
+    pairs
+}
+
+#[track_caller]
+fn assert_same(case: &str, croaring: &Bitmap, roaring: &RoaringBitmap) {
+    let roaring = roaring.iter().collect::<Vec<_>>();
+    assert_eq!(croaring.to_vec(), roaring, "{}", case);
+}
+
+#[test]
+fn construction() {
+    for (case, a, _) in pairs("construction") {
+        assert_same(&case, &a.croaring, &a.roaring);
+        assert_eq!(a.croaring.to_vec(), a.values, "{}", case);
+
+        let croaring = Bitmap::of(&a.values);
+        let roaring = RoaringBitmap::from_sorted_iter(a.values.iter().copied()).unwrap();
+        assert_same(&case, &croaring, &roaring);
+    }
+}
+
+#[test]
+fn set_operations() {
+    for (case, a, b) in pairs("set_operations") {
+        let (ca, cb, ra, rb) = (&a.croaring, &b.croaring, &a.roaring, &b.roaring);
+        assert_same(&format!("{case} and"), &ca.and(cb), &(ra & rb));
+        assert_same(&format!("{case} or"), &ca.or(cb), &(ra | rb));
+        assert_same(&format!("{case} andnot"), &ca.andnot(cb), &(ra - rb));
+        assert_same(&format!("{case} xor"), &ca.xor(cb), &(ra ^ rb));
+
+        let (mut c, mut r) = (ca.clone(), ra.clone());
+        c.and_inplace(cb);
+        r &= rb;
+        assert_same(&format!("{case} and_inplace"), &c, &r);
+        let (mut c, mut r) = (ca.clone(), ra.clone());
+        c.or_inplace(cb);
+        r |= rb;
+        assert_same(&format!("{case} or_inplace"), &c, &r);
+        let (mut c, mut r) = (ca.clone(), ra.clone());
+        c.andnot_inplace(cb);
+        r -= rb;
+        assert_same(&format!("{case} andnot_inplace"), &c, &r);
+        let (mut c, mut r) = (ca.clone(), ra.clone());
+        c.xor_inplace(cb);
+        r ^= rb;
+        assert_same(&format!("{case} xor_inplace"), &c, &r);
+    }
+}
+
+#[test]
+fn cardinalities_and_predicates() {
+    for (case, a, b) in pairs("cardinalities_and_predicates") {
+        let (ca, cb, ra, rb) = (&a.croaring, &b.croaring, &a.roaring, &b.roaring);
+        assert_eq!(ca.and_cardinality(cb), ra.intersection_len(rb), "{}", case);
+        assert_eq!(ca.or_cardinality(cb), ra.union_len(rb), "{}", case);
+        assert_eq!(ca.andnot_cardinality(cb), ra.difference_len(rb), "{}", case);
+        assert_eq!(
+            ca.xor_cardinality(cb),
+            ra.symmetric_difference_len(rb),
+            "{}",
+            case
+        );
+        assert_eq!(ca.is_subset(cb), ra.is_subset(rb), "{}", case);
+        assert_eq!(cb.is_subset(ca), ra.is_superset(rb), "{}", case);
+        assert_eq!(!ca.intersect(cb), ra.is_disjoint(rb), "{}", case);
+        assert_eq!(ca == cb, ra == rb, "{}", case);
+    }
+}
+
+#[test]
+fn accessors() {
+    for (case, a, _) in pairs("accessors") {
+        let (c, r) = (&a.croaring, &a.roaring);
+        assert_eq!(c.cardinality(), r.len(), "{}", case);
+        assert_eq!(c.is_empty(), r.is_empty(), "{}", case);
+        assert_eq!(c.minimum(), r.min(), "{}", case);
+        assert_eq!(c.maximum(), r.max(), "{}", case);
+    }
+}
+
+#[test]
+fn queries() {
+    for (case, a, b) in pairs("queries") {
+        let (c, r) = (&a.croaring, &a.roaring);
+        let mut rng = gen::rng(&format!("{case}/queries"));
+        let probes = b.values.iter().copied().chain(a.values.iter().copied());
+        for v in probes.chain([0, u32::MAX]).take(2_000) {
+            assert_eq!(c.contains(v), r.contains(v), "{} contains {}", case, v);
+            assert_eq!(c.rank(v), r.rank(v), "{} rank {}", case, v);
+        }
+        for n in 0..=a.values.len().min(1_000) as u32 {
+            assert_eq!(c.select(n), r.select(n), "{} select {}", case, n);
+        }
+        for _ in 0..100 {
+            let lo = rng.gen::<u32>() >> rng.gen_range(0..32);
+            let hi = lo.saturating_add(rng.gen_range(0..1 << 18));
+            assert_eq!(
+                c.range_cardinality(lo..hi),
+                r.range_cardinality(lo..hi),
+                "{} range_cardinality {}..{}",
+                case,
+                lo,
+                hi
+            );
+            assert_eq!(
+                c.contains_range(lo..hi),
+                r.contains_range(lo..hi),
+                "{} contains_range {}..{}",
+                case,
+                lo,
+                hi
+            );
+        }
+    }
+}
+
+#[test]
+fn iteration() {
+    for (case, a, _) in pairs("iteration") {
+        let (c, r) = (&a.croaring, &a.roaring);
+        assert_eq!(c.iter().collect::<Vec<_>>(), a.values, "{}", case);
+        assert_eq!(r.iter().collect::<Vec<_>>(), a.values, "{}", case);
+        let reversed = a.values.iter().rev().copied().collect::<Vec<_>>();
+        assert_eq!(r.iter().rev().collect::<Vec<_>>(), reversed, "{}", case);
+        // CRoaring's reverse iterator, driven by the BitmapImpl adapter.
+        assert_eq!(c.iter_rev().collect::<Vec<_>>(), reversed, "{}", case);
+        let mid = a.values.len() / 2;
+        assert_eq!(c.iter().nth(mid), r.iter().nth(mid), "{}", case);
+    }
+}
+
+#[test]
+fn mutation() {
+    for (case, a, b) in pairs("mutation") {
+        let mut rng = gen::rng(&format!("{case}/mutation"));
+        let (mut c, mut r) = (a.croaring.clone(), a.roaring.clone());
+        for &v in &b.values {
+            c.add(v);
+            r.insert(v);
+        }
+        assert_same(&format!("{case} insert"), &c, &r);
+        for &v in b.values.iter().step_by(2) {
+            c.remove(v);
+            r.remove(v);
+        }
+        assert_same(&format!("{case} remove"), &c, &r);
+
+        let lo = rng.gen::<u32>() >> rng.gen_range(0..32);
+        let hi = lo.saturating_add(rng.gen_range(0..1 << 18));
+        c.add_range(lo..hi);
+        r.insert_range(lo..hi);
+        assert_same(&format!("{case} insert_range {lo}..{hi}"), &c, &r);
+        // Overlapping the inserted range, and the values below it.
+        let lo = lo.saturating_sub(1 << 16);
+        c.remove_range(lo..hi);
+        r.remove_range(lo..hi);
+        assert_same(&format!("{case} remove_range {lo}..{hi}"), &c, &r);
+
+        // roaring has no flip, so the benchmarks XOR with the range instead.
+        let mut range = RoaringBitmap::new();
+        range.insert_range(lo..hi);
+        assert_same(
+            &format!("{case} flip {lo}..{hi}"),
+            &a.croaring.flip(lo..hi),
+            &(&a.roaring ^ &range),
+        );
+    }
+}
+
+#[test]
+fn serialization() {
+    for (case, a, _) in pairs("serialization") {
+        let mut roaring_bytes = Vec::new();
+        a.roaring.serialize_into(&mut roaring_bytes).unwrap();
+        let c = Bitmap::try_deserialize(&roaring_bytes).unwrap();
+        assert_same(&format!("{case} roaring to croaring"), &c, &a.roaring);
+
+        // roaring 0.10 cannot read run containers.
+        let mut plain = a.croaring.clone();
+        plain.remove_run_compression();
+        let r = RoaringBitmap::deserialize_from(plain.serialize().as_slice()).unwrap();
+        assert_same(&format!("{case} croaring to roaring"), &a.croaring, &r);
+    }
+}